@@ -0,0 +1,9 @@
+return {
+	on_tick = function(_, action, var)
+		action.apply_damage({
+			target = var.target.entity,
+			damage = 2 * var.stacks,
+			kind = "physical",
+		})
+	end,
+}
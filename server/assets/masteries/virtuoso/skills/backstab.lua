@@ -0,0 +1,44 @@
+return {
+	on_use = function(_, action, var)
+		action.combat_log({
+			source = var.source.entity,
+			target = var.source.entity,
+			used = { message = "You drive your weapon at their back." },
+		})
+	end,
+
+	on_dodge = function(_, action, var)
+		action.combat_log({
+			source = var.target.entity,
+			target = var.source.entity,
+			dodged = { message = "They twist away from your backstab." },
+		})
+
+		action.combat_log({
+			source = var.source.entity,
+			target = var.target.entity,
+			dodged = { message = "You twist away from their backstab." },
+		})
+	end,
+
+	on_hit = function(_, action, var)
+		action.apply_damage({
+			target = var.target.entity,
+			damage = var.source.stats:auto_attack_damage(var.source.weapon) * 2,
+			kind = var.source.weapon and var.source.weapon.kind or "physical",
+			after = function(damage, kind, _)
+				action.combat_log({
+					source = var.source.entity,
+					target = var.source.entity,
+					damaged = { message = "Your backstab lands!", damage = damage, kind = kind },
+				})
+
+				action.combat_log({
+					source = var.source.entity,
+					target = var.target.entity,
+					damaged = { message = "Their backstab lands!", damage = damage, kind = kind },
+				})
+			end,
+		})
+	end,
+}
@@ -0,0 +1,22 @@
+return {
+	on_block_reaction = function(_, action, var)
+		action.apply_damage({
+			target = var.target.entity,
+			damage = var.source.stats:auto_attack_damage(var.source.weapon),
+			kind = var.source.weapon and var.source.weapon.kind or "physical",
+			after = function(damage, kind, _)
+				action.combat_log({
+					source = var.source.entity,
+					target = var.source.entity,
+					damaged = { message = "You riposte!", damage = damage, kind = kind },
+				})
+
+				action.combat_log({
+					source = var.source.entity,
+					target = var.target.entity,
+					damaged = { message = "They riposte!", damage = damage, kind = kind },
+				})
+			end,
+		})
+	end,
+}
@@ -0,0 +1,41 @@
+return {
+	on_use = function(_, action, var)
+		action.set_approach({
+			target = var.source.entity,
+			approach = var.approach.Rear,
+		})
+
+		action.combat_log({
+			source = var.source.entity,
+			target = var.source.entity,
+			used = { message = "You slip around behind them." },
+		})
+
+		action.combat_log({
+			source = var.source.entity,
+			target = var.target.entity,
+			used = { message = "They slip around behind you." },
+		})
+	end,
+
+	on_hit = function(_, action, var)
+		action.apply_damage({
+			target = var.target.entity,
+			damage = var.source.stats:auto_attack_damage(var.source.weapon) / 2,
+			kind = var.source.weapon and var.source.weapon.kind or "physical",
+			after = function(damage, kind, _)
+				action.combat_log({
+					source = var.source.entity,
+					target = var.source.entity,
+					damaged = { message = "You clip them as you pass.", damage = damage, kind = kind },
+				})
+
+				action.combat_log({
+					source = var.source.entity,
+					target = var.target.entity,
+					damaged = { message = "They clip you as they pass.", damage = damage, kind = kind },
+				})
+			end,
+		})
+	end,
+}
@@ -38,8 +38,8 @@ return {
 	on_hit = function(_, action, var)
 		action.apply_damage({
 			target = var.target.entity,
-			damage = var.source.stats:auto_attack_damage(),
-			kind = "physical",
+			damage = var.source.stats:auto_attack_damage(var.source.weapon),
+			kind = var.source.weapon and var.source.weapon.kind or "physical",
 			after = function(damage, kind, _)
 				action.combat_log({
 					source = var.source.entity,
@@ -0,0 +1,44 @@
+return {
+	on_use = function(_, action, var)
+		action.combat_log({
+			source = var.source.entity,
+			target = var.source.entity,
+			used = { message = "You send a knife spinning through the air." },
+		})
+	end,
+
+	on_dodge = function(_, action, var)
+		action.combat_log({
+			source = var.target.entity,
+			target = var.source.entity,
+			dodged = { message = "They duck under your knife." },
+		})
+
+		action.combat_log({
+			source = var.source.entity,
+			target = var.target.entity,
+			dodged = { message = "You duck under their knife." },
+		})
+	end,
+
+	on_hit = function(_, action, var)
+		action.apply_damage({
+			target = var.target.entity,
+			damage = var.source.stats:auto_attack_damage() * 1.5,
+			kind = "physical",
+			after = function(damage, kind, _)
+				action.combat_log({
+					source = var.source.entity,
+					target = var.source.entity,
+					damaged = { message = "Your knife finds its mark!", damage = damage, kind = kind },
+				})
+
+				action.combat_log({
+					source = var.source.entity,
+					target = var.target.entity,
+					damaged = { message = "Their knife finds its mark!", damage = damage, kind = kind },
+				})
+			end,
+		})
+	end,
+}
@@ -1,4 +1,4 @@
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
 
 use anyhow::Context;
 use bevy::{
@@ -14,14 +14,17 @@ use regex::Regex;
 use sqlx::{types::Json, Pool, Postgres};
 
 use crate::{
-    combat::{bundles::CombatBundle, components::Stats},
-    data::resources::Masteries,
+    combat::{
+        bundles::CombatBundle,
+        components::{CombatMeter, LearnedSkills, Stats},
+    },
+    data::resources::{Balance, Masteries},
     db::{
         models::{CharacterModel, Role},
         pool::DatabasePool,
     },
     input::events::{Command, ParsedCommand, ProxyCommand},
-    items::components::Inventory,
+    items::components::{Equipment, Inventory},
     keycard::Keycard,
     paint,
     player::{
@@ -202,6 +205,7 @@ pub fn handle_authenticate_task(
     tiles: Query<(Entity, &Name), With<Tile>>,
     world_state: Res<WorldState>,
     masteries: Res<Masteries>,
+    balance: Res<Balance>,
 ) -> Result<(), anyhow::Error> {
     for (task_entity, mut task) in &mut tasks {
         if let Some(Ok((character_model, client_id))) =
@@ -227,7 +231,10 @@ pub fn handle_authenticate_task(
                     server.disconnect(&online.id);
                 }
 
-                let mut player_stats = Stats::default();
+                let mut player_stats = Stats {
+                    balance: Arc::new(balance.clone()),
+                    ..Default::default()
+                };
                 let mastery = masteries
                     .0
                     .get(&character.mastery)
@@ -241,6 +248,34 @@ pub fn handle_authenticate_task(
                 player_stats.status.health = player_stats.max_health();
                 player_stats.status.vigor = player_stats.max_vigor();
 
+                let character_in_state =
+                    world_state.characters.iter().find(|c| c.id == character.id);
+
+                let mut combat = CombatBundle {
+                    stats: player_stats,
+                    ..Default::default()
+                };
+
+                if let Some(saved) = character_in_state.and_then(|c| c.combat.as_ref()) {
+                    saved.restore(
+                        player_entity,
+                        &mut combat.stats,
+                        &mut combat.conditions,
+                        &mut combat.modifiers,
+                        &mut combat.cooldowns,
+                    );
+                }
+
+                let equipment = Equipment(
+                    character_in_state
+                        .iter()
+                        .flat_map(|c| c.equipment.iter())
+                        .map(|(slot, item)| (*slot, item.spawn(&mut proto)))
+                        .collect(),
+                );
+
+                let equipped = equipment.0.values().copied().collect::<Vec<_>>();
+
                 bevy.entity(player_entity)
                     .remove::<Authenticating>()
                     .insert((
@@ -257,18 +292,20 @@ pub fn handle_authenticate_task(
                                 mastery: character.mastery,
                                 name: character.name,
                             },
-                            combat: CombatBundle {
-                                stats: player_stats.clone(),
-                                ..Default::default()
-                            },
+                            combat,
+                            meter: CombatMeter::default(),
+                            skills: LearnedSkills(
+                                character_in_state
+                                    .map(|c| c.skills.iter().cloned().collect())
+                                    .unwrap_or_default(),
+                            ),
+                            equipment,
                         },
-                    ));
+                    ))
+                    .push_children(&equipped);
 
                 let spawn = spawn_tiles.iter().next().context("Spawn tile not found")?;
 
-                let character_in_state =
-                    world_state.characters.iter().find(|c| c.id == character.id);
-
                 if let Some(character_in_state) = character_in_state {
                     let tile = tiles
                         .iter()
@@ -284,10 +321,8 @@ pub fn handle_authenticate_task(
                         .with_children(|parent| {
                             let mut inventory = parent.spawn(Inventory);
 
-                            for item_name in character_in_state.inventory.iter() {
-                                inventory.add_child(
-                                    proto.spawn(item_name.trim_end_matches(" (Prototype)")).id(),
-                                );
+                            for item in character_in_state.inventory.iter() {
+                                inventory.add_child(item.spawn(&mut proto));
                             }
                         });
                 } else {
@@ -0,0 +1,617 @@
+//! Runs headless fights between a character and a hostile through the real
+//! combat, Lua and hostile AI systems, then reports how they went.
+//!
+//! ```sh
+//! cargo run --release --bin simulate -- virtuoso 3 racoon 500 [seed]
+//! ```
+//!
+//! The seed feeds every roll, but it doesn't make a run repeatable: system
+//! order and map iteration still change from one run to the next, and with
+//! them which roll goes where. Compare runs with plenty of fights instead.
+
+use std::{
+    env,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context};
+use ascii_table::AsciiTable;
+use bevy::{
+    asset::AssetPlugin,
+    ecs::system::SystemState,
+    log::{Level, LogPlugin},
+    prelude::*,
+    time::{TimePlugin, TimeUpdateStrategy},
+};
+use bevy_nest::prelude::*;
+use bevy_proto::prelude::*;
+
+use server::{
+    combat::{
+        bundles::CombatBundle,
+        components::{
+            CombatMeter, CombatState, Cooldowns, Distance, LearnedSkills, QueuedAttack, Stats,
+        },
+        events::{CombatEvent, CombatEventKind, CombatEventTrigger, DamageEvent},
+        plugin::CombatPlugin,
+    },
+    data::{
+        plugin::DataPlugin,
+        resources::{Masteries, Skills},
+    },
+    input::events::{Command, ParsedCommand, ProxyCommand},
+    interact::components::{Interaction, Interactions},
+    items::components::Equipment,
+    keycard::Keycard,
+    lua::{events::ExecutionPhase, plugin::LuaPlugin},
+    npc::plugin::NpcPlugin,
+    player::{
+        bundles::PlayerBundle,
+        components::{Character, Client, Online},
+        config::CharacterConfig,
+        events::Prompt,
+    },
+    spatial::{
+        bundles::TileBundle,
+        components::{Position, Tile, Zone},
+        events::MovementEvent,
+    },
+    visual::{
+        components::{Depiction, Sprite},
+        plugin::VisualPlugin,
+    },
+    world::resources::GameRng,
+};
+
+/// How much game time passes with each update.
+const TICK: f32 = 0.05;
+
+/// Fights still going after this many seconds are called off.
+const MAX_DURATION: f32 = 300.0;
+
+/// How long the simulated player waits between skills, so commands aren't sent
+/// faster than a person could type them.
+const REACTION_TIME: f32 = 0.5;
+
+/// How long to wait for prototypes to finish loading.
+const LOAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Options {
+    mastery: String,
+    level: u32,
+    hostile: String,
+    fights: u32,
+    seed: u64,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, anyhow::Error> {
+        let usage = "Usage: simulate <mastery> <level> <hostile> [fights] [seed]";
+
+        let mastery = args.next().context(usage)?;
+        let level = args.next().context(usage)?.parse().context(usage)?;
+        let hostile = args.next().context(usage)?;
+        let fights = args.next().map(|n| n.parse()).transpose()?.unwrap_or(100);
+        let seed = args
+            .next()
+            .map(|n| n.parse())
+            .transpose()?
+            .unwrap_or_else(rand::random);
+
+        let hostile = if hostile.starts_with("hostiles.") {
+            hostile
+        } else {
+            format!("hostiles.{hostile}")
+        };
+
+        Ok(Self {
+            mastery,
+            level,
+            hostile,
+            fights,
+            seed,
+        })
+    }
+}
+
+/// What one side of a fight managed.
+#[derive(Default, Clone)]
+struct Tally {
+    missed: u32,
+    dodged: u32,
+    blocked: u32,
+    hit: u32,
+    damage: u32,
+    damage_instances: u32,
+    crits: u32,
+}
+
+impl Tally {
+    fn swings(&self) -> u32 {
+        self.missed + self.dodged + self.blocked + self.hit
+    }
+
+    fn add(&mut self, other: &Tally) {
+        self.missed += other.missed;
+        self.dodged += other.dodged;
+        self.blocked += other.blocked;
+        self.hit += other.hit;
+        self.damage += other.damage;
+        self.damage_instances += other.damage_instances;
+        self.crits += other.crits;
+    }
+}
+
+#[derive(Resource, Default)]
+struct Observed {
+    player: Tally,
+    hostile: Tally,
+    died: bool,
+}
+
+#[derive(PartialEq)]
+enum Outcome {
+    Won,
+    Lost,
+    TimedOut,
+}
+
+struct Fight {
+    outcome: Outcome,
+    duration: f32,
+    player: Tally,
+    hostile: Tally,
+}
+
+/// Tallies each side's swings and damage from the events combat already sends.
+fn observe(
+    mut combat_events: EventReader<CombatEvent>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut observed: ResMut<Observed>,
+    characters: Query<(), With<Character>>,
+) {
+    for event in combat_events.iter() {
+        if let CombatEventTrigger::Death = event.trigger {
+            if characters.contains(event.source) {
+                observed.died = true;
+            }
+        }
+
+        let (CombatEventKind::ExecuteScripts(phase), CombatEventTrigger::Skill(_)) =
+            (&event.kind, &event.trigger)
+        else {
+            continue;
+        };
+
+        let tally = if characters.contains(event.source) {
+            &mut observed.player
+        } else {
+            &mut observed.hostile
+        };
+
+        match phase {
+            ExecutionPhase::OnMiss => tally.missed += 1,
+            ExecutionPhase::OnDodge => tally.dodged += 1,
+            ExecutionPhase::OnBlock => tally.blocked += 1,
+            ExecutionPhase::OnHit => tally.hit += 1,
+            _ => (),
+        }
+    }
+
+    for event in damage_events.iter() {
+        let tally = if characters.contains(event.source) {
+            &mut observed.player
+        } else {
+            &mut observed.hostile
+        };
+
+        tally.damage += event.damage;
+        tally.damage_instances += 1;
+
+        if event.crit {
+            tally.crits += 1;
+        }
+    }
+}
+
+/// Keeps within reach of the auto attack and uses the first skill learned from
+/// the mastery's tree that's ready, the way a player working through their rotation would.
+/// Auto attacks take care of the rest.
+fn use_skills(
+    mut commands: EventWriter<ParsedCommand>,
+    mut since: Local<f32>,
+    players: Query<(
+        &Client,
+        &Character,
+        &Stats,
+        &Cooldowns,
+        &LearnedSkills,
+        &CombatState,
+        Option<&QueuedAttack>,
+    )>,
+    masteries: Res<Masteries>,
+    skills: Res<Skills>,
+    time: Res<Time>,
+) {
+    *since += time.delta_seconds();
+
+    if *since < REACTION_TIME {
+        return;
+    }
+
+    for (client, character, stats, cooldowns, learned, combat_state, queued_attack) in
+        players.iter()
+    {
+        let Some(mastery) = masteries.0.get(&character.mastery) else {
+            continue;
+        };
+
+        let reach = skills
+            .0
+            .get(&mastery.auto_attack)
+            .map(|skill| skill.distance)
+            .filter(|distance| *distance != Distance::Either);
+
+        if let Some(distance) = reach.filter(|distance| *distance != combat_state.distance) {
+            let command = match distance {
+                Distance::Far => Command::Retreat,
+                _ => Command::Advance,
+            };
+
+            commands.send(ParsedCommand {
+                from: client.id,
+                command,
+            });
+
+            *since = 0.0;
+
+            continue;
+        }
+
+        if queued_attack.is_some() {
+            continue;
+        }
+
+        let ready = mastery
+            .skills
+            .iter()
+            .filter(|node| learned.0.contains(&node.skill))
+            .filter_map(|node| skills.0.get(&node.skill))
+            .find(|skill| {
+                !skill.commands.is_empty()
+                    && skill.ammo.is_none()
+                    && skill.reaches(combat_state.distance)
+                    && stats.status.vigor >= skill.cost
+                    && !cooldowns.0.contains_key(&skill.id)
+                    && skill.approach.allows(combat_state.approach)
+            });
+
+        if let Some(skill) = ready {
+            commands.send(ParsedCommand {
+                from: client.id,
+                command: Command::UseSkill((skill.commands[0].clone(), None)),
+            });
+
+            *since = 0.0;
+        }
+    }
+}
+
+fn build_app(seed: u64) -> App {
+    let mut app = App::new();
+
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+        TICK,
+    )))
+    .insert_resource(GameRng::seeded(seed))
+    .init_resource::<Observed>()
+    .add_plugins((
+        AssetPlugin::default(),
+        LogPlugin {
+            level: Level::WARN,
+            ..Default::default()
+        },
+        TaskPoolPlugin::default(),
+        TypeRegistrationPlugin,
+        TimePlugin,
+    ))
+    .add_plugins((NestPlugin, ProtoPlugin::new()))
+    .add_plugins((CombatPlugin, DataPlugin, LuaPlugin, NpcPlugin, VisualPlugin))
+    .register_type::<Vec<String>>()
+    .register_type::<Interaction>()
+    .register_type::<Vec<Interaction>>()
+    .register_type::<Interactions>()
+    .add_event::<ParsedCommand>()
+    .add_event::<ProxyCommand>()
+    .add_event::<Prompt>()
+    .add_event::<MovementEvent>()
+    .add_systems(First, use_skills)
+    .add_systems(Last, observe);
+
+    app
+}
+
+fn load_hostile(app: &mut App, hostile: &str) -> Result<(), anyhow::Error> {
+    let mut prototypes = SystemState::<PrototypesMut>::new(&mut app.world);
+
+    prototypes
+        .get_mut(&mut app.world)
+        .load_folder("prototypes/hostiles/")?;
+
+    let started = Instant::now();
+
+    loop {
+        app.update();
+
+        let mut prototypes = SystemState::<Prototypes>::new(&mut app.world);
+
+        if prototypes.get(&app.world).is_ready(hostile) {
+            return Ok(());
+        }
+
+        if started.elapsed() > LOAD_TIMEOUT {
+            bail!("Hostile not found: {hostile}");
+        }
+    }
+}
+
+fn fight(app: &mut App, options: &Options) -> Result<Fight, anyhow::Error> {
+    let masteries = app.world.resource::<Masteries>();
+    let mastery = masteries
+        .0
+        .get(&options.mastery)
+        .with_context(|| format!("Mastery not found: {}", options.mastery))?;
+
+    let mut stats = Stats {
+        level: options.level,
+        ..Default::default()
+    };
+
+    stats.attributes.vitality = mastery.vitality;
+    stats.attributes.stamina = mastery.stamina;
+    stats.attributes.strength = mastery.strength;
+    stats.attributes.dexterity = mastery.dexterity;
+    stats.attributes.intelligence = mastery.intelligence;
+    stats.balance = app
+        .world
+        .resource::<server::data::resources::Balance>()
+        .clone()
+        .into();
+    stats.status.health = stats.max_health();
+    stats.status.vigor = stats.max_vigor();
+
+    *app.world.resource_mut::<Observed>() = Observed::default();
+
+    let zone = app
+        .world
+        .spawn(Zone {
+            name: "Arena".into(),
+        })
+        .id();
+
+    let tile = app
+        .world
+        .spawn(TileBundle {
+            tile: Tile {
+                name: "Arena".into(),
+                description: "A bare patch of ground with nowhere to run.".into(),
+            },
+            sprite: Sprite {
+                character: "x".into(),
+            },
+            position: Position(IVec3::ZERO),
+        })
+        .set_parent(zone)
+        .id();
+
+    let client_id = ClientId::new();
+
+    app.world
+        .spawn((
+            Client {
+                id: client_id,
+                width: 80,
+            },
+            Online,
+            PlayerBundle {
+                keycard: Keycard::player(),
+                character: Character {
+                    id: 0,
+                    name: "Simulant".into(),
+                    description: None,
+                    config: CharacterConfig::default(),
+                    mastery: options.mastery.clone(),
+                },
+                combat: CombatBundle {
+                    stats,
+                    ..Default::default()
+                },
+                meter: CombatMeter::default(),
+                skills: LearnedSkills::default(),
+                equipment: Equipment::default(),
+            },
+        ))
+        .set_parent(tile);
+
+    let mut proto = SystemState::<ProtoCommands>::new(&mut app.world);
+    let hostile = proto.get_mut(&mut app.world).spawn(&options.hostile).id();
+    proto.apply(&mut app.world);
+
+    app.world.entity_mut(hostile).set_parent(tile);
+
+    let target = app
+        .world
+        .get::<Depiction>(hostile)
+        .context("Hostile has no depiction")?
+        .short_name
+        .clone();
+
+    app.world.send_event(ParsedCommand {
+        from: client_id,
+        command: Command::Attack(Some(target)),
+    });
+
+    let mut duration = 0.0;
+
+    let outcome = loop {
+        app.update();
+
+        duration += TICK;
+
+        if app.world.resource::<Observed>().died {
+            break Outcome::Lost;
+        }
+
+        if app
+            .world
+            .get::<Stats>(hostile)
+            .map_or(true, |stats| stats.status.health == 0)
+        {
+            break Outcome::Won;
+        }
+
+        if duration >= MAX_DURATION {
+            break Outcome::TimedOut;
+        }
+    };
+
+    app.world.entity_mut(zone).despawn_recursive();
+
+    // Nothing still in flight should carry over into the next fight.
+    app.world.resource_mut::<Events<CombatEvent>>().clear();
+    app.world.resource_mut::<Events<ParsedCommand>>().clear();
+
+    let observed = app.world.resource::<Observed>();
+
+    Ok(Fight {
+        outcome,
+        duration,
+        player: observed.player.clone(),
+        hostile: observed.hostile.clone(),
+    })
+}
+
+fn percent(part: u32, whole: u32) -> String {
+    if whole == 0 {
+        return "-".into();
+    }
+
+    format!("{:.1}%", part as f32 / whole as f32 * 100.0)
+}
+
+fn report(options: &Options, fights: &[Fight]) {
+    let count = fights.len() as f32;
+    let share = |outcome: Outcome| {
+        let matching = fights.iter().filter(|f| f.outcome == outcome).count();
+
+        format!("{:.1}%", matching as f32 / count * 100.0)
+    };
+
+    let duration: f32 = fights.iter().map(|f| f.duration).sum();
+
+    // Fights that were called off would only drag the average towards the cap.
+    let finished = fights
+        .iter()
+        .filter(|f| f.outcome != Outcome::TimedOut)
+        .map(|f| f.duration)
+        .collect::<Vec<_>>();
+
+    let average = if finished.is_empty() {
+        "-".into()
+    } else {
+        format!(
+            "{:.1}s",
+            finished.iter().sum::<f32>() / finished.len() as f32
+        )
+    };
+
+    let mut player = Tally::default();
+    let mut hostile = Tally::default();
+
+    for fight in fights {
+        player.add(&fight.player);
+        hostile.add(&fight.hostile);
+    }
+
+    println!(
+        "{} (level {}) vs {}, {} fights, seed {}\n",
+        options.mastery,
+        options.level,
+        options.hostile,
+        fights.len(),
+        options.seed
+    );
+
+    println!("Won: {}", share(Outcome::Won));
+    println!("Lost: {}", share(Outcome::Lost));
+    println!("Timed out: {}", share(Outcome::TimedOut));
+    println!("Average duration: {average} (excluding timeouts)\n");
+
+    let mut table = AsciiTable::default();
+    table.column(0).set_header("");
+    table.column(1).set_header(&options.mastery);
+    table.column(2).set_header(&options.hostile);
+
+    let row = |name: &'static str, stat: &dyn Fn(&Tally) -> String| -> Vec<Box<dyn Display>> {
+        vec![
+            Box::new(name),
+            Box::new(stat(&player)),
+            Box::new(stat(&hostile)),
+        ]
+    };
+
+    let rows = vec![
+        row("damage per second", &|t| {
+            format!("{:.1}", t.damage as f32 / duration.max(TICK))
+        }),
+        row("swings", &|t| t.swings().to_string()),
+        row("missed", &|t| percent(t.missed, t.swings())),
+        row("dodged", &|t| percent(t.dodged, t.swings())),
+        row("blocked", &|t| percent(t.blocked, t.swings())),
+        row("crits", &|t| percent(t.crits, t.damage_instances)),
+    ];
+
+    table.print(rows);
+}
+
+fn main() -> Result<(), anyhow::Error> {
+    let options = Options::parse(env::args().skip(1))?;
+
+    let mut app = build_app(options.seed);
+
+    load_hostile(&mut app, &options.hostile)?;
+
+    let fights = (0..options.fights)
+        .map(|_| fight(&mut app, &options))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    report(&options, &fights);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starter_mastery_beats_starter_hostile() {
+        // The matchup designers asked about: the only mastery against the
+        // only hostile, a few levels in.
+        let args = ["virtuoso", "3", "racoon", "3"].map(String::from);
+        let options = Options::parse(args.into_iter()).unwrap();
+
+        let mut app = build_app(options.seed);
+        load_hostile(&mut app, &options.hostile).unwrap();
+
+        let fights = (0..options.fights)
+            .map(|_| fight(&mut app, &options))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let won = fights.iter().filter(|f| f.outcome == Outcome::Won).count();
+
+        assert!(won * 2 > fights.len(), "won {won} of {}", fights.len());
+    }
+}
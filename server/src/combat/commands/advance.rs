@@ -7,9 +7,11 @@ use bevy_nest::prelude::*;
 use regex::Regex;
 
 use crate::{
-    combat::components::{AttackTimer, CombatState, Distance, Stats},
+    combat::{
+        components::{CombatState, Distance},
+        events::{CombatEvent, CombatEventKind, CombatEventTrigger},
+    },
     input::events::{Command, ParseError, ParsedCommand},
-    npc::components::Hostile,
     player::components::{Client, Online},
 };
 
@@ -26,36 +28,113 @@ pub fn handle_advance(content: &str) -> Result<Command, ParseError> {
 
 #[sysfail(log)]
 pub fn advance(
-    mut bevy: Commands,
     mut commands: EventReader<ParsedCommand>,
-    mut players: Query<(Entity, &Client, &Stats, Option<&mut CombatState>), With<Online>>,
-    mut hostiles: Query<&mut CombatState, (With<Hostile>, Without<Online>)>,
+    mut combat_events: EventWriter<CombatEvent>,
+    players: Query<(Entity, &Client, Option<&CombatState>), With<Online>>,
     mut outbox: EventWriter<Outbox>,
 ) -> Result<(), anyhow::Error> {
     for command in commands.iter() {
         if let Command::Advance = &command.command {
-            let (entity, _, stats, combat_state) = players
-                .iter_mut()
-                .find(|(_, c, _, _)| c.id == command.from)
+            let (entity, _, combat_state) = players
+                .iter()
+                .find(|(_, c, _)| c.id == command.from)
                 .context("Player not found")?;
 
-            if let Some(mut combat_state) = combat_state {
-                combat_state.distance = Distance::Near;
+            let Some(combat_state) = combat_state else {
+                outbox.send_text(command.from, "You are not in combat.");
 
-                let mut hostile = hostiles.get_mut(combat_state.target)?;
-                hostile.distance = Distance::Near;
+                continue;
+            };
 
-                bevy.entity(entity).insert(AttackTimer(Timer::from_seconds(
-                    stats.attack_speed(),
-                    TimerMode::Once,
-                )));
+            if combat_state.distance == Distance::Near {
+                outbox.send_text(command.from, "You are already close to your target.");
 
-                outbox.send_text(command.from, "You advance on your target.");
-            } else {
-                outbox.send_text(command.from, "You are not in combat.");
+                continue;
             }
+
+            combat_events.send(CombatEvent {
+                source: entity,
+                trigger: CombatEventTrigger::Movement,
+                kind: CombatEventKind::AttemptReposition(Distance::Near),
+            });
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        combat::{components::Repositioning, systems::on_combat_event_attempt_reposition},
+        test::{
+            app_builder::AppBuilder,
+            npc_builder::NpcBuilder,
+            player_builder::PlayerBuilder,
+            tile_builder::{TileBuilder, ZoneBuilder},
+            utils::{get_message_content, send_message},
+        },
+    };
+
+    #[test]
+    fn starts_moving() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(
+            Update,
+            (advance, on_combat_event_attempt_reposition).chain(),
+        );
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let npc = NpcBuilder::new()
+            .name("Goat")
+            .short_name("goat")
+            .tile(tile)
+            .combat(true)
+            .build(&mut app);
+
+        let (player, client_id, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+
+        app.world
+            .entity_mut(player)
+            .insert(CombatState::new(npc, Distance::Far));
+
+        send_message(&mut app, client_id, "advance");
+        app.update();
+
+        assert_eq!(
+            app.world.get::<Repositioning>(player).unwrap().distance,
+            Distance::Near
+        );
+        assert_eq!(
+            app.world.get::<CombatState>(player).unwrap().distance,
+            Distance::Far
+        );
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "You advance on the goat."
+        );
+    }
+
+    #[test]
+    fn already_near() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, advance);
+
+        let (player, client_id, _) = PlayerBuilder::new().build(&mut app);
+
+        app.world
+            .entity_mut(player)
+            .insert(CombatState::new(Entity::PLACEHOLDER, Distance::Near));
+
+        send_message(&mut app, client_id, "advance");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "You are already close to your target."
+        );
+    }
+}
@@ -9,15 +9,16 @@ use thiserror::Error;
 
 use crate::{
     combat::{
-        components::{Approach, CombatState, Distance, Stats},
+        components::{CombatState, Distance, Duel, Stats},
         events::{CombatEvent, CombatEventKind, CombatEventTrigger},
+        utils::engage,
     },
     data::resources::{Masteries, Skills},
     input::events::{Command, ParseError, ParsedCommand},
     interact::components::{Interaction, Interactions},
     npc::components::Npc,
     player::components::{Character, Client, Online},
-    spatial::components::Tile,
+    spatial::components::{NoPvp, Tile},
     visual::components::Depiction,
 };
 
@@ -39,7 +40,6 @@ pub fn handle_attack(content: &str) -> Result<Command, ParseError> {
 }
 
 #[derive(WorldQuery)]
-#[world_query(mutable)]
 pub struct NpcQuery {
     entity: Entity,
     depiction: &'static Depiction,
@@ -49,13 +49,12 @@ pub struct NpcQuery {
 }
 
 #[derive(WorldQuery)]
-#[world_query(mutable)]
 pub struct PlayerQuery {
     entity: Entity,
     client: &'static Client,
     tile: &'static Parent,
     character: &'static Character,
-    combat_state: Option<&'static CombatState>,
+    duel: Option<&'static Duel>,
     with_online: With<Online>,
     without_npc: Without<Npc>,
 }
@@ -64,6 +63,7 @@ pub struct PlayerQuery {
 pub struct TileQuery {
     entity: Entity,
     children: &'static Children,
+    zone: &'static Parent,
     with_tile: With<Tile>,
 }
 
@@ -74,70 +74,107 @@ pub fn attack(
     mut commands: EventReader<ParsedCommand>,
     mut events: EventWriter<CombatEvent>,
     mut outbox: EventWriter<Outbox>,
-    mut players: Query<PlayerQuery>,
+    mut combat_states: Query<Option<&mut CombatState>>,
+    players: Query<PlayerQuery>,
     npcs: Query<NpcQuery>,
+    no_pvp: Query<(), With<NoPvp>>,
     skills: Res<Skills>,
     tiles: Query<TileQuery>,
 ) -> Result<(), anyhow::Error> {
     for command in commands.iter() {
         if let Command::Attack(target) = &command.command {
             let player = players
-                .iter_mut()
+                .iter()
                 .find(|p| p.client.id == command.from)
                 .context("Player not found")?;
 
-            if player.combat_state.is_some() {
-                outbox.send_text(player.client.id, "You are already in combat.");
+            let Some(target) = target else {
+                if combat_states.get(player.entity)?.is_some() {
+                    outbox.send_text(player.client.id, "You are already in combat.");
+                }
 
                 continue;
-            }
+            };
+
+            let target = match get_target(target, &tiles, &player, &npcs, &players, &no_pvp) {
+                Ok(target) => target,
+                Err(err) => {
+                    outbox.send_text(player.client.id, err.to_string());
+
+                    continue;
+                }
+            };
+
+            let [combat_state, target_combat_state] =
+                combat_states.get_many_mut([player.entity, target.entity])?;
+
+            // Already fighting, so this only changes who we're swinging at.
+            if let Some(mut combat_state) = combat_state {
+                if combat_state.target == target.entity {
+                    outbox.send_text(player.client.id, "You are already in combat.");
+
+                    continue;
+                }
+
+                combat_state.target = target.entity;
+                combat_state.threat.add(target.entity, 0.0);
 
-            if let Some(target) = target {
-                let target = match get_target(target, &tiles, &player.tile.get(), &npcs) {
-                    Ok(entity) => entity,
-                    Err(err) => {
-                        outbox.send_text(player.client.id, err.to_string());
-
-                        continue;
-                    }
-                };
-
-                let skill_id = masteries
-                    .0
-                    .get(&player.character.mastery)
-                    .with_context(|| format!("Mastery not found: {}", player.character.mastery))?
-                    .auto_attack
-                    .clone();
-
-                let skill = skills
-                    .0
-                    .get(&skill_id)
-                    .with_context(|| format!("Auto attack skill not found: {}", skill_id))?;
-
-                bevy.entity(player.entity).insert(CombatState {
-                    target,
-                    distance: Distance::Near,
-                    approach: Approach::Front,
-                });
-
-                bevy.entity(target).insert(CombatState {
-                    target: player.entity,
-                    distance: Distance::Near,
-                    approach: Approach::Front,
-                });
-
-                events.send(CombatEvent {
-                    source: player.entity,
-                    trigger: CombatEventTrigger::Skill(skill.clone()),
-                    kind: CombatEventKind::Attack,
-                });
+                outbox.send_text(
+                    player.client.id,
+                    format!("You turn to face {}.", target.name),
+                );
+
+                engage(
+                    &mut bevy,
+                    target.entity,
+                    target_combat_state,
+                    player.entity,
+                    combat_state.distance,
+                );
+
+                continue;
             }
+
+            let skill_id = masteries
+                .0
+                .get(&player.character.mastery)
+                .with_context(|| format!("Mastery not found: {}", player.character.mastery))?
+                .auto_attack
+                .clone();
+
+            let skill = skills
+                .0
+                .get(&skill_id)
+                .with_context(|| format!("Auto attack skill not found: {}", skill_id))?;
+
+            bevy.entity(player.entity)
+                .insert(CombatState::new(target.entity, Distance::Near));
+
+            engage(
+                &mut bevy,
+                target.entity,
+                target_combat_state,
+                player.entity,
+                Distance::Near,
+            );
+
+            events.send(CombatEvent {
+                source: player.entity,
+                trigger: CombatEventTrigger::Skill(skill.clone()),
+                kind: CombatEventKind::Attack,
+            });
         }
     }
 
     Ok(())
 }
 
+/// Whoever is being attacked, and how we refer to them.
+struct Target {
+    entity: Entity,
+    name: String,
+}
+
 #[derive(Error, Debug, PartialEq)]
 enum TargetError {
     #[error("You don't see anything here.")]
@@ -146,15 +183,61 @@ enum TargetError {
     NotFound(String),
     #[error("You can't attack the {0}.")]
     Invalid(String),
+    #[error("You can't attack {0} here. Challenge them to a duel instead.")]
+    NoPvp(String),
+    #[error("You can't turn on anyone else in the middle of a duel.")]
+    InDuel,
+    #[error("{0} is in the middle of a duel.")]
+    Dueling(String),
 }
 
 fn get_target(
     target: &str,
     tiles: &Query<TileQuery>,
-    tile: &Entity,
+    player: &PlayerQueryItem,
     npcs: &Query<NpcQuery>,
-) -> Result<Entity, TargetError> {
-    let siblings = tiles.get(*tile).ok().ok_or(TargetError::NoTile)?;
+    players: &Query<PlayerQuery>,
+    no_pvp: &Query<(), With<NoPvp>>,
+) -> Result<Target, TargetError> {
+    let siblings = tiles
+        .get(player.tile.get())
+        .ok()
+        .ok_or(TargetError::NoTile)?;
+
+    if let Some(other) = siblings
+        .children
+        .iter()
+        .filter(|sibling| **sibling != player.entity)
+        .filter_map(|sibling| players.get(*sibling).ok())
+        .find(|other| other.character.name.eq_ignore_ascii_case(target))
+    {
+        let name = other.character.name.clone();
+
+        // Whoever we're dueling is always fair game.
+        if player.duel.is_some_and(|duel| duel.0 == other.entity) {
+            return Ok(Target {
+                entity: other.entity,
+                name,
+            });
+        }
+
+        if no_pvp.contains(siblings.zone.get()) {
+            return Err(TargetError::NoPvp(name));
+        }
+
+        if player.duel.is_some() {
+            return Err(TargetError::InDuel);
+        }
+
+        if other.duel.is_some() {
+            return Err(TargetError::Dueling(name));
+        }
+
+        return Ok(Target {
+            entity: other.entity,
+            name,
+        });
+    }
 
     let npc = siblings
         .children
@@ -176,5 +259,82 @@ fn get_target(
         return Err(TargetError::Invalid(target.into()));
     }
 
-    Ok(npc.entity)
+    Ok(Target {
+        entity: npc.entity,
+        name: format!("the {}", npc.depiction.short_name),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{
+        app_builder::AppBuilder,
+        player_builder::PlayerBuilder,
+        tile_builder::{TileBuilder, ZoneBuilder},
+        utils::{get_message_content, send_message},
+    };
+
+    #[test]
+    fn attacks_player() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, attack);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (player, client_id, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+        let (target, _, _) = PlayerBuilder::new().name("Bau").tile(tile).build(&mut app);
+
+        send_message(&mut app, client_id, "attack bau");
+        app.update();
+
+        assert_eq!(app.world.get::<CombatState>(player).unwrap().target, target);
+        assert_eq!(app.world.get::<CombatState>(target).unwrap().target, player);
+    }
+
+    #[test]
+    fn no_pvp_zone() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, attack);
+
+        let zone = ZoneBuilder::new().no_pvp().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (player, client_id, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+        PlayerBuilder::new().name("Bau").tile(tile).build(&mut app);
+
+        send_message(&mut app, client_id, "attack bau");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "You can't attack Bau here. Challenge them to a duel instead."
+        );
+
+        assert!(app.world.get::<CombatState>(player).is_none());
+    }
+
+    #[test]
+    fn duel_opponent_in_no_pvp_zone() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, attack);
+
+        let zone = ZoneBuilder::new().no_pvp().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (player, client_id, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+        let (opponent, _, _) = PlayerBuilder::new().name("Bau").tile(tile).build(&mut app);
+
+        app.world.entity_mut(player).insert(Duel(opponent));
+        app.world.entity_mut(opponent).insert(Duel(player));
+
+        send_message(&mut app, client_id, "attack bau");
+        app.update();
+
+        assert_eq!(
+            app.world.get::<CombatState>(player).unwrap().target,
+            opponent
+        );
+    }
 }
@@ -8,9 +8,9 @@ use regex::Regex;
 
 use crate::{
     combat::components::{BlockCooldown, ManualBlock, Stats},
+    data::resources::Balance,
     input::events::{Command, ParseError, ParsedCommand},
     player::components::{Client, Online},
-    values::MANUAL_BLOCK_TIMER,
 };
 
 static REGEX: OnceLock<Regex> = OnceLock::new();
@@ -30,6 +30,7 @@ pub fn block(
     mut commands: EventReader<ParsedCommand>,
     players: Query<(Entity, &Client, &Stats, Option<&BlockCooldown>), With<Online>>,
     mut outbox: EventWriter<Outbox>,
+    balance: Res<Balance>,
 ) -> Result<(), anyhow::Error> {
     for command in commands.iter() {
         if let Command::Block = &command.command {
@@ -45,7 +46,7 @@ pub fn block(
             }
 
             bevy.entity(entity).insert(ManualBlock(Timer::from_seconds(
-                MANUAL_BLOCK_TIMER,
+                balance.manual_block_timer,
                 TimerMode::Once,
             )));
 
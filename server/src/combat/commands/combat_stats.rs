@@ -0,0 +1,224 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    sync::OnceLock,
+};
+
+use anyhow::Context;
+use ascii_table::AsciiTable;
+use bevy::prelude::*;
+use bevy_mod_sysfail::sysfail;
+use bevy_nest::prelude::*;
+use inflector::Inflector;
+use regex::Regex;
+
+use crate::{
+    combat::components::{CombatMeter, FightStats},
+    input::events::{Command, ParseError, ParsedCommand},
+    player::components::{Client, Online},
+};
+
+static REGEX: OnceLock<Regex> = OnceLock::new();
+
+pub fn handle_combat_stats(content: &str) -> Result<Command, ParseError> {
+    let regex = REGEX.get_or_init(|| Regex::new(r"^(combatstats|cstats)$").unwrap());
+
+    match regex.is_match(content) {
+        false => Err(ParseError::WrongCommand),
+        true => Ok(Command::CombatStats),
+    }
+}
+
+#[sysfail(log)]
+pub fn combat_stats(
+    mut commands: EventReader<ParsedCommand>,
+    mut outbox: EventWriter<Outbox>,
+    players: Query<(&Client, &CombatMeter), With<Online>>,
+) -> Result<(), anyhow::Error> {
+    for command in commands.iter() {
+        if let Command::CombatStats = &command.command {
+            let (client, meter) = players
+                .iter()
+                .find(|(c, _)| c.id == command.from)
+                .context("Player not found")?;
+
+            let (heading, fight) = match (&meter.current, &meter.last) {
+                (Some(fight), _) => ("This fight", fight),
+                (None, Some(fight)) => ("Last fight", fight),
+                (None, None) => {
+                    outbox.send_text(client.id, "You haven't fought anyone yet.");
+
+                    continue;
+                }
+            };
+
+            outbox.send_text(client.id, breakdown(heading, fight));
+        }
+    }
+
+    Ok(())
+}
+
+/// Totals for both sides of the fight, followed by where the damage came from
+/// and what kind it was.
+fn breakdown(heading: &str, fight: &FightStats) -> String {
+    let seconds = fight.duration.max(1.0);
+
+    let per_second = |total: u32| format!("{:.1}", total as f32 / seconds);
+
+    let totals: Vec<(&str, String, String)> = vec![
+        (
+            "damage",
+            fight.dealt.total.to_string(),
+            fight.taken.total.to_string(),
+        ),
+        (
+            "per second",
+            per_second(fight.dealt.total),
+            per_second(fight.taken.total),
+        ),
+        (
+            "hits",
+            fight.dealt.hits.to_string(),
+            fight.taken.hits.to_string(),
+        ),
+        (
+            "crits",
+            fight.dealt.crits.to_string(),
+            fight.taken.crits.to_string(),
+        ),
+        (
+            "misses",
+            fight.dealt.misses.to_string(),
+            fight.taken.misses.to_string(),
+        ),
+        (
+            "dodged",
+            fight.dealt.dodges.to_string(),
+            fight.taken.dodges.to_string(),
+        ),
+        (
+            "blocked",
+            fight.dealt.blocks.to_string(),
+            fight.taken.blocks.to_string(),
+        ),
+    ];
+
+    let mut sections = vec![
+        format!("{heading} ({:.1}s)", fight.duration),
+        table("", &totals),
+    ];
+
+    let by_skill = split(&fight.dealt.by_skill, &fight.taken.by_skill);
+
+    if !by_skill.is_empty() {
+        sections.push(table("skill", &by_skill));
+    }
+
+    let by_kind = split(&fight.dealt.by_kind, &fight.taken.by_kind);
+
+    if !by_kind.is_empty() {
+        sections.push(table("kind", &by_kind));
+    }
+
+    sections.join("\n")
+}
+
+/// Lines up what was dealt against what was taken for every key in either.
+fn split<'a>(
+    dealt: &'a BTreeMap<String, u32>,
+    taken: &'a BTreeMap<String, u32>,
+) -> Vec<(String, String, String)> {
+    dealt
+        .keys()
+        .chain(taken.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|key| {
+            (
+                key.to_title_case(),
+                dealt.get(key).copied().unwrap_or_default().to_string(),
+                taken.get(key).copied().unwrap_or_default().to_string(),
+            )
+        })
+        .collect()
+}
+
+fn table<T: Display>(header: &str, rows: &[(T, String, String)]) -> String {
+    let mut table = AsciiTable::default();
+    table.set_max_width(64);
+    table.column(0).set_header(header);
+    table.column(1).set_header("dealt");
+    table.column(2).set_header("taken");
+
+    let rows: Vec<Vec<&dyn Display>> = rows
+        .iter()
+        .map(|(label, dealt, taken)| vec![label as &dyn Display, dealt, taken])
+        .collect();
+
+    table.format(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{
+        app_builder::AppBuilder,
+        player_builder::PlayerBuilder,
+        utils::{get_message_content, send_message},
+    };
+
+    #[test]
+    fn parses() {
+        assert_eq!(handle_combat_stats("combatstats"), Ok(Command::CombatStats));
+        assert_eq!(handle_combat_stats("cstats"), Ok(Command::CombatStats));
+        assert_eq!(
+            handle_combat_stats("combatstats me"),
+            Err(ParseError::WrongCommand)
+        );
+    }
+
+    #[test]
+    fn no_fights() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, combat_stats);
+
+        let (_, client_id, _) = PlayerBuilder::new().build(&mut app);
+
+        send_message(&mut app, client_id, "combatstats");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "You haven't fought anyone yet."
+        );
+    }
+
+    #[test]
+    fn shows_last_fight() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, combat_stats);
+
+        let (player, client_id, _) = PlayerBuilder::new().build(&mut app);
+
+        let mut fight = FightStats {
+            duration: 10.0,
+            ..Default::default()
+        };
+
+        fight.dealt.add(12, Some("Punch"), "blunt", true);
+        fight.taken.add(5, Some("Bite"), "pierce", false);
+
+        app.world.get_mut::<CombatMeter>(player).unwrap().last = Some(fight);
+
+        send_message(&mut app, client_id, "combatstats");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert!(content.starts_with("Last fight (10.0s)"));
+        assert!(content.contains("Punch"));
+        assert!(content.contains("Pierce"));
+        assert!(content.contains("1.2"));
+    }
+}
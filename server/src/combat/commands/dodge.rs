@@ -8,9 +8,9 @@ use regex::Regex;
 
 use crate::{
     combat::components::{DodgeCooldown, ManualDodge, Stats},
+    data::resources::Balance,
     input::events::{Command, ParseError, ParsedCommand},
     player::components::{Client, Online},
-    values::MANUAL_DODGE_TIMER,
 };
 
 static REGEX: OnceLock<Regex> = OnceLock::new();
@@ -30,6 +30,7 @@ pub fn dodge(
     mut commands: EventReader<ParsedCommand>,
     players: Query<(Entity, &Client, &Stats, Option<&DodgeCooldown>), With<Online>>,
     mut outbox: EventWriter<Outbox>,
+    balance: Res<Balance>,
 ) -> Result<(), anyhow::Error> {
     for command in commands.iter() {
         if let Command::Dodge = &command.command {
@@ -45,7 +46,7 @@ pub fn dodge(
             }
 
             bevy.entity(entity).insert(ManualDodge(Timer::from_seconds(
-                MANUAL_DODGE_TIMER,
+                balance.manual_dodge_timer,
                 TimerMode::Once,
             )));
 
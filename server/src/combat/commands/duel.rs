@@ -0,0 +1,306 @@
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use bevy::prelude::*;
+use bevy_mod_sysfail::sysfail;
+use bevy_nest::prelude::*;
+use regex::Regex;
+
+use crate::{
+    combat::components::{CombatState, Distance, Duel, DuelChallenge},
+    input::events::{Command, DuelAction, ParseError, ParsedCommand},
+    player::components::{Character, Client, Online},
+    spatial::components::Tile,
+};
+
+static REGEX: OnceLock<Regex> = OnceLock::new();
+
+pub fn handle_duel(content: &str) -> Result<Command, ParseError> {
+    let regex = REGEX.get_or_init(|| Regex::new(r"^duel( (?P<target>.*))?$").unwrap());
+
+    match regex.captures(content) {
+        None => Err(ParseError::WrongCommand),
+        Some(captures) => {
+            let target = captures
+                .name("target")
+                .map(|m| m.as_str().trim())
+                .filter(|m| !m.is_empty())
+                .ok_or(ParseError::InvalidArguments("Duel whom?".into()))?;
+
+            Ok(Command::Duel(match target {
+                "accept" => DuelAction::Accept,
+                "decline" => DuelAction::Decline,
+                _ => DuelAction::Challenge(target.into()),
+            }))
+        }
+    }
+}
+
+#[sysfail(log)]
+pub fn duel(
+    mut bevy: Commands,
+    mut commands: EventReader<ParsedCommand>,
+    mut outbox: EventWriter<Outbox>,
+    players: Query<
+        (
+            Entity,
+            &Client,
+            &Character,
+            &Parent,
+            Option<&CombatState>,
+            Option<&DuelChallenge>,
+        ),
+        With<Online>,
+    >,
+    tiles: Query<&Children, With<Tile>>,
+) -> Result<(), anyhow::Error> {
+    for command in commands.iter() {
+        if let Command::Duel(action) = &command.command {
+            let (player, client, character, tile, combat_state, challenge) = players
+                .iter()
+                .find(|(_, c, _, _, _, _)| c.id == command.from)
+                .context("Player not found")?;
+
+            match action {
+                DuelAction::Challenge(name) => {
+                    let Some((target, target_client, target_character, _, target_combat_state, _)) =
+                        players.iter().find(|(_, _, c, parent, _, _)| {
+                            parent.get() == tile.get() && c.name.eq_ignore_ascii_case(name)
+                        })
+                    else {
+                        outbox.send_text(client.id, format!("You don't see {name} here."));
+
+                        continue;
+                    };
+
+                    if target == player {
+                        outbox.send_text(client.id, "You can't duel yourself.");
+
+                        continue;
+                    }
+
+                    if combat_state.is_some() {
+                        outbox.send_text(client.id, "You can't start a duel while fighting.");
+
+                        continue;
+                    }
+
+                    if target_combat_state.is_some() {
+                        outbox.send_text(
+                            client.id,
+                            format!("{} is busy fighting.", target_character.name),
+                        );
+
+                        continue;
+                    }
+
+                    bevy.entity(target).insert(DuelChallenge(player));
+
+                    outbox.send_text(
+                        client.id,
+                        format!("You challenge {} to a duel.", target_character.name),
+                    );
+
+                    outbox.send_text(
+                        target_client.id,
+                        format!(
+                            "{} challenges you to a duel. Type 'duel accept' to fight.",
+                            character.name
+                        ),
+                    );
+                }
+                DuelAction::Accept | DuelAction::Decline => {
+                    let Some(challenge) = challenge else {
+                        outbox.send_text(client.id, "No one has challenged you to a duel.");
+
+                        continue;
+                    };
+
+                    bevy.entity(player).remove::<DuelChallenge>();
+
+                    let Some((challenger, challenger_client, challenger_character, _, busy, _)) =
+                        players
+                            .get(challenge.0)
+                            .ok()
+                            .filter(|(_, _, _, parent, _, _)| parent.get() == tile.get())
+                    else {
+                        outbox.send_text(client.id, "Your challenger is no longer here.");
+
+                        continue;
+                    };
+
+                    if let DuelAction::Decline = action {
+                        outbox.send_text(
+                            client.id,
+                            format!("You decline {}'s challenge.", challenger_character.name),
+                        );
+
+                        outbox.send_text(
+                            challenger_client.id,
+                            format!("{} declines your challenge.", character.name),
+                        );
+
+                        continue;
+                    }
+
+                    if combat_state.is_some() || busy.is_some() {
+                        outbox.send_text(
+                            client.id,
+                            "You can't start a duel while anyone's fighting.",
+                        );
+
+                        continue;
+                    }
+
+                    bevy.entity(player).insert((
+                        CombatState::new(challenger, Distance::Near),
+                        Duel(challenger),
+                    ));
+
+                    bevy.entity(challenger)
+                        .insert((CombatState::new(player, Distance::Near), Duel(player)));
+
+                    let siblings = tiles.get(tile.get())?;
+
+                    for (_, other_client, _, _, _, _) in
+                        siblings.iter().filter_map(|c| players.get(*c).ok())
+                    {
+                        outbox.send_text(
+                            other_client.id,
+                            format!(
+                                "{} and {} square off for a duel.",
+                                challenger_character.name, character.name
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{
+        app_builder::AppBuilder,
+        player_builder::PlayerBuilder,
+        tile_builder::{TileBuilder, ZoneBuilder},
+        utils::{clear_messages, get_message_content, send_message},
+    };
+
+    #[test]
+    fn parses() {
+        assert_eq!(
+            handle_duel("duel Bau"),
+            Ok(Command::Duel(DuelAction::Challenge("Bau".into())))
+        );
+
+        assert_eq!(
+            handle_duel("duel accept"),
+            Ok(Command::Duel(DuelAction::Accept))
+        );
+
+        assert_eq!(
+            handle_duel("duel"),
+            Err(ParseError::InvalidArguments("Duel whom?".into()))
+        );
+
+        assert_eq!(handle_duel("duels"), Err(ParseError::WrongCommand));
+    }
+
+    #[test]
+    fn challenge_and_accept() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, duel);
+
+        let zone = ZoneBuilder::new().no_pvp().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (challenger, challenger_id, _) = PlayerBuilder::new()
+            .name("Ashur")
+            .tile(tile)
+            .build(&mut app);
+
+        let (challenged, challenged_id, _) =
+            PlayerBuilder::new().name("Bau").tile(tile).build(&mut app);
+
+        send_message(&mut app, challenger_id, "duel bau");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, challenged_id).unwrap(),
+            "Ashur challenges you to a duel. Type 'duel accept' to fight."
+        );
+
+        clear_messages(&mut app);
+
+        send_message(&mut app, challenged_id, "duel accept");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, challenger_id).unwrap(),
+            "Ashur and Bau square off for a duel."
+        );
+
+        assert_eq!(app.world.get::<Duel>(challenger).unwrap().0, challenged);
+        assert_eq!(app.world.get::<Duel>(challenged).unwrap().0, challenger);
+        assert_eq!(
+            app.world.get::<CombatState>(challenged).unwrap().target,
+            challenger
+        );
+        assert!(app.world.get::<DuelChallenge>(challenged).is_none());
+    }
+
+    #[test]
+    fn decline() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, duel);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (challenger, challenger_id, _) = PlayerBuilder::new()
+            .name("Ashur")
+            .tile(tile)
+            .build(&mut app);
+
+        let (_, challenged_id, _) = PlayerBuilder::new().name("Bau").tile(tile).build(&mut app);
+
+        send_message(&mut app, challenger_id, "duel bau");
+        app.update();
+
+        clear_messages(&mut app);
+
+        send_message(&mut app, challenged_id, "duel decline");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, challenger_id).unwrap(),
+            "Bau declines your challenge."
+        );
+
+        assert!(app.world.get::<Duel>(challenger).is_none());
+    }
+
+    #[test]
+    fn accept_without_challenge() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, duel);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (_, client_id, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+
+        send_message(&mut app, client_id, "duel accept");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "No one has challenged you to a duel."
+        );
+    }
+}
@@ -1,6 +1,9 @@
 pub mod advance;
 pub mod attack;
 pub mod block;
+pub mod combat_stats;
 pub mod dodge;
+pub mod duel;
 pub mod retreat;
+pub mod skills;
 pub mod use_skill;
@@ -7,9 +7,11 @@ use bevy_nest::prelude::*;
 use regex::Regex;
 
 use crate::{
-    combat::components::{AttackTimer, CombatState, Distance, Stats},
+    combat::{
+        components::{CombatState, Distance},
+        events::{CombatEvent, CombatEventKind, CombatEventTrigger},
+    },
     input::events::{Command, ParseError, ParsedCommand},
-    npc::components::Hostile,
     player::components::{Client, Online},
 };
 
@@ -26,34 +28,35 @@ pub fn handle_retreat(content: &str) -> Result<Command, ParseError> {
 
 #[sysfail(log)]
 pub fn retreat(
-    mut bevy: Commands,
     mut commands: EventReader<ParsedCommand>,
-    mut players: Query<(Entity, &Client, &Stats, Option<&mut CombatState>), With<Online>>,
-    mut hostiles: Query<&mut CombatState, (With<Hostile>, Without<Online>)>,
+    mut combat_events: EventWriter<CombatEvent>,
+    players: Query<(Entity, &Client, Option<&CombatState>), With<Online>>,
     mut outbox: EventWriter<Outbox>,
 ) -> Result<(), anyhow::Error> {
     for command in commands.iter() {
         if let Command::Retreat = &command.command {
-            let (entity, _, stats, combat_state) = players
-                .iter_mut()
-                .find(|(_, c, _, _)| c.id == command.from)
+            let (entity, _, combat_state) = players
+                .iter()
+                .find(|(_, c, _)| c.id == command.from)
                 .context("Player not found")?;
 
-            if let Some(mut combat_state) = combat_state {
-                combat_state.distance = Distance::Far;
+            let Some(combat_state) = combat_state else {
+                outbox.send_text(command.from, "You are not in combat.");
 
-                let mut hostile = hostiles.get_mut(combat_state.target)?;
-                hostile.distance = Distance::Near;
+                continue;
+            };
 
-                bevy.entity(entity).insert(AttackTimer(Timer::from_seconds(
-                    stats.attack_speed(),
-                    TimerMode::Once,
-                )));
+            if combat_state.distance == Distance::Far {
+                outbox.send_text(command.from, "You are already keeping your distance.");
 
-                outbox.send_text(command.from, "You retreat from your target.");
-            } else {
-                outbox.send_text(command.from, "You are not in combat.");
+                continue;
             }
+
+            combat_events.send(CombatEvent {
+                source: entity,
+                trigger: CombatEventTrigger::Movement,
+                kind: CombatEventKind::AttemptReposition(Distance::Far),
+            });
         }
     }
 
@@ -0,0 +1,196 @@
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use bevy::prelude::*;
+use bevy_mod_sysfail::sysfail;
+use bevy_nest::prelude::*;
+use regex::Regex;
+
+use crate::{
+    combat::components::{LearnedSkills, Stats},
+    data::resources::{Masteries, Mastery, SkillNode, Skills},
+    input::events::{Command, ParseError, ParsedCommand},
+    player::components::{Character, Client, Online},
+};
+
+static REGEX: OnceLock<Regex> = OnceLock::new();
+
+pub fn handle_skills(content: &str) -> Result<Command, ParseError> {
+    let regex = REGEX.get_or_init(|| Regex::new(r"^skills$").unwrap());
+
+    match regex.is_match(content) {
+        false => Err(ParseError::WrongCommand),
+        true => Ok(Command::Skills),
+    }
+}
+
+#[sysfail(log)]
+pub fn skills(
+    mut commands: EventReader<ParsedCommand>,
+    mut outbox: EventWriter<Outbox>,
+    players: Query<(&Client, &Character, &Stats, &LearnedSkills), With<Online>>,
+    masteries: Res<Masteries>,
+    skills: Res<Skills>,
+) -> Result<(), anyhow::Error> {
+    for command in commands.iter() {
+        if let Command::Skills = &command.command {
+            let (client, character, stats, learned) = players
+                .iter()
+                .find(|(c, _, _, _)| c.id == command.from)
+                .context("Player not found")?;
+
+            let mastery = masteries
+                .0
+                .get(&character.mastery)
+                .with_context(|| format!("Mastery not found: {}", character.mastery))?;
+
+            if mastery.skills.is_empty() {
+                outbox.send_text(
+                    client.id,
+                    format!("There are no skills to learn as a {}.", mastery.name),
+                );
+
+                continue;
+            }
+
+            let count = mastery
+                .skills
+                .iter()
+                .filter(|node| learned.0.contains(&node.skill))
+                .count();
+
+            let mut lines = vec![format!(
+                "{} skills ({count} of {} learned)",
+                mastery.name,
+                mastery.skills.len()
+            )];
+
+            let tree = Tree {
+                mastery,
+                skills: &skills,
+                learned,
+                level: stats.level,
+            };
+
+            for root in mastery
+                .skills
+                .iter()
+                .filter(|node| tree.parent(node).is_none())
+            {
+                tree.branch(root, 0, &mut lines);
+            }
+
+            outbox.send_text(client.id, lines.join("\n"));
+        }
+    }
+
+    Ok(())
+}
+
+struct Tree<'a> {
+    mastery: &'a Mastery,
+    skills: &'a Skills,
+    learned: &'a LearnedSkills,
+    level: u32,
+}
+
+impl Tree<'_> {
+    /// A skill hangs off its first prerequisite, so each appears only once.
+    fn parent(&self, node: &SkillNode) -> Option<&SkillNode> {
+        node.requires.first().and_then(|id| self.mastery.node(id))
+    }
+
+    fn name<'b>(&'b self, id: &'b str) -> &'b str {
+        self.skills
+            .0
+            .get(id)
+            .map_or(id, |skill| skill.name.as_str())
+    }
+
+    fn branch(&self, node: &SkillNode, depth: usize, lines: &mut Vec<String>) {
+        let indent = "  ".repeat(depth);
+        let name = self.name(&node.skill);
+
+        if self.learned.0.contains(&node.skill) {
+            lines.push(format!("{indent}[x] {name}"));
+        } else {
+            let mut needs = vec![];
+
+            if self.level < node.level {
+                needs.push(format!("level {}", node.level));
+            }
+
+            needs.extend(
+                node.requires
+                    .iter()
+                    .filter(|id| !self.learned.0.contains(*id))
+                    .map(|id| self.name(id).to_string()),
+            );
+
+            match needs.is_empty() {
+                true => lines.push(format!("{indent}[ ] {name}")),
+                false => lines.push(format!("{indent}[ ] {name} (needs {})", needs.join(", "))),
+            }
+        }
+
+        for child in self
+            .mastery
+            .skills
+            .iter()
+            .filter(|child| self.parent(child).map(|p| p.skill.as_str()) == Some(&node.skill))
+        {
+            self.branch(child, depth + 1, lines);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{
+        app_builder::AppBuilder,
+        player_builder::PlayerBuilder,
+        utils::{get_message_content, send_message},
+    };
+
+    #[test]
+    fn parses() {
+        assert_eq!(handle_skills("skills"), Ok(Command::Skills));
+        assert_eq!(handle_skills("skills punch"), Err(ParseError::WrongCommand));
+    }
+
+    #[test]
+    fn shows_tree() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, skills);
+
+        app.world
+            .resource_mut::<Masteries>()
+            .0
+            .get_mut("freelancer")
+            .unwrap()
+            .skills
+            .extend([
+                SkillNode {
+                    skill: "kick".into(),
+                    level: 2,
+                    requires: vec!["punch".into()],
+                },
+                SkillNode {
+                    skill: "headbutt".into(),
+                    level: 0,
+                    requires: vec!["kick".into()],
+                },
+            ]);
+
+        let (_, client_id, _) = PlayerBuilder::new().skills(&["punch"]).build(&mut app);
+
+        send_message(&mut app, client_id, "skills");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "Freelancer skills (1 of 3 learned)\n[x] Punch\n  [ ] kick (needs level 2)\n    [ ] headbutt (needs kick)"
+        );
+    }
+}
@@ -9,12 +9,16 @@ use thiserror::Error;
 
 use crate::{
     combat::{
-        components::{Approach, AttackTimer, CombatState, Cooldowns, QueuedAttack, Stats},
+        components::{
+            Approach, AttackTimer, CombatState, Cooldowns, LearnedSkills, QueuedAttack, Stats,
+        },
         events::{CombatEvent, CombatEventKind, CombatEventTrigger},
+        utils::engage,
     },
-    data::resources::{Masteries, Skill, Skills},
+    data::resources::{Masteries, Skill, SkillApproach, Skills},
     input::events::{Command, ParseError, ParsedCommand},
     interact::components::{Interaction, Interactions},
+    items::components::{Inventory, Item, Stack},
     npc::components::Npc,
     player::components::{Character, Client, Online},
     spatial::components::Tile,
@@ -50,6 +54,7 @@ pub struct NpcQuery {
     depiction: &'static Depiction,
     interactions: Option<&'static Interactions>,
     stats: Option<&'static Stats>,
+    combat_state: Option<&'static mut CombatState>,
     with_npc: With<Npc>,
 }
 
@@ -61,10 +66,12 @@ pub struct PlayerQuery {
     character: &'static mut Character,
     stats: &'static mut Stats,
     tile: &'static Parent,
-    combat_state: Option<&'static CombatState>,
+    combat_state: Option<&'static mut CombatState>,
     attack_timer: Option<&'static AttackTimer>,
     queued_attack: Option<&'static mut QueuedAttack>,
     cooldowns: &'static Cooldowns,
+    learned: &'static LearnedSkills,
+    children: Option<&'static Children>,
     with_online: With<Online>,
     without_npc: Without<Npc>,
 }
@@ -80,24 +87,32 @@ pub struct TileQuery {
 pub fn use_skill(
     mut bevy: Commands,
     mut commands: EventReader<ParsedCommand>,
-    npcs: Query<NpcQuery>,
+    mut npcs: Query<NpcQuery>,
     mut players: Query<PlayerQuery>,
     mut outbox: EventWriter<Outbox>,
     mut combat_events: EventWriter<CombatEvent>,
     skills: Res<Skills>,
     masteries: Res<Masteries>,
     tiles: Query<TileQuery>,
+    inventories: Query<&Children, With<Inventory>>,
+    items: Query<(Entity, &Name, Option<&Stack>), With<Item>>,
 ) -> Result<(), anyhow::Error> {
     for command in commands.iter() {
         if let Command::UseSkill((skill, target)) = &command.command {
             let mut put_in_combat = false;
 
-            let player = players
+            let mut player = players
                 .iter_mut()
                 .find(|p| p.client.id == command.from)
                 .context("Player not found")?;
 
-            let skill = match get_skill(&skills, &masteries, &player.character, skill) {
+            let skill = match get_skill(
+                &skills,
+                &masteries,
+                &player.character,
+                player.learned,
+                skill,
+            ) {
                 Ok(skill) => skill,
                 Err(err) => {
                     outbox.send_text(player.client.id, err.to_string());
@@ -128,6 +143,46 @@ pub fn use_skill(
                 continue;
             }
 
+            if let SkillApproach::Requires(approach) = skill.approach {
+                let current = player
+                    .combat_state
+                    .as_ref()
+                    .map(|combat_state| combat_state.approach)
+                    .unwrap_or(Approach::Front);
+
+                if current != approach {
+                    outbox.send_text(
+                        player.client.id,
+                        format!("{} can only be used from the {approach}.", skill.name),
+                    );
+
+                    continue;
+                }
+            }
+
+            if let Some(combat_state) = &player.combat_state {
+                if !skill.reaches(combat_state.distance) {
+                    outbox.send_text(player.client.id, skill.out_of_range());
+
+                    continue;
+                }
+            }
+
+            let ammo = match &skill.ammo {
+                Some(ammo) => match find_ammo(ammo, player.children, &inventories, &items) {
+                    Some(item) => Some(item),
+                    None => {
+                        outbox.send_text(
+                            player.client.id,
+                            format!("You have nothing left to use {} with.", skill.name),
+                        );
+
+                        continue;
+                    }
+                },
+                None => None,
+            };
+
             if player.attack_timer.is_some() {
                 match player.queued_attack {
                     Some(mut queued_attack) => {
@@ -154,17 +209,25 @@ pub fn use_skill(
                     }
                 };
 
-                bevy.entity(player.entity).insert(CombatState {
-                    target,
-                    distance: skill.distance,
-                    approach: Approach::Front,
-                });
+                let npc = npcs.get_mut(target)?;
 
-                bevy.entity(target).insert(CombatState {
-                    target: player.entity,
-                    distance: skill.distance,
-                    approach: Approach::Front,
-                });
+                let distance = match &player.combat_state {
+                    Some(combat_state) => combat_state.distance,
+                    None => skill.distance,
+                };
+
+                engage(&mut bevy, target, npc.combat_state, player.entity, distance);
+
+                match player.combat_state.as_mut() {
+                    Some(combat_state) => {
+                        combat_state.target = target;
+                        combat_state.threat.add(target, 0.0);
+                    }
+                    None => {
+                        bevy.entity(player.entity)
+                            .insert(CombatState::new(target, skill.distance));
+                    }
+                }
 
                 put_in_combat = true;
             }
@@ -175,6 +238,18 @@ pub fn use_skill(
                 continue;
             }
 
+            if let Some((item, stack)) = ammo {
+                match stack.filter(|stack| stack.quantity > 1) {
+                    Some(stack) => {
+                        bevy.entity(item).insert(Stack {
+                            quantity: stack.quantity - 1,
+                            ..*stack
+                        });
+                    }
+                    None => bevy.entity(item).despawn_recursive(),
+                }
+            }
+
             combat_events.send(CombatEvent {
                 source: player.entity,
                 trigger: CombatEventTrigger::Skill(skill.clone()),
@@ -186,16 +261,36 @@ pub fn use_skill(
     Ok(())
 }
 
+/// The first of the ammo's items in the player's inventory, and its stack
+/// if it's one of many.
+fn find_ammo<'a>(
+    ammo: &str,
+    children: Option<&Children>,
+    inventories: &Query<&Children, With<Inventory>>,
+    items: &'a Query<(Entity, &Name, Option<&Stack>), With<Item>>,
+) -> Option<(Entity, Option<&'a Stack>)> {
+    children?
+        .iter()
+        .find_map(|child| inventories.get(*child).ok())?
+        .iter()
+        .filter_map(|child| items.get(*child).ok())
+        .find(|(_, name, _)| name.as_str().trim_end_matches(" (Prototype)") == ammo)
+        .map(|(entity, _, stack)| (entity, stack))
+}
+
 #[derive(Error, Debug, PartialEq)]
 enum SkillError {
     #[error("You don't know how to do that.")]
     Unknown,
+    #[error("You haven't learned {0} yet.")]
+    NotLearned(String),
 }
 
 fn get_skill<'a>(
     skills: &'a Skills,
     masteries: &Masteries,
     character: &Character,
+    learned: &LearnedSkills,
     skill: &str,
 ) -> Result<&'a Skill, SkillError> {
     let (key, skill) = skills
@@ -209,10 +304,14 @@ fn get_skill<'a>(
         .get(&character.mastery)
         .ok_or(SkillError::Unknown)?;
 
-    if !mastery.skills.contains(key) {
+    if mastery.node(key).is_none() {
         return Err(SkillError::Unknown);
     }
 
+    if !learned.0.contains(key) {
+        return Err(SkillError::NotLearned(skill.name.clone()));
+    }
+
     Ok(skill)
 }
 
@@ -261,10 +360,15 @@ fn get_target(
 mod tests {
     use crate::test::{
         app_builder::AppBuilder,
+        item_builder::ItemBuilder,
         npc_builder::NpcBuilder,
         player_builder::PlayerBuilder,
         tile_builder::{TileBuilder, ZoneBuilder},
-        utils::send_message,
+        utils::{get_message_content, send_message},
+    };
+    use crate::{
+        combat::components::{Conditions, Distance},
+        data::{self, resources::ControlFlag},
     };
 
     use super::*;
@@ -292,7 +396,10 @@ mod tests {
 
         let zone = ZoneBuilder::new().build(&mut app);
         let tile = TileBuilder::new().build(&mut app, zone);
-        let player = PlayerBuilder::new().tile(tile).build(&mut app);
+        let player = PlayerBuilder::new()
+            .tile(tile)
+            .skills(&["punch"])
+            .build(&mut app);
 
         (app, tile, player.0)
     }
@@ -300,6 +407,7 @@ mod tests {
     #[rstest]
     fn get_skill_valid(get_skill_setup: (App, Entity, Entity)) {
         let (mut app, _, player) = get_skill_setup;
+        let learned = app.world.get::<LearnedSkills>(player).unwrap().clone();
 
         let mut system_state: SystemState<(Res<Skills>, Res<Masteries>, Query<&Character>)> =
             SystemState::new(&mut app.world);
@@ -310,6 +418,7 @@ mod tests {
             skills.into_inner(),
             masteries.into_inner(),
             &character,
+            &learned,
             "punch",
         )
         .map(|s| s.name.clone());
@@ -320,6 +429,7 @@ mod tests {
     #[rstest]
     fn get_skill_invalid_mastery(get_skill_setup: (App, Entity, Entity)) {
         let (mut app, _, player) = get_skill_setup;
+        let learned = app.world.get::<LearnedSkills>(player).unwrap().clone();
 
         let mut system_state: SystemState<(Res<Skills>, Res<Masteries>, Query<&mut Character>)> =
             SystemState::new(&mut app.world);
@@ -332,6 +442,7 @@ mod tests {
             skills.into_inner(),
             masteries.into_inner(),
             &character,
+            &learned,
             "punch",
         )
         .map(|s| s.name.clone());
@@ -342,6 +453,7 @@ mod tests {
     #[rstest]
     fn get_skill_invalid_skill(get_skill_setup: (App, Entity, Entity)) {
         let (mut app, _, player) = get_skill_setup;
+        let learned = app.world.get::<LearnedSkills>(player).unwrap().clone();
 
         let mut system_state: SystemState<(Res<Skills>, Res<Masteries>, Query<&Character>)> =
             SystemState::new(&mut app.world);
@@ -352,6 +464,7 @@ mod tests {
             skills.into_inner(),
             masteries.into_inner(),
             &character,
+            &learned,
             "kick",
         )
         .map(|s| s.name.clone());
@@ -359,6 +472,27 @@ mod tests {
         assert_eq!(result, Err(SkillError::Unknown));
     }
 
+    #[rstest]
+    fn get_skill_not_learned(get_skill_setup: (App, Entity, Entity)) {
+        let (mut app, _, player) = get_skill_setup;
+
+        let mut system_state: SystemState<(Res<Skills>, Res<Masteries>, Query<&Character>)> =
+            SystemState::new(&mut app.world);
+        let (skills, masteries, character_query) = system_state.get(&app.world);
+        let character = character_query.get(player).unwrap();
+
+        let result = get_skill(
+            skills.into_inner(),
+            masteries.into_inner(),
+            character,
+            &LearnedSkills::default(),
+            "punch",
+        )
+        .map(|s| s.name.clone());
+
+        assert_eq!(result, Err(SkillError::NotLearned("Punch".into())));
+    }
+
     #[fixture]
     fn get_target_setup() -> (App, Entity, Entity) {
         let mut app = AppBuilder::new().build();
@@ -432,7 +566,10 @@ mod tests {
             .combat(true)
             .build(&mut app);
 
-        let (player, client_id, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+        let (player, client_id, _) = PlayerBuilder::new()
+            .tile(tile)
+            .skills(&["punch"])
+            .build(&mut app);
 
         send_message(&mut app, client_id, "punch goat");
         app.update();
@@ -440,4 +577,214 @@ mod tests {
         assert_eq!(app.world.get::<CombatState>(player).unwrap().target, npc);
         assert_eq!(app.world.get::<CombatState>(npc).unwrap().target, player);
     }
+
+    #[test]
+    fn requires_approach() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, use_skill);
+
+        let mut skills = app.world.resource_mut::<Skills>();
+        let punch = skills.0.get_mut("punch").unwrap();
+        punch.approach = SkillApproach::Requires(Approach::Rear);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        NpcBuilder::new()
+            .name("Goat")
+            .short_name("goat")
+            .tile(tile)
+            .combat(true)
+            .build(&mut app);
+
+        let (player, client_id, _) = PlayerBuilder::new()
+            .tile(tile)
+            .skills(&["punch"])
+            .build(&mut app);
+
+        send_message(&mut app, client_id, "punch goat");
+        app.update();
+
+        assert!(app.world.get::<CombatState>(player).is_none());
+        assert_eq!(
+            get_message_content(&mut app, client_id),
+            Some("Punch can only be used from the rear.".into())
+        );
+    }
+
+    #[test]
+    fn out_of_range() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, use_skill);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let npc = NpcBuilder::new()
+            .name("Goat")
+            .short_name("goat")
+            .tile(tile)
+            .combat(true)
+            .build(&mut app);
+
+        let (player, client_id, _) = PlayerBuilder::new()
+            .tile(tile)
+            .skills(&["punch"])
+            .build(&mut app);
+
+        app.world
+            .entity_mut(player)
+            .insert(CombatState::new(npc, Distance::Far));
+
+        send_message(&mut app, client_id, "punch");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id),
+            Some("You are too far away to use Punch.".into())
+        );
+    }
+
+    #[test]
+    fn uses_up_ammo() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, use_skill);
+
+        let mut skills = app.world.resource_mut::<Skills>();
+        let punch = skills.0.get_mut("punch").unwrap();
+        punch.ammo = Some("items.rock".into());
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let npc = NpcBuilder::new()
+            .name("Goat")
+            .short_name("goat")
+            .tile(tile)
+            .combat(true)
+            .build(&mut app);
+
+        let (player, client_id, inventory) = PlayerBuilder::new()
+            .tile(tile)
+            .skills(&["punch"])
+            .has_inventory()
+            .build(&mut app);
+
+        app.world
+            .entity_mut(player)
+            .insert(CombatState::new(npc, Distance::Near));
+
+        let rock = ItemBuilder::new().build(&mut app);
+
+        app.world
+            .entity_mut(rock)
+            .insert(Name::new("items.rock (Prototype)"))
+            .set_parent(inventory.unwrap());
+
+        send_message(&mut app, client_id, "punch");
+        app.update();
+
+        assert!(app.world.get_entity(rock).is_none());
+
+        send_message(&mut app, client_id, "punch");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id),
+            Some("You have nothing left to use Punch with.".into())
+        );
+    }
+
+    #[test]
+    fn uses_one_of_a_stack() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, use_skill);
+
+        let mut skills = app.world.resource_mut::<Skills>();
+        let punch = skills.0.get_mut("punch").unwrap();
+        punch.ammo = Some("items.rock".into());
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let npc = NpcBuilder::new()
+            .name("Goat")
+            .short_name("goat")
+            .tile(tile)
+            .combat(true)
+            .build(&mut app);
+
+        let (player, client_id, inventory) = PlayerBuilder::new()
+            .tile(tile)
+            .skills(&["punch"])
+            .has_inventory()
+            .build(&mut app);
+
+        app.world
+            .entity_mut(player)
+            .insert(CombatState::new(npc, Distance::Near));
+
+        let rocks = ItemBuilder::new().stack(3, 10).build(&mut app);
+
+        app.world
+            .entity_mut(rocks)
+            .insert(Name::new("items.rock (Prototype)"))
+            .set_parent(inventory.unwrap());
+
+        send_message(&mut app, client_id, "punch");
+        app.update();
+
+        assert_eq!(app.world.get::<Stack>(rocks).unwrap().quantity, 2);
+    }
+
+    #[test]
+    fn stunned_cant_attack() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, use_skill);
+
+        let stunned = data::resources::Condition {
+            id: "stunned".into(),
+            name: "Stunned".into(),
+            description: "".into(),
+            scripts: vec![],
+            modifiers: vec![],
+            percent_modifiers: vec![],
+            tick: None,
+            stacking: data::resources::Stacking::Refresh,
+            tags: vec![],
+            flags: vec![ControlFlag::Stunned],
+        };
+
+        let mut conditions = data::resources::Conditions::default();
+        conditions.0.insert(stunned.id.clone(), stunned.clone());
+        app.insert_resource(conditions);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        NpcBuilder::new()
+            .name("Goat")
+            .short_name("goat")
+            .tile(tile)
+            .combat(true)
+            .build(&mut app);
+
+        let (player, client_id, _) = PlayerBuilder::new()
+            .tile(tile)
+            .skills(&["punch"])
+            .build(&mut app);
+
+        let mut applied = Conditions::default();
+        applied.apply(&stunned, player, None);
+        app.world.entity_mut(player).insert(applied);
+
+        send_message(&mut app, client_id, "punch goat");
+        app.update();
+
+        assert!(app.world.get::<CombatState>(player).is_none());
+        assert_eq!(
+            get_message_content(&mut app, client_id),
+            Some("You are stunned and can't act.".into())
+        );
+    }
 }
@@ -1,27 +1,19 @@
-use std::fmt::{self, Display, Formatter};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display, Formatter},
+    sync::Arc,
+};
 
 use bevy::{prelude::*, utils::HashMap};
-use serde::Deserialize;
-
-use crate::data::resources::DamageKind;
-use crate::{data::resources::Stat, input::events::ParsedCommand};
-
-use crate::values::{
-    ATTACK_SPEED_CAP, ATTACK_SPEED_FACTOR, AUTO_ATTACK_LEVEL_CONTRIBUTION,
-    AUTO_ATTACK_SPEED_FACTOR, AUTO_ATTACK_STAT_CONTRIBUTION, BASE_ATTACK_SPEED,
-    BASE_AUTO_ATTACK_DAMAGE, BASE_BLOCK_CHANCE, BASE_BLOCK_RATE, BASE_CRIT_DAMAGE_MULTIPLIER,
-    BASE_CRIT_STRIKE_CHANCE, BASE_DODGE_CHANCE, BASE_DODGE_RATE, BASE_FLEE_CHANCE, BASE_HEALTH,
-    BASE_HEALTH_REGEN, BASE_VIGOR, BASE_VIGOR_REGEN, BLOCK_CHANCE_CAP,
-    BLOCK_CHANCE_STAT_CONTRIBUTION, BLOCK_CHANCE_STRENGTH_CONTRIBUTION, BLOCK_RATE_CAP,
-    BLOCK_RATE_STAT_CONTRIBUTION, BLOCK_RATE_STRENGTH_CONTRIBUTION, CRIT_DAMAGE_STAT_CONTRIBUTION,
-    CRIT_STRIKE_CHANCE_CAP, CRIT_STRIKE_STAT_CONTRIBUTION, DODGE_CHANCE_CAP,
-    DODGE_CHANCE_DEXTERITY_CONTRIBUTION, DODGE_CHANCE_STAT_CONTRIBUTION, DODGE_RATE_CAP,
-    DODGE_RATE_DEXTERITY_CONTRIBUTION, DODGE_RATE_STAT_CONTRIBUTION,
-    FLEE_CHANCE_DOMINANCE_CONTRIBUTION, FLEE_CHANCE_FLEET_CONTRIBUTION,
-    HEALTH_REGEN_STAT_CONTRIBUTION, HEALTH_REGEN_TICK, MAX_HEALTH_LEVEL_CONTRIBUTION,
-    MAX_HEALTH_STAT_CONTRIBUTION, MAX_VIGOR_LEVEL_CONTRIBUTION, MAX_VIGOR_STAT_CONTRIBUTION,
-    RESISTANCE_CAP, RESISTANCE_FACTOR, VIGOR_REGEN_STAT_CONTRIBUTION, VIGOR_REGEN_TICK,
+use serde::{Deserialize, Serialize};
+
+use crate::data::{
+    self,
+    resources::{Balance, Condition, ControlFlag, DamageKind, Modifier, ModifierKind, Stacking},
 };
+use crate::{data::resources::Stat, input::events::ParsedCommand, lua::events::ExecutionPhase};
+
+use crate::values::{HEALTH_REGEN_TICK, VIGOR_REGEN_TICK};
 
 #[derive(Component, Deserialize, Debug, Default, Reflect, Clone)]
 pub struct Stats {
@@ -36,6 +28,34 @@ pub struct Stats {
     pub offense: Offense,
     #[reflect(default)]
     pub resistance: Resistance,
+    /// Totals from `Modifiers`, cached here by `apply_modifiers` so every
+    /// derived stat below sees them.
+    #[reflect(ignore)]
+    #[serde(skip)]
+    pub bonuses: StatBonuses,
+    /// Resistances from worn and wielded gear, cached here by
+    /// `apply_equipment`.
+    #[reflect(ignore)]
+    #[serde(skip)]
+    pub gear_resistance: Resistance,
+    /// Shared with the `Balance` resource by `apply_balance`.
+    #[reflect(ignore)]
+    #[serde(skip)]
+    pub balance: Arc<Balance>,
+}
+
+/// The flat and percentage totals of every modified stat.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct StatBonuses(pub HashMap<Stat, (f32, f32)>);
+
+impl StatBonuses {
+    /// Adds the flat total to the value, then scales it by the percentage.
+    pub fn apply(&self, stat: &Stat, value: f32) -> f32 {
+        match self.0.get(stat) {
+            Some((flat, percent)) => (value + flat) * (1.0 + percent),
+            None => value,
+        }
+    }
 }
 
 #[derive(Default, Deserialize, Debug, Reflect, Clone)]
@@ -60,6 +80,9 @@ pub struct Status {
     pub vigor: u32,
     #[reflect(default)]
     pub vigor_regen: u32,
+    #[reflect(default)]
+    #[serde(default)]
+    pub experience: u32,
 }
 
 #[derive(Default, Deserialize, Debug, Reflect, Clone)]
@@ -92,58 +115,163 @@ pub struct Offense {
 pub struct Resistance(pub HashMap<String, u32>);
 
 impl Stats {
+    fn modified(&self, stat: Stat, value: f32) -> f32 {
+        self.bonuses.apply(&stat, value)
+    }
+
+    fn modified_rating(&self, stat: Stat, value: u32) -> u32 {
+        self.modified(stat, value as f32).max(0.0) as u32
+    }
+
+    pub fn vitality(&self) -> u32 {
+        self.modified_rating(Stat::Vitality, self.attributes.vitality)
+    }
+
+    pub fn stamina(&self) -> u32 {
+        self.modified_rating(Stat::Stamina, self.attributes.stamina)
+    }
+
+    pub fn strength(&self) -> u32 {
+        self.modified_rating(Stat::Strength, self.attributes.strength)
+    }
+
+    pub fn dexterity(&self) -> u32 {
+        self.modified_rating(Stat::Dexterity, self.attributes.dexterity)
+    }
+
+    pub fn intelligence(&self) -> u32 {
+        self.modified_rating(Stat::Intelligence, self.attributes.intelligence)
+    }
+
+    pub fn fleet(&self) -> u32 {
+        self.modified_rating(Stat::Fleet, self.defense.fleet)
+    }
+
+    pub fn dominance(&self) -> u32 {
+        self.modified_rating(Stat::Dominance, self.offense.dominance)
+    }
+
+    /// Attributes with modifiers applied.
+    pub fn effective_attributes(&self) -> Attributes {
+        Attributes {
+            vitality: self.vitality(),
+            stamina: self.stamina(),
+            strength: self.strength(),
+            dexterity: self.dexterity(),
+            intelligence: self.intelligence(),
+        }
+    }
+
+    /// Defense with modifiers applied.
+    pub fn effective_defense(&self) -> Defense {
+        Defense {
+            dodge_chance: self.modified(Stat::DodgeChance, self.defense.dodge_chance),
+            dodge_rate: self.modified(Stat::DodgeRate, self.defense.dodge_rate),
+            block_chance: self.modified(Stat::BlockChance, self.defense.block_chance),
+            block_rate: self.modified(Stat::BlockRate, self.defense.block_rate),
+            fleet: self.fleet(),
+        }
+    }
+
+    /// Offense with modifiers applied.
+    pub fn effective_offense(&self) -> Offense {
+        Offense {
+            attack_speed: self.modified_rating(Stat::AttackSpeed, self.offense.attack_speed),
+            dominance: self.dominance(),
+            crit_strike_chance: self
+                .modified(Stat::CritStrikeChance, self.offense.crit_strike_chance),
+            crit_strike_damage: self
+                .modified(Stat::CritStrikeDamage, self.offense.crit_strike_damage),
+        }
+    }
+
     pub fn max_health(&self) -> u32 {
-        let mut max_health =
-            BASE_HEALTH + (self.attributes.vitality as f32 * MAX_HEALTH_STAT_CONTRIBUTION);
+        let mut max_health = self.balance.base_health
+            + (self.vitality() as f32 * self.balance.max_health_stat_contribution);
 
         for _ in 1..self.level {
-            max_health += max_health * MAX_HEALTH_LEVEL_CONTRIBUTION;
+            max_health += max_health * self.balance.max_health_level_contribution;
+        }
+
+        f32::floor(self.modified(Stat::Health, max_health)).max(1.0) as u32
+    }
+
+    pub fn experience_to_level(&self) -> u32 {
+        self.balance.experience_per_level * (self.level + 1)
+    }
+
+    pub fn experience_reward(&self) -> u32 {
+        self.balance.base_experience_reward
+            + (self.level * self.balance.experience_reward_level_contribution)
+    }
+
+    /// Adds experience, levelling up as many times as it allows. Returns
+    /// whether a level was gained.
+    pub fn gain_experience(&mut self, amount: u32) -> bool {
+        let level = self.level;
+
+        self.status.experience += amount;
+
+        while self.status.experience >= self.experience_to_level() {
+            self.status.experience -= self.experience_to_level();
+            self.level += 1;
         }
 
-        f32::floor(max_health) as u32
+        self.level > level
     }
 
     pub fn health_per_second(&self) -> u32 {
-        BASE_HEALTH_REGEN
-            + ((self.attributes.vitality as f32 * HEALTH_REGEN_STAT_CONTRIBUTION).floor() as u32)
+        self.balance.base_health_regen
+            + ((self.vitality() as f32 * self.balance.health_regen_stat_contribution).floor()
+                as u32)
     }
 
     pub fn max_vigor(&self) -> u32 {
-        let mut max_vigor =
-            BASE_VIGOR + (self.attributes.stamina as f32 * MAX_VIGOR_STAT_CONTRIBUTION);
+        let mut max_vigor = self.balance.base_vigor
+            + (self.stamina() as f32 * self.balance.max_vigor_stat_contribution);
 
         for _ in 1..self.level {
-            max_vigor += max_vigor * MAX_VIGOR_LEVEL_CONTRIBUTION;
+            max_vigor += max_vigor * self.balance.max_vigor_level_contribution;
         }
 
-        f32::floor(max_vigor) as u32
+        f32::floor(self.modified(Stat::Vigor, max_vigor)).max(0.0) as u32
     }
 
     pub fn vigor_per_second(&self) -> u32 {
-        BASE_VIGOR_REGEN
-            + ((self.attributes.stamina as f32 * VIGOR_REGEN_STAT_CONTRIBUTION).floor() as u32)
+        let vigor_per_second = self.balance.base_vigor_regen as f32
+            + (self.stamina() as f32 * self.balance.vigor_regen_stat_contribution).floor();
+
+        self.modified(Stat::VigorRegen, vigor_per_second).max(0.0) as u32
     }
 
     pub fn attack_speed(&self) -> f32 {
+        let offense = self.effective_offense();
+
         f32::max(
-            BASE_ATTACK_SPEED / (1.0 + (self.offense.attack_speed as f32 * ATTACK_SPEED_FACTOR)),
-            ATTACK_SPEED_CAP,
+            self.balance.base_attack_speed
+                / (1.0 + (offense.attack_speed as f32 * self.balance.attack_speed_factor)),
+            self.balance.attack_speed_cap,
         )
     }
 
     pub fn auto_attack_speed(&self) -> f32 {
-        self.attack_speed() * AUTO_ATTACK_SPEED_FACTOR
+        self.attack_speed() * self.balance.auto_attack_speed_factor
     }
 
     pub fn auto_attack_damage(&self) -> u32 {
+        self.attack_damage(self.balance.base_auto_attack_damage)
+    }
+
+    /// Damage for an attack that starts from `base`, such as a weapon's
+    /// roll, before level and attributes are added.
+    pub fn attack_damage(&self, base: u32) -> u32 {
         let highest_stat = self
-            .attributes
-            .strength
-            .max(self.attributes.dexterity)
-            .max(self.attributes.intelligence);
+            .strength()
+            .max(self.dexterity())
+            .max(self.intelligence());
 
-        (BASE_AUTO_ATTACK_DAMAGE + (self.level * AUTO_ATTACK_LEVEL_CONTRIBUTION))
-            + (highest_stat as f32 * AUTO_ATTACK_STAT_CONTRIBUTION) as u32
+        (base + (self.level * self.balance.auto_attack_level_contribution))
+            + (highest_stat as f32 * self.balance.auto_attack_stat_contribution) as u32
     }
 
     pub fn dodge_chance(&self, manual_dodge: bool, difficulty: &f32) -> f32 {
@@ -151,21 +279,22 @@ impl Stats {
             1.0 - difficulty
         } else {
             f32::min(
-                BASE_DODGE_CHANCE
-                    + (self.attributes.dexterity as f32 * DODGE_CHANCE_DEXTERITY_CONTRIBUTION)
-                    + (self.defense.dodge_chance * DODGE_CHANCE_STAT_CONTRIBUTION)
+                self.balance.base_dodge_chance
+                    + (self.dexterity() as f32 * self.balance.dodge_chance_dexterity_contribution)
+                    + (self.effective_defense().dodge_chance
+                        * self.balance.dodge_chance_stat_contribution)
                     - difficulty,
-                DODGE_CHANCE_CAP,
+                self.balance.dodge_chance_cap,
             )
         }
     }
 
     pub fn dodge_cooldown(&self) -> f32 {
         f32::max(
-            BASE_DODGE_RATE
-                - (self.attributes.dexterity as f32 * DODGE_RATE_DEXTERITY_CONTRIBUTION)
-                - (self.defense.dodge_rate * DODGE_RATE_STAT_CONTRIBUTION),
-            DODGE_RATE_CAP,
+            self.balance.base_dodge_rate
+                - (self.dexterity() as f32 * self.balance.dodge_rate_dexterity_contribution)
+                - (self.effective_defense().dodge_rate * self.balance.dodge_rate_stat_contribution),
+            self.balance.dodge_rate_cap,
         )
     }
 
@@ -174,55 +303,75 @@ impl Stats {
             1.0 - difficulty
         } else {
             f32::min(
-                BASE_BLOCK_CHANCE
-                    + (self.attributes.strength as f32 * BLOCK_CHANCE_STRENGTH_CONTRIBUTION)
-                    + (self.defense.block_chance * BLOCK_CHANCE_STAT_CONTRIBUTION)
+                self.balance.base_block_chance
+                    + (self.strength() as f32 * self.balance.block_chance_strength_contribution)
+                    + (self.effective_defense().block_chance
+                        * self.balance.block_chance_stat_contribution)
                     - difficulty,
-                BLOCK_CHANCE_CAP,
+                self.balance.block_chance_cap,
             )
         }
     }
 
     pub fn block_cooldown(&self) -> f32 {
         f32::max(
-            BASE_BLOCK_RATE
-                - (self.attributes.strength as f32 * BLOCK_RATE_STRENGTH_CONTRIBUTION)
-                - (self.defense.block_rate * BLOCK_RATE_STAT_CONTRIBUTION),
-            BLOCK_RATE_CAP,
+            self.balance.base_block_rate
+                - (self.strength() as f32 * self.balance.block_rate_strength_contribution)
+                - (self.effective_defense().block_rate * self.balance.block_rate_stat_contribution),
+            self.balance.block_rate_cap,
         )
     }
 
     pub fn flee_chance(&self, dominance: &f32) -> f32 {
-        BASE_FLEE_CHANCE - (dominance * FLEE_CHANCE_DOMINANCE_CONTRIBUTION)
-            + (self.defense.fleet as f32 * FLEE_CHANCE_FLEET_CONTRIBUTION)
+        self.balance.base_flee_chance
+            - (dominance * self.balance.flee_chance_dominance_contribution)
+            + (self.fleet() as f32 * self.balance.flee_chance_fleet_contribution)
+    }
+
+    /// The chance of getting to a new distance before an opponent with the
+    /// given fleet can stop it.
+    pub fn reposition_chance(&self, opponent_fleet: u32) -> f32 {
+        let difference = self.fleet() as f32 - opponent_fleet as f32;
+
+        (self.balance.base_reposition_chance
+            + (difference * self.balance.reposition_chance_fleet_contribution))
+            .clamp(0.0, self.balance.reposition_chance_cap)
     }
 
     pub fn critical_strike_chance(&self) -> f32 {
         f32::min(
-            BASE_CRIT_STRIKE_CHANCE
-                + (self.offense.crit_strike_chance * CRIT_STRIKE_STAT_CONTRIBUTION),
-            CRIT_STRIKE_CHANCE_CAP,
+            self.balance.base_crit_strike_chance
+                + (self.effective_offense().crit_strike_chance
+                    * self.balance.crit_strike_stat_contribution),
+            self.balance.crit_strike_chance_cap,
         )
     }
 
     pub fn critical_strike_damage(&self) -> f32 {
-        BASE_CRIT_DAMAGE_MULTIPLIER
-            + (self.offense.crit_strike_damage * CRIT_DAMAGE_STAT_CONTRIBUTION)
+        self.balance.base_crit_damage_multiplier
+            + (self.effective_offense().crit_strike_damage
+                * self.balance.crit_damage_stat_contribution)
     }
 
-    pub fn resisted(&self, damage_kind: &DamageKind) -> u32 {
-        let resistances = self.resistance.0.iter().fold(0, |acc, (key, value)| {
-            if damage_kind.resistances.contains(key) {
-                acc + value
-            } else {
-                acc
-            }
-        });
+    /// The fraction of damage of the kind that is resisted.
+    pub fn resisted(&self, damage_kind: &DamageKind) -> f32 {
+        let resistances = self
+            .resistance
+            .0
+            .iter()
+            .chain(self.gear_resistance.0.iter())
+            .fold(0, |acc, (key, value)| {
+                if damage_kind.resistances.contains(key) {
+                    acc + value
+                } else {
+                    acc
+                }
+            });
 
-        f32::floor(f32::min(
-            resistances as f32 * RESISTANCE_FACTOR,
-            RESISTANCE_CAP,
-        )) as u32
+        f32::min(
+            resistances as f32 * self.balance.resistance_factor,
+            self.balance.resistance_cap,
+        )
     }
 }
 
@@ -230,18 +379,129 @@ impl Stats {
 pub struct Cooldowns(pub HashMap<String, (Entity, Timer)>);
 
 #[derive(Component, Reflect, Default, Clone)]
-pub struct Conditions(pub HashMap<String, Option<Timer>>);
+pub struct Conditions(pub HashMap<String, Vec<AppliedCondition>>);
+
+/// A single instance of a condition, along with whoever applied it.
+#[derive(Reflect, Clone, Debug)]
+pub struct AppliedCondition {
+    pub source: Entity,
+    pub stacks: u32,
+    pub duration: Option<Timer>,
+    /// Started the first time the condition is updated, if it ticks at all.
+    pub tick: Option<Timer>,
+}
+
+impl AppliedCondition {
+    pub fn new(source: Entity, duration: Option<f32>) -> Self {
+        Self {
+            source,
+            stacks: 1,
+            duration: duration.map(|duration| Timer::from_seconds(duration, TimerMode::Once)),
+            tick: None,
+        }
+    }
+}
+
+impl Conditions {
+    /// Applies a condition according to its stacking rules. Returns whether a
+    /// new instance was started rather than an existing one built upon.
+    pub fn apply(&mut self, condition: &Condition, source: Entity, duration: Option<f32>) -> bool {
+        let instances = self.0.entry(condition.id.clone()).or_default();
+
+        let existing = match condition.stacking {
+            Stacking::UniquePerSource => instances
+                .iter_mut()
+                .find(|instance| instance.source == source),
+            Stacking::Refresh | Stacking::Stack(_) => instances.first_mut(),
+        };
+
+        match existing {
+            Some(existing) => {
+                existing.source = source;
+                existing.duration =
+                    duration.map(|duration| Timer::from_seconds(duration, TimerMode::Once));
+
+                if let Stacking::Stack(max) = condition.stacking {
+                    existing.stacks = (existing.stacks + 1).min(max.max(1));
+                }
+
+                false
+            }
+            None => {
+                instances.push(AppliedCondition::new(source, duration));
+
+                true
+            }
+        }
+    }
+
+    /// Every crowd control flag imposed by the applied conditions, in order.
+    pub fn flags(&self, definitions: &data::resources::Conditions) -> Vec<ControlFlag> {
+        let mut flags = self
+            .0
+            .keys()
+            .filter_map(|id| definitions.0.get(id))
+            .flat_map(|condition| condition.flags.iter().copied())
+            .collect::<Vec<_>>();
+
+        flags.sort();
+        flags.dedup();
+
+        flags
+    }
+
+    pub fn stacks(&self, id: &str) -> u32 {
+        self.0
+            .get(id)
+            .map_or(0, |instances| instances.iter().map(|i| i.stacks).sum())
+    }
+}
 
 #[derive(Component, Reflect, Default, Clone)]
-pub struct Modifiers(pub HashMap<String, (Stat, f32)>);
+pub struct Modifiers(pub HashMap<String, Modifier>);
 
 impl Modifiers {
+    /// The total of every flat modifier to the stat.
     pub fn sum_stat(&self, stat: &Stat) -> f32 {
         self.0
             .values()
-            .filter_map(|(s, v)| if s == stat { Some(*v) } else { None })
+            .filter(|modifier| modifier.stat == *stat && modifier.kind == ModifierKind::Flat)
+            .map(|modifier| modifier.amount)
             .sum()
     }
+
+    pub fn bonuses(&self) -> StatBonuses {
+        let mut bonuses = StatBonuses::default();
+
+        for modifier in self.0.values() {
+            let (flat, percent) = bonuses.0.entry(modifier.stat.clone()).or_default();
+
+            match modifier.kind {
+                ModifierKind::Flat => *flat += modifier.amount,
+                ModifierKind::Percent => *percent += modifier.amount,
+            }
+        }
+
+        bonuses
+    }
+
+    /// Brings a condition's modifiers in line with how many stacks of it
+    /// are applied, removing them entirely at zero.
+    pub fn sync_condition(&mut self, condition: &Condition, stacks: u32) {
+        for (id, modifier) in condition.keyed_modifiers() {
+            if stacks == 0 {
+                self.0.remove(&id);
+            } else {
+                self.0.insert(
+                    id,
+                    Modifier {
+                        amount: modifier.amount * stacks as f32,
+                        ..modifier
+                    },
+                );
+            }
+        }
+    }
 }
 
 #[derive(Component, Clone)]
@@ -249,6 +509,125 @@ pub struct CombatState {
     pub target: Entity,
     pub distance: Distance,
     pub approach: Approach,
+    pub threat: ThreatTable,
+}
+
+impl CombatState {
+    pub fn new(target: Entity, distance: Distance) -> Self {
+        let mut threat = ThreatTable::default();
+        threat.add(target, 0.0);
+
+        Self {
+            target,
+            distance,
+            approach: Approach::Front,
+            threat,
+        }
+    }
+}
+
+/// The skills a player has learned from their mastery's tree.
+#[derive(Component, Default, Debug, Clone)]
+pub struct LearnedSkills(pub BTreeSet<String>);
+
+/// How a player's current fight is going, and how their last one went, for
+/// the `combatstats` command and client meters.
+#[derive(Component, Default, Debug)]
+pub struct CombatMeter {
+    pub current: Option<FightStats>,
+    pub last: Option<FightStats>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize)]
+pub struct FightStats {
+    pub duration: f32,
+    pub dealt: DamageTally,
+    pub taken: DamageTally,
+}
+
+/// Damage going one way, broken down by where it came from, along with how
+/// the attacks behind it turned out.
+#[derive(Default, Debug, Clone, PartialEq, Serialize)]
+pub struct DamageTally {
+    pub total: u32,
+    pub by_skill: BTreeMap<String, u32>,
+    pub by_kind: BTreeMap<String, u32>,
+    pub hits: u32,
+    pub crits: u32,
+    pub misses: u32,
+    pub dodges: u32,
+    pub blocks: u32,
+}
+
+impl DamageTally {
+    pub fn add(&mut self, damage: u32, origin: Option<&str>, kind: &str, crit: bool) {
+        self.total += damage;
+
+        *self
+            .by_skill
+            .entry(origin.unwrap_or("other").into())
+            .or_default() += damage;
+        *self.by_kind.entry(kind.into()).or_default() += damage;
+
+        if crit {
+            self.crits += 1;
+        }
+    }
+
+    /// Counts an attack by how it turned out, ignoring anything that isn't
+    /// an outcome.
+    pub fn record(&mut self, phase: &ExecutionPhase) {
+        match phase {
+            ExecutionPhase::OnHit => self.hits += 1,
+            ExecutionPhase::OnMiss => self.misses += 1,
+            ExecutionPhase::OnDodge => self.dodges += 1,
+            ExecutionPhase::OnBlock => self.blocks += 1,
+            _ => (),
+        }
+    }
+}
+
+/// A duel someone has offered this player, holding the challenger until it's
+/// accepted or declined.
+#[derive(Component, Debug)]
+pub struct DuelChallenge(pub Entity);
+
+/// Marks a player fighting a duel, holding their opponent. Duels end when one
+/// side is beaten down rather than killed.
+#[derive(Component, Debug)]
+pub struct Duel(pub Entity);
+
+/// Every opponent an entity is engaged with, and how much threat
+/// each of them has generated against it.
+#[derive(Clone, Default, Debug)]
+pub struct ThreatTable(pub HashMap<Entity, f32>);
+
+impl ThreatTable {
+    pub fn add(&mut self, entity: Entity, amount: f32) {
+        *self.0.entry(entity).or_default() += amount;
+    }
+
+    /// Puts the entity at the top of the table, ahead of the current highest.
+    pub fn taunt(&mut self, entity: Entity, bonus: f32) {
+        let highest = self.0.values().copied().fold(0.0, f32::max);
+
+        self.0.insert(entity, highest + bonus);
+    }
+
+    pub fn highest(&self) -> Option<Entity> {
+        self.0
+            .iter()
+            .max_by(|(a, a_threat), (b, b_threat)| {
+                a_threat
+                    .total_cmp(b_threat)
+                    .then_with(|| b.index().cmp(&a.index()))
+            })
+            .map(|(entity, _)| *entity)
+    }
+
+    pub fn contains(&self, entity: &Entity) -> bool {
+        self.0.contains_key(entity)
+    }
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
@@ -268,7 +647,7 @@ impl Display for Distance {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
 pub enum Approach {
     Front,
     Rear,
@@ -307,6 +686,14 @@ pub struct BlockCooldown(pub Timer);
 #[derive(Component)]
 pub struct FleeTimer(pub Timer);
 
+/// Moving to a new distance from the target. Once the timer runs out, the
+/// target gets a chance to keep them where they are.
+#[derive(Component)]
+pub struct Repositioning {
+    pub distance: Distance,
+    pub timer: Timer,
+}
+
 #[derive(Component, Reflect, Clone)]
 pub struct HealthRegenTimer(pub Timer);
 
@@ -324,3 +711,104 @@ impl Default for VigorRegenTimer {
         Self(Timer::from_seconds(VIGOR_REGEN_TICK, TimerMode::Repeating))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn condition(stacking: Stacking) -> Condition {
+        Condition {
+            id: "bleeding".into(),
+            name: "Bleeding".into(),
+            description: "".into(),
+            scripts: vec![],
+            modifiers: vec![(Stat::Dexterity, -1.0)],
+            percent_modifiers: vec![],
+            tick: Some(3.0),
+            stacking,
+            tags: vec!["bleed".into()],
+            flags: vec![],
+        }
+    }
+
+    #[test]
+    fn refreshes() {
+        let condition = condition(Stacking::Refresh);
+        let mut conditions = Conditions::default();
+
+        assert!(conditions.apply(&condition, Entity::from_raw(1), Some(5.0)));
+        assert!(!conditions.apply(&condition, Entity::from_raw(2), Some(10.0)));
+
+        let instances = conditions.0.get("bleeding").unwrap();
+
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0].source, Entity::from_raw(2));
+        assert_eq!(
+            instances[0].duration.as_ref().unwrap().remaining_secs(),
+            10.0
+        );
+        assert_eq!(conditions.stacks("bleeding"), 1);
+    }
+
+    #[test]
+    fn stacks_up_to_max() {
+        let condition = condition(Stacking::Stack(2));
+        let mut conditions = Conditions::default();
+
+        for _ in 0..3 {
+            conditions.apply(&condition, Entity::from_raw(1), Some(5.0));
+        }
+
+        assert_eq!(conditions.0.get("bleeding").unwrap().len(), 1);
+        assert_eq!(conditions.stacks("bleeding"), 2);
+
+        let mut modifiers = Modifiers::default();
+        modifiers.sync_condition(&condition, conditions.stacks("bleeding"));
+
+        assert_eq!(modifiers.sum_stat(&Stat::Dexterity), -2.0);
+
+        modifiers.sync_condition(&condition, 0);
+
+        assert!(modifiers.0.is_empty());
+    }
+
+    #[test]
+    fn modifiers_feed_derived_stats() {
+        let mut stats = Stats::default();
+        stats.attributes.vitality = 10;
+
+        let base_health = stats.max_health();
+        let base_dodge = stats.dodge_chance(false, &0.0);
+
+        let mut modifiers = Modifiers::default();
+        modifiers
+            .0
+            .insert("sturdy".into(), Modifier::flat(Stat::Vitality, 10.0));
+        modifiers
+            .0
+            .insert("frail".into(), Modifier::percent(Stat::Vitality, -0.5));
+        modifiers
+            .0
+            .insert("nimble".into(), Modifier::flat(Stat::Dexterity, 5.0));
+
+        stats.bonuses = modifiers.bonuses();
+
+        assert_eq!(stats.vitality(), 10);
+        assert_eq!(stats.max_health(), base_health);
+        assert_eq!(stats.dexterity(), 5);
+        assert!(stats.dodge_chance(false, &0.0) > base_dodge);
+    }
+
+    #[test]
+    fn unique_per_source() {
+        let condition = condition(Stacking::UniquePerSource);
+        let mut conditions = Conditions::default();
+
+        assert!(conditions.apply(&condition, Entity::from_raw(1), Some(5.0)));
+        assert!(conditions.apply(&condition, Entity::from_raw(2), Some(5.0)));
+        assert!(!conditions.apply(&condition, Entity::from_raw(1), Some(5.0)));
+
+        assert_eq!(conditions.0.get("bleeding").unwrap().len(), 2);
+        assert_eq!(conditions.stacks("bleeding"), 2);
+    }
+}
@@ -2,7 +2,7 @@ use bevy::prelude::*;
 use uuid::Uuid;
 
 use crate::{
-    data::resources::{Condition, Skill, Stat},
+    data::resources::{Condition, ModifierKind, Skill, Stat},
     lua::{context::ExecutionContext, events::ExecutionPhase},
 };
 
@@ -15,11 +15,27 @@ pub struct CombatEvent {
     pub kind: CombatEventKind,
 }
 
+/// Sent once damage has actually landed, after crits and resistances.
+#[derive(Event, Clone, Debug)]
+pub struct DamageEvent {
+    pub source: Entity,
+    pub target: Entity,
+    pub damage: u32,
+    pub kind: String,
+    pub crit: bool,
+    /// The name of the skill or condition that dealt it.
+    pub origin: Option<String>,
+    /// Whether a skill dealt it, rather than something like a condition
+    /// ticking. Only skills set off reactions.
+    pub from_skill: bool,
+}
+
 #[derive(Clone, Debug)]
 pub enum CombatEventTrigger {
     Skill(Skill),
     Condition(Condition),
     Movement,
+    Death,
 }
 
 #[derive(Debug)]
@@ -29,10 +45,15 @@ pub enum CombatEventKind {
     AttemptDodge,
     AttemptBlock,
     ExecuteScripts(ExecutionPhase),
-    ExecuteCondition(ExecutionPhase),
+    ExecuteCondition(ExecuteCondition),
+    ExecuteReaction(ExecuteReaction),
     AttemptFlee(String),
+    AttemptReposition(Distance),
     ApplyDamage(ApplyDamage),
+    ApplyHeal(ApplyHeal),
+    Taunt(Taunt),
     ApplyCondition(ApplyCondition),
+    Dispel(Dispel),
     SetDistance(SetDistance),
     SetApproach(SetApproach),
     AddStatModifier(AddStatModifier),
@@ -45,7 +66,18 @@ pub struct ApplyDamage {
     pub target: Entity,
     pub damage: f32,
     pub kind: String,
-    pub with_callback: Option<WithCallback>,
+    pub with_callback: Option<Box<WithCallback>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ApplyHeal {
+    pub target: Entity,
+    pub amount: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Taunt {
+    pub target: Entity,
 }
 
 #[derive(Clone, Debug)]
@@ -55,6 +87,30 @@ pub struct ApplyCondition {
     pub duration: Option<f32>,
 }
 
+/// Runs a condition's scripts for the given phase. The event's source is
+/// whoever applied the condition, and the target is the one affected by it.
+#[derive(Clone, Debug)]
+pub struct ExecuteCondition {
+    pub target: Entity,
+    pub phase: ExecutionPhase,
+}
+
+/// Runs a reactive skill's scripts for the given phase. The event's source is
+/// the defender reacting, and the target is whoever attacked them.
+#[derive(Clone, Debug)]
+pub struct ExecuteReaction {
+    pub target: Entity,
+    pub phase: ExecutionPhase,
+}
+
+/// Removes every condition from the target matching the ID or tag.
+#[derive(Clone, Debug)]
+pub struct Dispel {
+    pub target: Entity,
+    pub id: Option<String>,
+    pub tag: Option<String>,
+}
+
 #[derive(Clone, Debug)]
 pub struct SetDistance {
     pub target: Entity,
@@ -73,6 +129,7 @@ pub struct AddStatModifier {
     pub id: String,
     pub stat: Stat,
     pub amount: f32,
+    pub kind: ModifierKind,
 }
 
 #[derive(Clone, Debug)]
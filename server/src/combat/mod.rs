@@ -4,3 +4,4 @@ pub mod components;
 pub mod events;
 pub mod plugin;
 mod systems;
+pub mod utils;
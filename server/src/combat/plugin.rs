@@ -2,7 +2,10 @@ use bevy::prelude::*;
 
 use super::{
     bundles::*,
-    commands::{advance::*, attack::*, block::*, dodge::*, retreat::*, use_skill::*},
+    commands::{
+        advance::*, attack::*, block::*, combat_stats::*, dodge::*, duel::*, retreat::*, skills::*,
+        use_skill::*,
+    },
     components::*,
     events::*,
     systems::*,
@@ -23,6 +26,7 @@ impl Plugin for CombatPlugin {
         app.register_type::<CombatBundle>();
 
         app.add_event::<CombatEvent>();
+        app.add_event::<DamageEvent>();
 
         app.add_systems(
             Update,
@@ -31,9 +35,11 @@ impl Plugin for CombatPlugin {
                     attack,
                     use_skill,
                     start_auto_attacks,
+                    update_auto_attack_speed,
                     handle_auto_attack,
                     stop_auto_attacks,
                     dodge,
+                    duel,
                     block,
                     advance,
                     retreat,
@@ -44,13 +50,17 @@ impl Plugin for CombatPlugin {
                     on_combat_event_attempt_dodge,
                     on_combat_event_attempt_block,
                     on_combat_event_execute_scripts,
+                    trigger_reactions,
                     on_combat_event_attempt_flee,
+                    on_combat_event_attempt_reposition,
                     on_combat_event_apply_damage,
-                    on_combat_event_apply_condition,
+                    on_combat_event_apply_heal,
+                    on_combat_event_taunt,
+                    (on_combat_event_apply_condition, on_combat_event_dispel),
                     on_combat_event_set_distance,
                     on_combat_event_set_approach,
                     on_combat_event_add_stat_modifier,
-                    on_combat_event_add_stat_modifier,
+                    on_combat_event_remove_stat_modifier,
                     on_combat_event_combat_log,
                 ),
                 (
@@ -60,14 +70,32 @@ impl Plugin for CombatPlugin {
                     update_manual_block_timer,
                     update_block_timer,
                     update_flee_timer,
+                    update_repositioning,
                     update_condition_timer,
                     health_regen,
                     vigor_regen,
                     update_cooldowns,
                     on_hostile_death,
                     on_player_death,
+                    end_duels,
+                    learn_skills,
+                ),
+                (
+                    update_combat_meters,
+                    record_combat_stats,
+                    end_combat_meters,
+                    combat_stats,
+                    skills,
                 ),
             ),
         );
+
+        app.add_systems(
+            PostUpdate,
+            (
+                update_threat_tables,
+                (apply_balance, apply_modifiers).chain(),
+            ),
+        );
     }
 }
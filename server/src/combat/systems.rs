@@ -1,22 +1,32 @@
+use std::{sync::Arc, time::Duration};
+
 use anyhow::Context;
-use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
 use bevy_mod_sysfail::sysfail;
 use bevy_nest::prelude::*;
 use inflector::Inflector;
 use mlua::prelude::*;
+use rand::Rng;
 
 use crate::{
     data::{
         self,
-        resources::{DamageKinds, Masteries, Skill, Skills, Stat},
+        resources::{
+            Balance, ControlFlag, DamageKinds, Masteries, Modifier, Reaction, Skill, Skills,
+        },
     },
     input::events::{Command, ParsedCommand, ProxyCommand},
+    items::{
+        bundles::CorpseBundle,
+        components::{Equipment, Inventory, PlayerCorpse, UnrolledLoot, Weapon},
+    },
     lua::{
         context::{ExecutionContext, ExecutionKind},
         events::{ApplyDamageResponse, ExecutionEvent, ExecutionPhase},
     },
     npc::components::Hostile,
     paint,
+    party::components::Party,
     player::{
         components::{Character, Client, Online},
         events::Prompt,
@@ -25,30 +35,49 @@ use crate::{
         components::{DeathSpawn, Tile},
         events::{MovementEvent, MovementEventKind},
     },
-    values::FLEE_COOLDOWN,
     visual::components::Depiction,
+    world::resources::GameRng,
 };
 
 use super::{
     components::{
-        AttackTimer, AutoAttackTimer, BlockCooldown, CombatState, Conditions, Cooldowns, Distance,
-        DodgeCooldown, FleeTimer, HealthRegenTimer, ManualBlock, ManualDodge, Modifiers,
-        QueuedAttack, Stats, VigorRegenTimer,
+        Approach, AttackTimer, AutoAttackTimer, BlockCooldown, CombatMeter, CombatState,
+        Conditions, Cooldowns, Distance, DodgeCooldown, Duel, FightStats, FleeTimer,
+        HealthRegenTimer, LearnedSkills, ManualBlock, ManualDodge, Modifiers, QueuedAttack,
+        Repositioning, Stats, VigorRegenTimer,
     },
-    events::{CombatEvent, CombatEventKind, CombatEventTrigger, CombatLogKind, WithCallback},
+    events::{
+        ApplyCondition, CombatEvent, CombatEventKind, CombatEventTrigger, CombatLogKind,
+        DamageEvent, ExecuteCondition, ExecuteReaction, SetDistance, WithCallback,
+    },
+    utils::{capitalize, fighter_name},
 };
 
 #[derive(SystemParam)]
 pub struct CharacterOrHostile<'w, 's> {
     characters: Query<'w, 's, &'static Character>,
+    learned: Query<'w, 's, &'static LearnedSkills>,
     hostiles: Query<'w, 's, &'static Hostile>,
+    equipment: Query<'w, 's, &'static Equipment>,
+    weapons: Query<'w, 's, &'static Weapon>,
     masteries: Res<'w, Masteries>,
     skills: Res<'w, Skills>,
 }
 
 impl<'w, 's> CharacterOrHostile<'w, 's> {
+    /// The wielded weapon's auto-attack if it has one, otherwise the
+    /// mastery's or hostile's.
     fn get_auto_attack(&self, entity: Entity) -> Result<Skill, anyhow::Error> {
-        let skill_id = if let Ok(character) = self.characters.get(entity) {
+        let weapon_skill = self
+            .equipment
+            .get(entity)
+            .ok()
+            .and_then(|equipment| equipment.weapon(&self.weapons))
+            .and_then(|weapon| weapon.auto_attack.clone());
+
+        let skill_id = if let Some(skill_id) = weapon_skill {
+            Ok(skill_id)
+        } else if let Ok(character) = self.characters.get(entity) {
             Ok(self
                 .masteries
                 .0
@@ -72,32 +101,103 @@ impl<'w, 's> CharacterOrHostile<'w, 's> {
 
         Ok(skill.clone())
     }
+
+    /// Every reactive skill the entity knows.
+    fn get_reactions(&self, entity: Entity) -> Vec<&Skill> {
+        let ids: Vec<&String> = if let Ok(learned) = self.learned.get(entity) {
+            learned.0.iter().collect()
+        } else {
+            self.hostiles
+                .get(entity)
+                .map(|hostile| hostile.skills.iter().collect())
+                .unwrap_or_default()
+        };
+
+        ids.into_iter()
+            .filter_map(|id| self.skills.0.get(id))
+            .filter(|skill| skill.reaction.is_some())
+            .collect()
+    }
+}
+
+/// How long the fighter waits between auto attacks, scaled by the speed of
+/// whatever they're wielding.
+fn auto_attack_interval(
+    stats: &Stats,
+    equipment: Option<&Equipment>,
+    weapons: &Query<&Weapon>,
+) -> Duration {
+    let speed = equipment
+        .and_then(|equipment| equipment.weapon(weapons))
+        .map_or(1.0, |weapon| weapon.speed);
+
+    Duration::from_secs_f32(stats.auto_attack_speed() * speed)
 }
 
 pub fn start_auto_attacks(
     mut bevy: Commands,
-    fighters: Query<(Entity, &Stats), Added<CombatState>>,
+    fighters: Query<(Entity, &Stats, Option<&Equipment>), Added<CombatState>>,
+    weapons: Query<&Weapon>,
+) {
+    for (entity, stats, equipment) in fighters.iter() {
+        bevy.entity(entity).insert(AutoAttackTimer(Timer::new(
+            auto_attack_interval(stats, equipment, &weapons),
+            TimerMode::Repeating,
+        )));
+    }
+}
+
+/// Keeps auto attacks in step with attack speed as it changes mid-fight,
+/// such as from a condition, a Lua modifier or wielding another weapon.
+pub fn update_auto_attack_speed(
+    mut fighters: Query<
+        (&Stats, Option<&Equipment>, &mut AutoAttackTimer),
+        Or<(Changed<Stats>, Changed<Equipment>)>,
+    >,
+    weapons: Query<&Weapon>,
 ) {
-    for (entity, stats) in fighters.iter() {
-        bevy.entity(entity)
-            .insert(AutoAttackTimer(Timer::from_seconds(
-                stats.auto_attack_speed(),
-                TimerMode::Repeating,
-            )));
+    for (stats, equipment, mut timer) in fighters.iter_mut() {
+        let interval = auto_attack_interval(stats, equipment, &weapons);
+
+        if timer.0.duration() != interval {
+            timer.0.set_duration(interval);
+        }
     }
 }
 
 #[sysfail(log)]
 pub fn handle_auto_attack(
     time: Res<Time>,
-    mut fighters: Query<(Entity, &mut AutoAttackTimer)>,
+    mut fighters: Query<(
+        Entity,
+        &mut AutoAttackTimer,
+        Option<&CombatState>,
+        Option<&Conditions>,
+    )>,
     mut events: EventWriter<CombatEvent>,
     character_or_hostile: CharacterOrHostile,
+    conditions: Res<data::resources::Conditions>,
 ) -> Result<(), anyhow::Error> {
-    for (entity, mut timer) in fighters.iter_mut() {
+    for (entity, mut timer, combat_state, applied) in fighters.iter_mut() {
         let skill = character_or_hostile.get_auto_attack(entity)?;
 
         if timer.0.tick(time.delta()).just_finished() {
+            // Auto attacks hold off until their target is within reach.
+            if !combat_state.is_some_and(|combat_state| skill.reaches(combat_state.distance)) {
+                continue;
+            }
+
+            let restricted = applied.is_some_and(|applied| {
+                applied
+                    .flags(&conditions)
+                    .iter()
+                    .any(|flag| matches!(flag, ControlFlag::Stunned | ControlFlag::Disarmed))
+            });
+
+            if restricted {
+                continue;
+            }
+
             events.send(CombatEvent {
                 source: entity,
                 trigger: CombatEventTrigger::Skill(skill),
@@ -109,6 +209,56 @@ pub fn handle_auto_attack(
     Ok(())
 }
 
+/// Drops anyone from a threat table who is no longer fighting on the same tile,
+/// points hostiles at whoever has generated the most threat, and ends combat
+/// once there's no one left to fight.
+pub fn update_threat_tables(
+    mut bevy: Commands,
+    mut fighters: Query<(Entity, &mut CombatState, &Stats, &Parent, Option<&Hostile>)>,
+) {
+    let standing: HashMap<Entity, Entity> = fighters
+        .iter()
+        .filter(|(_, _, stats, _, _)| stats.status.health > 0)
+        .map(|(entity, _, _, parent, _)| (entity, parent.get()))
+        .collect();
+
+    for (entity, mut combat_state, stats, parent, hostile) in fighters.iter_mut() {
+        if stats.status.health == 0 {
+            continue;
+        }
+
+        let tile = parent.get();
+
+        if combat_state
+            .threat
+            .0
+            .keys()
+            .any(|opponent| standing.get(opponent) != Some(&tile))
+        {
+            combat_state
+                .threat
+                .0
+                .retain(|opponent, _| standing.get(opponent) == Some(&tile));
+        }
+
+        let Some(highest) = combat_state.threat.highest() else {
+            bevy.entity(entity).remove::<CombatState>();
+
+            continue;
+        };
+
+        let retarget = if hostile.is_some() {
+            combat_state.target != highest
+        } else {
+            !combat_state.threat.contains(&combat_state.target)
+        };
+
+        if retarget {
+            combat_state.target = highest;
+        }
+    }
+}
+
 pub fn stop_auto_attacks(
     mut bevy: Commands,
     mut ready: RemovedComponents<CombatState>,
@@ -177,18 +327,27 @@ pub fn on_combat_event_attack(
 #[sysfail(log)]
 pub fn on_combat_event_attempt_hit(
     mut combat_events: ParamSet<(EventReader<CombatEvent>, EventWriter<CombatEvent>)>,
-    fighters: Query<&CombatState>,
+    mut outbox: EventWriter<Outbox>,
+    fighters: Query<(&CombatState, Option<&Client>)>,
 ) -> Result<(), anyhow::Error> {
     let mut events_to_send: Vec<CombatEvent> = vec![];
 
     for event in combat_events.p0().iter() {
         if let CombatEventKind::AttemptHit = &event.kind {
             if let CombatEventTrigger::Skill(skill) = &event.trigger {
-                let source_combat_state = fighters.get(event.source)?;
+                let (source_combat_state, source_client) = fighters.get(event.source)?;
+
+                // The distance changed since the skill was used, so it never
+                // gets as far as missing.
+                if !skill.reaches(source_combat_state.distance) {
+                    if let Some(client) = source_client {
+                        outbox.send_text(client.id, skill.out_of_range());
+                    }
+
+                    continue;
+                }
 
-                if skill.distance != Distance::Either
-                    && source_combat_state.distance != skill.distance
-                {
+                if !skill.approach.allows(source_combat_state.approach) {
                     events_to_send.push(CombatEvent {
                         source: event.source,
                         trigger: event.trigger.clone(),
@@ -222,16 +381,26 @@ pub fn on_combat_event_attempt_dodge(
         Option<&ManualDodge>,
         Option<&DodgeCooldown>,
     )>,
+    balance: Res<Balance>,
+    mut rng: ResMut<GameRng>,
 ) -> Result<(), anyhow::Error> {
     let mut events_to_send: Vec<CombatEvent> = vec![];
 
     for event in combat_events.p0().iter() {
         if let CombatEventKind::AttemptDodge = &event.kind {
             let (source_combat_state, _, _, _) = fighters.get(event.source)?;
-            let (_, target_stats, target_manual_dodge, target_dodge_timer) =
+            let (_, target_stats, target_manual_dodge, target_dodge_cooldown) =
                 fighters.get(source_combat_state.target)?;
 
-            if target_dodge_timer.is_some() {
+            // Still recovering from their last dodge, so the attack goes on to
+            // be blocked or land.
+            if target_dodge_cooldown.is_some() && target_manual_dodge.is_none() {
+                events_to_send.push(CombatEvent {
+                    source: event.source,
+                    trigger: event.trigger.clone(),
+                    kind: CombatEventKind::AttemptBlock,
+                });
+
                 continue;
             }
 
@@ -240,11 +409,20 @@ pub fn on_combat_event_attempt_dodge(
                 _ => None,
             };
 
-            let difficulty = skill.map(|skill| skill.dodge_difficulty).unwrap_or(0.0);
+            let approach = source_combat_state.approach;
+
+            let mut difficulty = skill
+                .map(|skill| skill.dodge_difficulty + skill.approach.difficulty(approach, &balance))
+                .unwrap_or(0.0);
+
+            if approach == Approach::Rear {
+                difficulty += balance.rear_dodge_difficulty;
+            }
+
             let dodge_chance =
                 target_stats.dodge_chance(target_manual_dodge.is_some(), &difficulty);
 
-            let target_number = rand::random::<f32>();
+            let target_number = rng.gen::<f32>();
 
             if target_number <= dodge_chance {
                 if let CombatEventTrigger::Skill(_) = &event.trigger {
@@ -286,16 +464,24 @@ pub fn on_combat_event_attempt_block(
         Option<&ManualBlock>,
         Option<&BlockCooldown>,
     )>,
+    balance: Res<Balance>,
+    mut rng: ResMut<GameRng>,
 ) -> Result<(), anyhow::Error> {
     let mut events_to_send: Vec<CombatEvent> = vec![];
 
     for event in combat_events.p0().iter() {
         if let CombatEventKind::AttemptBlock = &event.kind {
             let (source_combat_state, _, _, _) = fighters.get(event.source)?;
-            let (_, target_stats, target_manual_block, target_dodge_timer) =
+            let (_, target_stats, target_manual_block, target_block_cooldown) =
                 fighters.get(source_combat_state.target)?;
 
-            if target_dodge_timer.is_some() {
+            if target_block_cooldown.is_some() && target_manual_block.is_none() {
+                events_to_send.push(CombatEvent {
+                    source: event.source,
+                    trigger: event.trigger.clone(),
+                    kind: CombatEventKind::ExecuteScripts(ExecutionPhase::OnHit),
+                });
+
                 continue;
             }
 
@@ -304,13 +490,18 @@ pub fn on_combat_event_attempt_block(
                 _ => None,
             };
 
-            let difficulty = skill.map(|skill| skill.block_difficulty).unwrap_or(0.0);
+            let approach = source_combat_state.approach;
+
+            let difficulty = skill
+                .map(|skill| skill.block_difficulty + skill.approach.difficulty(approach, &balance))
+                .unwrap_or(0.0);
             let block_chance =
                 target_stats.block_chance(target_manual_block.is_some(), &difficulty);
 
-            let target_number = rand::random::<f32>();
+            let target_number = rng.gen::<f32>();
 
-            if target_number <= block_chance {
+            // There's no blocking what you can't see coming.
+            if approach == Approach::Front && target_number <= block_chance {
                 if let CombatEventTrigger::Skill(_) = &event.trigger {
                     events_to_send.push(CombatEvent {
                         source: event.source,
@@ -346,6 +537,7 @@ pub fn on_combat_event_execute_scripts(
     mut event_reader: EventReader<CombatEvent>,
     mut executions: EventWriter<ExecutionEvent>,
     combat_states: Query<&CombatState>,
+    fighters: Query<(), With<Stats>>,
 ) -> Result<(), anyhow::Error> {
     for event in event_reader.iter() {
         if let CombatEventKind::ExecuteScripts(phase) = &event.kind {
@@ -379,6 +571,153 @@ pub fn on_combat_event_execute_scripts(
                 _ => (),
             }
         }
+
+        if let CombatEventKind::ExecuteReaction(args) = &event.kind {
+            if let CombatEventTrigger::Skill(skill) = &event.trigger {
+                executions.send(ExecutionEvent {
+                    context: ExecutionContext::with_sandbox(
+                        &lua,
+                        ExecutionKind::Skill(skill.clone()),
+                        event.source,
+                        args.target,
+                    ),
+                    scripts: skill.scripts.clone(),
+                    phase: args.phase.clone(),
+                });
+            }
+        }
+
+        if let CombatEventKind::ExecuteCondition(args) = &event.kind {
+            if let CombatEventTrigger::Condition(condition) = &event.trigger {
+                // Whoever applied the condition may be long gone by the time
+                // it ticks or ends, in which case it's the target's own doing.
+                let source = if fighters.contains(event.source) {
+                    event.source
+                } else {
+                    args.target
+                };
+
+                executions.send(ExecutionEvent {
+                    context: ExecutionContext::with_sandbox(
+                        &lua,
+                        ExecutionKind::Condition(condition.clone()),
+                        source,
+                        args.target,
+                    ),
+                    scripts: condition.scripts.clone(),
+                    phase: args.phase.clone(),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Sets off the defender's reactive skills once an attack has been blocked,
+/// dodged or has landed, paying for them the way an active skill would.
+#[sysfail(log)]
+pub fn trigger_reactions(
+    mut combat_events: ParamSet<(EventReader<CombatEvent>, EventWriter<CombatEvent>)>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut fighters: Query<(&mut Stats, &mut Cooldowns, Option<&Conditions>)>,
+    combat_states: Query<&CombatState>,
+    character_or_hostile: CharacterOrHostile,
+    conditions: Res<data::resources::Conditions>,
+) -> Result<(), anyhow::Error> {
+    // The defender, whoever they're reacting to, and what happened.
+    let mut attacks: Vec<(Entity, Entity, ExecutionPhase)> = vec![];
+
+    for event in combat_events.p0().iter() {
+        let (CombatEventKind::ExecuteScripts(phase), CombatEventTrigger::Skill(_)) =
+            (&event.kind, &event.trigger)
+        else {
+            continue;
+        };
+
+        if matches!(phase, ExecutionPhase::OnBlock | ExecutionPhase::OnDodge) {
+            // The fight may have ended this frame, taking the attacker's target with it.
+            let Ok(combat_state) = combat_states.get(event.source) else {
+                continue;
+            };
+
+            attacks.push((combat_state.target, event.source, phase.clone()));
+        }
+    }
+
+    for event in damage_events.iter() {
+        if event.from_skill && event.source != event.target {
+            attacks.push((event.target, event.source, ExecutionPhase::OnHit));
+        }
+    }
+
+    let mut events_to_send: Vec<CombatEvent> = vec![];
+
+    for (defender, attacker, phase) in attacks {
+        let Ok((mut stats, mut cooldowns, applied)) = fighters.get_mut(defender) else {
+            continue;
+        };
+
+        if stats.status.health == 0 {
+            continue;
+        }
+
+        let restricted = applied.is_some_and(|applied| {
+            applied
+                .flags(&conditions)
+                .iter()
+                .any(|flag| matches!(flag, ControlFlag::Stunned | ControlFlag::Silenced))
+        });
+
+        if restricted {
+            continue;
+        }
+
+        for skill in character_or_hostile.get_reactions(defender) {
+            let Some(reaction) = skill.reaction else {
+                continue;
+            };
+
+            let triggered = match (reaction, &phase) {
+                (Reaction::Block, ExecutionPhase::OnBlock) => true,
+                (Reaction::Dodge, ExecutionPhase::OnDodge) => true,
+                (Reaction::Hit, ExecutionPhase::OnHit) => true,
+                (Reaction::HitBelow(fraction), ExecutionPhase::OnHit) => {
+                    (stats.status.health as f32) < stats.max_health() as f32 * fraction
+                }
+                _ => false,
+            };
+
+            if !triggered || cooldowns.0.contains_key(&skill.id) || skill.cost > stats.status.vigor
+            {
+                continue;
+            }
+
+            stats.status.vigor -= skill.cost;
+
+            if skill.cooldown > 0 {
+                cooldowns.0.insert(
+                    skill.id.clone(),
+                    (
+                        defender,
+                        Timer::from_seconds(skill.cooldown as f32, TimerMode::Once),
+                    ),
+                );
+            }
+
+            events_to_send.push(CombatEvent {
+                source: defender,
+                trigger: CombatEventTrigger::Skill(skill.clone()),
+                kind: CombatEventKind::ExecuteReaction(ExecuteReaction {
+                    target: attacker,
+                    phase: reaction.phase(),
+                }),
+            });
+        }
+    }
+
+    for event in events_to_send {
+        combat_events.p1().send(event);
     }
 
     Ok(())
@@ -389,31 +728,39 @@ pub fn on_combat_event_attempt_flee(
     mut bevy: Commands,
     mut event_reader: EventReader<CombatEvent>,
     mut movement_events: EventWriter<MovementEvent>,
-    fighters: Query<(Option<&Client>, &CombatState, &Stats, Option<&FleeTimer>)>,
+    fighters: Query<(
+        Option<&Client>,
+        &CombatState,
+        &Stats,
+        Option<&FleeTimer>,
+        Option<&Depiction>,
+    )>,
     mut outbox: EventWriter<Outbox>,
+    balance: Res<Balance>,
+    mut rng: ResMut<GameRng>,
 ) -> Result<(), anyhow::Error> {
     for event in event_reader.iter() {
         if let CombatEventKind::AttemptFlee(direction) = &event.kind {
-            let (source_client, source_combat_state, source_stats, source_flee_timer) =
+            let (source_client, source_combat_state, source_stats, source_flee_timer, depiction) =
                 fighters.get(event.source)?;
-            let (_, _, target_stats, _) = fighters.get(source_combat_state.target)?;
-
-            let client =
-                source_client.with_context(|| "Failed to find client for fleeing player")?;
+            let (target_client, _, target_stats, _, _) =
+                fighters.get(source_combat_state.target)?;
 
             if source_flee_timer.is_some() {
-                outbox.send_text(client.id, "You are not ready to flee again.");
+                if let Some(client) = source_client {
+                    outbox.send_text(client.id, "You are not ready to flee again.");
+                }
 
                 continue;
             }
 
-            let flee_chance = source_stats.flee_chance(&(target_stats.offense.dominance as f32));
+            let flee_chance = source_stats.flee_chance(&(target_stats.dominance() as f32));
 
-            let target_number = rand::random::<f32>();
+            let target_number = rng.gen::<f32>();
 
             bevy.entity(event.source)
                 .insert(FleeTimer(Timer::from_seconds(
-                    FLEE_COOLDOWN,
+                    balance.flee_cooldown,
                     TimerMode::Once,
                 )));
 
@@ -422,8 +769,75 @@ pub fn on_combat_event_attempt_flee(
                     source: event.source,
                     kind: MovementEventKind::Flee(direction.clone()),
                 })
-            } else {
+            } else if let Some(client) = source_client {
                 outbox.send_text(client.id, "You failed to get away.");
+            } else if let (Some(client), Some(depiction)) = (target_client, depiction) {
+                outbox.send_text(
+                    client.id,
+                    format!("The {} tries to get away, but fails.", depiction.short_name),
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Sets a fighter moving to a new distance from their target. Getting there
+/// takes a moment and costs them their next attack.
+#[sysfail(log)]
+pub fn on_combat_event_attempt_reposition(
+    mut bevy: Commands,
+    mut event_reader: EventReader<CombatEvent>,
+    mut outbox: EventWriter<Outbox>,
+    fighters: Query<(&CombatState, &Stats, Option<&Repositioning>)>,
+    names: Query<(Option<&Client>, Option<&Character>, Option<&Depiction>)>,
+    balance: Res<Balance>,
+) -> Result<(), anyhow::Error> {
+    for event in event_reader.iter() {
+        if let CombatEventKind::AttemptReposition(distance) = &event.kind {
+            let (combat_state, stats, repositioning) = fighters.get(event.source)?;
+            let (client, character, depiction) = names.get(event.source)?;
+
+            if repositioning.is_some() {
+                if let Some(client) = client {
+                    outbox.send_text(client.id, "You are already on the move.");
+                }
+
+                continue;
+            }
+
+            bevy.entity(event.source).insert((
+                Repositioning {
+                    distance: *distance,
+                    timer: Timer::from_seconds(balance.reposition_time, TimerMode::Once),
+                },
+                AttackTimer(Timer::from_seconds(stats.attack_speed(), TimerMode::Once)),
+            ));
+
+            let (target_client, target_character, target_depiction) =
+                names.get(combat_state.target)?;
+
+            let name = capitalize(&fighter_name(character, depiction));
+            let target_name = fighter_name(target_character, target_depiction);
+
+            let (message, target_message) = match distance {
+                Distance::Far => (
+                    format!("You start backing away from {target_name}."),
+                    format!("{name} starts backing away from you."),
+                ),
+                _ => (
+                    format!("You advance on {target_name}."),
+                    format!("{name} advances on you."),
+                ),
+            };
+
+            if let Some(client) = client {
+                outbox.send_text(client.id, message);
+            }
+
+            if let Some(client) = target_client {
+                outbox.send_text(client.id, target_message);
             }
         }
     }
@@ -431,28 +845,151 @@ pub fn on_combat_event_attempt_flee(
     Ok(())
 }
 
+/// Settles moves once they've taken their time. Whoever is being closed on or
+/// backed away from gets to keep pace with their fleet.
+#[allow(clippy::too_many_arguments)]
+pub fn update_repositioning(
+    mut bevy: Commands,
+    mut combat_events: EventWriter<CombatEvent>,
+    mut outbox: EventWriter<Outbox>,
+    mut movers: Query<(
+        Entity,
+        &mut Repositioning,
+        Option<&CombatState>,
+        Option<&Conditions>,
+    )>,
+    fighters: Query<(
+        &Stats,
+        Option<&Client>,
+        Option<&Character>,
+        Option<&Depiction>,
+    )>,
+    conditions: Res<data::resources::Conditions>,
+    time: Res<Time>,
+    mut rng: ResMut<GameRng>,
+) {
+    for (entity, mut repositioning, combat_state, applied) in movers.iter_mut() {
+        if !repositioning.timer.tick(time.delta()).finished() {
+            continue;
+        }
+
+        bevy.entity(entity).remove::<Repositioning>();
+
+        let Some(combat_state) = combat_state.filter(|c| c.distance != repositioning.distance)
+        else {
+            continue;
+        };
+
+        let (Ok((stats, client, character, depiction)), Ok(target)) =
+            (fighters.get(entity), fighters.get(combat_state.target))
+        else {
+            continue;
+        };
+
+        let (target_stats, target_client, target_character, target_depiction) = target;
+
+        let held = applied.and_then(|applied| {
+            applied
+                .flags(&conditions)
+                .into_iter()
+                .find(|flag| matches!(flag, ControlFlag::Stunned | ControlFlag::Rooted))
+        });
+
+        if let Some(flag) = held {
+            if let Some(client) = client {
+                outbox.send_text(client.id, flag.message());
+            }
+
+            continue;
+        }
+
+        let name = fighter_name(character, depiction);
+        let target_name = fighter_name(target_character, target_depiction);
+
+        let chance = stats.reposition_chance(target_stats.fleet());
+
+        let (message, target_message) = if chance > rng.gen::<f32>() {
+            combat_events.send(CombatEvent {
+                source: entity,
+                trigger: CombatEventTrigger::Movement,
+                kind: CombatEventKind::SetDistance(SetDistance {
+                    target: entity,
+                    distance: repositioning.distance,
+                }),
+            });
+
+            match repositioning.distance {
+                Distance::Far => (
+                    format!("You put some distance between yourself and {target_name}."),
+                    format!("{} gets away from you.", capitalize(&name)),
+                ),
+                _ => (
+                    format!("You close in on {target_name}."),
+                    format!("{} closes in on you.", capitalize(&name)),
+                ),
+            }
+        } else {
+            match repositioning.distance {
+                Distance::Far => (
+                    format!("{} keeps pace with you.", capitalize(&target_name)),
+                    format!("You keep pace with {name}."),
+                ),
+                _ => (
+                    format!("{} keeps you at bay.", capitalize(&target_name)),
+                    format!("You keep {name} at bay."),
+                ),
+            }
+        };
+
+        if let Some(client) = client {
+            outbox.send_text(client.id, message);
+        }
+
+        if let Some(client) = target_client {
+            outbox.send_text(client.id, target_message);
+        }
+    }
+}
+
 #[sysfail(log)]
 pub fn on_combat_event_apply_damage(
     mut events: EventReader<CombatEvent>,
-    mut fighters: Query<(&CombatState, &mut Stats, &Modifiers, Option<&Client>)>,
+    mut fighters: Query<(Option<&mut CombatState>, &mut Stats, Option<&Client>)>,
     damage_kinds: Res<DamageKinds>,
     mut response: EventWriter<ApplyDamageResponse>,
+    mut damage_events: EventWriter<DamageEvent>,
     mut prompts: EventWriter<Prompt>,
+    balance: Res<Balance>,
+    mut rng: ResMut<GameRng>,
 ) -> Result<(), anyhow::Error> {
-    let mut targets_to_damage: Vec<(Entity, u32, String, bool, &Option<WithCallback>)> = vec![];
+    let mut targets_to_damage: Vec<(
+        Entity,
+        Entity,
+        u32,
+        String,
+        bool,
+        Option<String>,
+        bool,
+        &Option<Box<WithCallback>>,
+    )> = vec![];
+
     for event in events.iter() {
         if let CombatEventKind::ApplyDamage(args) = &event.kind {
-            let (source_combat_state, source_stats, source_modifiers, _) =
-                fighters.get(event.source)?;
-            let (_, target_stats, _, _) = fighters.get(args.target)?;
+            let (source_combat_state, source_stats, _) = fighters.get(event.source)?;
+            let (_, target_stats, _) = fighters.get(args.target)?;
 
             let mut damage = f32::floor(args.damage) as u32;
             let mut crit = false;
 
-            let critical_strike_chance = source_stats.critical_strike_chance()
-                + source_modifiers.sum_stat(&Stat::CritStrikeChance);
+            let mut critical_strike_chance = source_stats.critical_strike_chance();
+
+            if source_combat_state.is_some_and(|combat_state| {
+                combat_state.target == args.target && combat_state.approach == Approach::Rear
+            }) {
+                critical_strike_chance += balance.rear_crit_strike_bonus;
+            }
 
-            let target_number = rand::random::<f32>();
+            let target_number = rng.gen::<f32>();
 
             if target_number <= critical_strike_chance {
                 let critical_strike_damage = source_stats.critical_strike_damage();
@@ -469,23 +1006,46 @@ pub fn on_combat_event_apply_damage(
 
             let resisted = target_stats.resisted(kind);
 
-            damage *= 1 - resisted;
+            damage = f32::floor(damage as f32 * (1.0 - resisted)) as u32;
 
             targets_to_damage.push((
-                source_combat_state.target,
+                event.source,
+                args.target,
                 damage,
                 args.kind.clone(),
                 crit,
+                match &event.trigger {
+                    CombatEventTrigger::Skill(skill) => Some(skill.name.clone()),
+                    CombatEventTrigger::Condition(condition) => Some(condition.name.clone()),
+                    _ => None,
+                },
+                matches!(event.trigger, CombatEventTrigger::Skill(_)),
                 &args.with_callback,
             ));
         }
     }
 
-    for (target, damage, kind, crit, callback) in targets_to_damage {
-        let (_, mut stats, _, client) = fighters.get_mut(target)?;
+    for (source, target, damage, kind, crit, origin, from_skill, callback) in targets_to_damage {
+        let (combat_state, mut stats, client) = fighters.get_mut(target)?;
 
         stats.status.health = stats.status.health.saturating_sub(damage);
 
+        if let Some(mut combat_state) = combat_state {
+            if source != target {
+                combat_state.threat.add(source, damage as f32);
+            }
+        }
+
+        damage_events.send(DamageEvent {
+            source,
+            target,
+            damage,
+            kind: kind.clone(),
+            crit,
+            origin,
+            from_skill,
+        });
+
         if let Some(with_callback) = callback {
             response.send(ApplyDamageResponse {
                 context: with_callback.context.clone(),
@@ -504,10 +1064,69 @@ pub fn on_combat_event_apply_damage(
     Ok(())
 }
 
+#[sysfail(log)]
+pub fn on_combat_event_apply_heal(
+    mut events: EventReader<CombatEvent>,
+    mut fighters: Query<(
+        Entity,
+        Option<&mut CombatState>,
+        &mut Stats,
+        Option<&Client>,
+    )>,
+    mut prompts: EventWriter<Prompt>,
+    balance: Res<Balance>,
+) -> Result<(), anyhow::Error> {
+    for event in events.iter() {
+        if let CombatEventKind::ApplyHeal(args) = &event.kind {
+            let (_, _, mut stats, client) = fighters.get_mut(args.target)?;
+
+            let amount = f32::floor(args.amount) as u32;
+
+            stats.status.health = u32::min(stats.status.health + amount, stats.max_health());
+
+            if let Some(client) = client {
+                prompts.send(Prompt::new(client.id));
+            }
+
+            // Healing draws the attention of everyone fighting the one being healed.
+            for (_, combat_state, _, _) in fighters.iter_mut() {
+                if let Some(mut combat_state) = combat_state {
+                    if combat_state.threat.contains(&args.target) && event.source != args.target {
+                        combat_state
+                            .threat
+                            .add(event.source, amount as f32 * balance.heal_threat_factor);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[sysfail(log)]
+pub fn on_combat_event_taunt(
+    mut events: EventReader<CombatEvent>,
+    mut fighters: Query<&mut CombatState>,
+    balance: Res<Balance>,
+) -> Result<(), anyhow::Error> {
+    for event in events.iter() {
+        if let CombatEventKind::Taunt(args) = &event.kind {
+            if let Ok(mut combat_state) = fighters.get_mut(args.target) {
+                combat_state
+                    .threat
+                    .taunt(event.source, balance.taunt_threat_bonus);
+            }
+        }
+    }
+
+    Ok(())
+}
+
 #[sysfail(log)]
 pub fn on_combat_event_apply_condition(
     mut combat_events: ParamSet<(EventReader<CombatEvent>, EventWriter<CombatEvent>)>,
-    mut fighters: Query<(Entity, &mut Conditions)>,
+    mut fighters: Query<(&mut Conditions, &mut Modifiers)>,
     conditions: Res<data::resources::Conditions>,
 ) -> Result<(), anyhow::Error> {
     let mut events_to_send: Vec<CombatEvent> = vec![];
@@ -520,20 +1139,22 @@ pub fn on_combat_event_apply_condition(
                 .get(&args.condition)
                 .with_context(|| format!("Failed to find condition: {}", args.condition))?;
 
-            let (_, _) = fighters.get(event.source)?;
-            let (_, mut target_conditions) = fighters.get_mut(args.target)?;
+            let (mut target_conditions, mut target_modifiers) = fighters.get_mut(args.target)?;
 
-            target_conditions.0.insert(
-                args.condition.clone(),
-                args.duration
-                    .map(|duration| Timer::from_seconds(duration, TimerMode::Once)),
-            );
+            let started = target_conditions.apply(condition, event.source, args.duration);
 
-            events_to_send.push(CombatEvent {
-                source: event.source,
-                trigger: CombatEventTrigger::Condition(condition.clone()),
-                kind: CombatEventKind::ExecuteCondition(ExecutionPhase::OnInit),
-            });
+            target_modifiers.sync_condition(condition, target_conditions.stacks(&condition.id));
+
+            if started {
+                events_to_send.push(CombatEvent {
+                    source: event.source,
+                    trigger: CombatEventTrigger::Condition(condition.clone()),
+                    kind: CombatEventKind::ExecuteCondition(ExecuteCondition {
+                        target: args.target,
+                        phase: ExecutionPhase::OnInit,
+                    }),
+                });
+            }
         }
     }
 
@@ -545,39 +1166,122 @@ pub fn on_combat_event_apply_condition(
 }
 
 #[sysfail(log)]
-pub fn update_condition_timer(
+pub fn on_combat_event_dispel(
+    mut combat_events: ParamSet<(EventReader<CombatEvent>, EventWriter<CombatEvent>)>,
+    mut fighters: Query<(&mut Conditions, &mut Modifiers)>,
     conditions: Res<data::resources::Conditions>,
-    mut events: EventWriter<CombatEvent>,
-    mut timers: Query<(Entity, &mut Conditions)>,
-    time: Res<Time>,
 ) -> Result<(), anyhow::Error> {
-    for (entity, mut applied_conditions) in timers.iter_mut() {
-        let mut conditions_to_remove: Vec<String> = vec![];
+    let mut events_to_send: Vec<CombatEvent> = vec![];
+    let mut events = combat_events.p0();
+
+    for event in events.iter() {
+        if let CombatEventKind::Dispel(args) = &event.kind {
+            let (mut applied_conditions, mut modifiers) = fighters.get_mut(args.target)?;
 
-        for (condition, timer) in applied_conditions.0.iter_mut() {
-            if let Some(timer) = timer {
-                timer.tick(time.delta());
+            let dispelled = applied_conditions
+                .0
+                .keys()
+                .filter(|id| {
+                    args.id.as_ref() == Some(*id)
+                        || args.tag.as_ref().is_some_and(|tag| {
+                            conditions
+                                .0
+                                .get(*id)
+                                .is_some_and(|condition| condition.tags.contains(tag))
+                        })
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+
+            for id in dispelled {
+                let condition = conditions
+                    .0
+                    .get(&id)
+                    .with_context(|| format!("Failed to find condition: {}", id))?;
 
-                if timer.finished() {
-                    conditions_to_remove.push(condition.clone());
+                modifiers.sync_condition(condition, 0);
+
+                for instance in applied_conditions.0.remove(&id).unwrap_or_default() {
+                    events_to_send.push(CombatEvent {
+                        source: instance.source,
+                        trigger: CombatEventTrigger::Condition(condition.clone()),
+                        kind: CombatEventKind::ExecuteCondition(ExecuteCondition {
+                            target: args.target,
+                            phase: ExecutionPhase::OnEnd,
+                        }),
+                    });
                 }
             }
         }
+    }
 
-        for condition in conditions_to_remove {
-            applied_conditions.0.remove(&condition);
+    for event in events_to_send {
+        combat_events.p1().send(event);
+    }
+
+    Ok(())
+}
 
+/// Counts down applied conditions, running their `on_tick` scripts each
+/// interval and their `on_end` scripts once they run out.
+#[sysfail(log)]
+pub fn update_condition_timer(
+    conditions: Res<data::resources::Conditions>,
+    mut events: EventWriter<CombatEvent>,
+    mut timers: Query<(Entity, &mut Conditions, &mut Modifiers)>,
+    time: Res<Time>,
+) -> Result<(), anyhow::Error> {
+    for (entity, mut applied_conditions, mut modifiers) in timers.iter_mut() {
+        for (id, instances) in applied_conditions.0.iter_mut() {
             let condition = conditions
                 .0
-                .get(&condition)
-                .with_context(|| format!("Failed to find condition: {}", condition))?;
+                .get(id)
+                .with_context(|| format!("Failed to find condition: {}", id))?;
+
+            let mut send = |source: Entity, phase: ExecutionPhase| {
+                events.send(CombatEvent {
+                    source,
+                    trigger: CombatEventTrigger::Condition(condition.clone()),
+                    kind: CombatEventKind::ExecuteCondition(ExecuteCondition {
+                        target: entity,
+                        phase,
+                    }),
+                });
+            };
 
-            events.send(CombatEvent {
-                source: entity,
-                trigger: CombatEventTrigger::Condition(condition.clone()),
-                kind: CombatEventKind::ExecuteCondition(ExecutionPhase::OnEnd),
+            let before = instances.len();
+
+            instances.retain_mut(|instance| {
+                if let Some(interval) = condition.tick {
+                    let tick = instance
+                        .tick
+                        .get_or_insert_with(|| Timer::from_seconds(interval, TimerMode::Repeating));
+
+                    for _ in 0..tick.tick(time.delta()).times_finished_this_tick() {
+                        send(instance.source, ExecutionPhase::OnTick);
+                    }
+                }
+
+                let ended = instance
+                    .duration
+                    .as_mut()
+                    .is_some_and(|duration| duration.tick(time.delta()).finished());
+
+                if ended {
+                    send(instance.source, ExecutionPhase::OnEnd);
+                }
+
+                !ended
             });
+
+            if instances.len() != before {
+                modifiers.sync_condition(condition, instances.iter().map(|i| i.stacks).sum());
+            }
         }
+
+        applied_conditions
+            .0
+            .retain(|_, instances| !instances.is_empty());
     }
 
     Ok(())
@@ -633,15 +1337,54 @@ pub fn on_combat_event_add_stat_modifier(
         if let CombatEventKind::AddStatModifier(args) = &event.kind {
             let mut modifiers = fighters.get_mut(args.target)?;
 
-            modifiers
-                .0
-                .insert(args.id.clone(), (args.stat.clone(), args.amount));
+            modifiers.0.insert(
+                args.id.clone(),
+                Modifier {
+                    stat: args.stat.clone(),
+                    amount: args.amount,
+                    kind: args.kind,
+                },
+            );
         }
     }
 
     Ok(())
 }
 
+/// Caches modifier totals on `Stats` whenever they change, so derived stats
+/// don't have to sum them on every use. Health and vigor are brought down to
+/// any lower maximums, such as when a buff wears off.
+pub fn apply_modifiers(
+    mut fighters: Query<(&Modifiers, &mut Stats), Or<(Changed<Modifiers>, Added<Stats>)>>,
+) {
+    for (modifiers, mut stats) in fighters.iter_mut() {
+        stats.bonuses = modifiers.bonuses();
+
+        let (max_health, max_vigor) = (stats.max_health(), stats.max_vigor());
+
+        stats.status.health = stats.status.health.min(max_health);
+        stats.status.vigor = stats.status.vigor.min(max_vigor);
+    }
+}
+
+/// Shares the current balance with every fighter, handing it to everyone
+/// again whenever it's reloaded.
+pub fn apply_balance(
+    balance: Res<Balance>,
+    mut shared: Local<Arc<Balance>>,
+    mut fighters: Query<&mut Stats>,
+) {
+    if balance.is_changed() {
+        *shared = Arc::new(balance.clone());
+    }
+
+    for mut stats in fighters.iter_mut() {
+        if balance.is_changed() || stats.is_added() {
+            stats.balance = shared.clone();
+        }
+    }
+}
+
 #[sysfail(log)]
 pub fn on_combat_event_remove_stat_modifier(
     mut event_reader: EventReader<CombatEvent>,
@@ -745,6 +1488,138 @@ pub fn on_combat_event_combat_log(
     Ok(())
 }
 
+/// Keeps the clock running on each player's fight, starting a fresh one for
+/// anyone who has just entered combat.
+pub fn update_combat_meters(
+    time: Res<Time>,
+    mut meters: Query<&mut CombatMeter, With<CombatState>>,
+) {
+    for mut meter in meters.iter_mut() {
+        meter
+            .current
+            .get_or_insert_with(FightStats::default)
+            .duration += time.delta_seconds();
+    }
+}
+
+/// Tallies how attacks turned out and the damage they did for anyone keeping
+/// a meter on either end of them.
+pub fn record_combat_stats(
+    mut combat_events: EventReader<CombatEvent>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut outbox: EventWriter<Outbox>,
+    mut meters: Query<(&mut CombatMeter, Option<&Client>, Option<&Character>)>,
+    combat_states: Query<&CombatState>,
+) {
+    let mut changed: Vec<Entity> = vec![];
+
+    for event in combat_events.iter() {
+        let (CombatEventKind::ExecuteScripts(phase), CombatEventTrigger::Skill(_)) =
+            (&event.kind, &event.trigger)
+        else {
+            continue;
+        };
+
+        if !matches!(
+            phase,
+            ExecutionPhase::OnHit
+                | ExecutionPhase::OnMiss
+                | ExecutionPhase::OnDodge
+                | ExecutionPhase::OnBlock
+        ) {
+            continue;
+        }
+
+        if let Ok((mut meter, _, _)) = meters.get_mut(event.source) {
+            let fight = meter.current.get_or_insert_with(FightStats::default);
+
+            fight.dealt.record(phase);
+            changed.push(event.source);
+        }
+
+        let Ok(combat_state) = combat_states.get(event.source) else {
+            continue;
+        };
+
+        if let Ok((mut meter, _, _)) = meters.get_mut(combat_state.target) {
+            let fight = meter.current.get_or_insert_with(FightStats::default);
+
+            fight.taken.record(phase);
+            changed.push(combat_state.target);
+        }
+    }
+
+    for event in damage_events.iter() {
+        let origin = event.origin.as_deref();
+
+        if event.source != event.target {
+            if let Ok((mut meter, _, _)) = meters.get_mut(event.source) {
+                let fight = meter.current.get_or_insert_with(FightStats::default);
+
+                fight
+                    .dealt
+                    .add(event.damage, origin, &event.kind, event.crit);
+                changed.push(event.source);
+            }
+        }
+
+        if let Ok((mut meter, _, _)) = meters.get_mut(event.target) {
+            let fight = meter.current.get_or_insert_with(FightStats::default);
+
+            fight
+                .taken
+                .add(event.damage, origin, &event.kind, event.crit);
+            changed.push(event.target);
+        }
+    }
+
+    changed.sort();
+    changed.dedup();
+
+    for entity in changed {
+        if let Ok((meter, Some(client), Some(character))) = meters.get(entity) {
+            if let Some(fight) = &meter.current {
+                send_meter(&mut outbox, client, character, fight);
+            }
+        }
+    }
+}
+
+/// Files a player's fight away as their last once they're out of combat.
+pub fn end_combat_meters(
+    mut ended: RemovedComponents<CombatState>,
+    mut meters: Query<&mut CombatMeter>,
+) {
+    for entity in ended.iter() {
+        if let Ok(mut meter) = meters.get_mut(entity) {
+            if let Some(fight) = meter.current.take() {
+                meter.last = Some(fight);
+            }
+        }
+    }
+}
+
+/// Sends the fight to the client over GMCP, if they've asked for meters.
+fn send_meter(
+    outbox: &mut EventWriter<Outbox>,
+    client: &Client,
+    character: &Character,
+    fight: &FightStats,
+) {
+    if !character.config.meters {
+        return;
+    }
+
+    outbox.send_gmcp(
+        client.id,
+        Payload {
+            package: "Char.Combat".into(),
+            subpackage: Some("Stats".into()),
+            data: serde_json::to_string(fight).ok(),
+        },
+    );
+}
+
 pub fn update_attack_timer(
     mut bevy: Commands,
     mut proxy: EventWriter<ProxyCommand>,
@@ -911,33 +1786,107 @@ pub fn update_cooldowns(
 pub fn on_hostile_death(
     mut bevy: Commands,
     mut outbox: EventWriter<Outbox>,
-    hostiles: Query<(Entity, &Depiction, &Stats, &Parent), With<Hostile>>,
-    mut players: Query<(Entity, &Client, &CombatState), With<Online>>,
+    hostiles: Query<(
+        Entity,
+        &Hostile,
+        &Depiction,
+        &Stats,
+        &Parent,
+        Option<&CombatState>,
+    )>,
+    mut players: Query<(&Client, &Character, &mut Stats), (With<Online>, Without<Hostile>)>,
+    parties: Query<&Party>,
     tiles: Query<&Children, With<Tile>>,
+    balance: Res<Balance>,
 ) {
-    for (entity, depiction, stats, parent) in hostiles.iter() {
+    for (entity, hostile, depiction, stats, parent, combat_state) in hostiles.iter() {
         let siblings = tiles.get(parent.get()).ok();
 
         if stats.status.health == 0 {
-            let players_in_combat = players
-                .iter_mut()
-                .filter(|(_, _, combat_state)| combat_state.target == entity);
-
-            for (player, _, _) in players_in_combat {
-                bevy.entity(player).remove::<CombatState>();
-            }
-
             let players_on_tile = siblings
                 .map(|siblings| {
                     siblings
                         .iter()
-                        .filter_map(|entity| players.get(*entity).ok())
+                        .filter(|entity| players.contains(**entity))
+                        .copied()
                         .collect::<Vec<_>>()
                 })
                 .unwrap_or_default();
 
-            for (_, client, _) in players_on_tile {
-                outbox.send_text(client.id, format!("{} has died.", depiction.name));
+            for player in players_on_tile.iter() {
+                if let Ok((client, _, _)) = players.get(*player) {
+                    outbox.send_text(client.id, format!("{} has died.", depiction.name));
+                }
+            }
+
+            // Everyone who fought gets a share, along with any of their party
+            // members who were there to see it.
+            let fought = players_on_tile
+                .iter()
+                .filter(|player| combat_state.is_some_and(|c| c.threat.contains(player)))
+                .filter_map(|player| players.get(*player).ok())
+                .map(|(_, character, _)| character.id)
+                .collect::<Vec<_>>();
+
+            let rewarded = players_on_tile
+                .iter()
+                .filter(|player| {
+                    players.get(**player).is_ok_and(|(_, character, _)| {
+                        fought.contains(&character.id)
+                            || parties.iter().any(|party| {
+                                party.contains(character.id)
+                                    && fought.iter().any(|id| party.contains(*id))
+                            })
+                    })
+                })
+                .copied()
+                .collect::<Vec<_>>();
+
+            // Loot goes to whoever did the most to bring it down.
+            let killer = combat_state.and_then(|combat_state| {
+                players_on_tile
+                    .iter()
+                    .filter_map(|player| {
+                        let threat = combat_state.threat.0.get(player)?;
+                        let (_, character, _) = players.get(*player).ok()?;
+
+                        Some((character.id, *threat))
+                    })
+                    .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .map(|(id, _)| id)
+            });
+
+            let corpse = bevy
+                .spawn(CorpseBundle::hostile(
+                    &depiction.name,
+                    &depiction.short_name,
+                    killer,
+                    balance.corpse_decay_timer,
+                ))
+                .set_parent(parent.get())
+                .id();
+
+            if let Some(loot) = &hostile.loot {
+                bevy.entity(corpse).insert(UnrolledLoot(loot.clone()));
+            }
+
+            if !rewarded.is_empty() {
+                let share = stats.experience_reward().div_ceil(rewarded.len() as u32);
+
+                for player in rewarded {
+                    let Ok((client, _, mut player_stats)) = players.get_mut(player) else {
+                        continue;
+                    };
+
+                    outbox.send_text(client.id, format!("You gain {share} experience."));
+
+                    if player_stats.gain_experience(share) {
+                        outbox.send_text(
+                            client.id,
+                            format!("You have reached level {}!", player_stats.level),
+                        );
+                    }
+                }
             }
 
             bevy.entity(entity).despawn();
@@ -945,15 +1894,68 @@ pub fn on_hostile_death(
     }
 }
 
+/// Teaches players whatever their mastery's tree has opened up to them, be it
+/// from a new level or a skill that was a prerequisite for others. Skills
+/// picked up on logging in are learned quietly.
+#[sysfail(log)]
+pub fn learn_skills(
+    mut outbox: EventWriter<Outbox>,
+    mut players: Query<(&Client, &Character, &Stats, &mut LearnedSkills), Changed<Stats>>,
+    masteries: Res<Masteries>,
+    skills: Res<Skills>,
+) -> Result<(), anyhow::Error> {
+    for (client, character, stats, mut learned) in players.iter_mut() {
+        let mastery = masteries
+            .0
+            .get(&character.mastery)
+            .with_context(|| format!("Mastery not found: {}", character.mastery))?;
+
+        loop {
+            let next: Vec<String> = mastery
+                .learnable(stats.level, &learned.0)
+                .map(|node| node.skill.clone())
+                .collect();
+
+            if next.is_empty() {
+                break;
+            }
+
+            for id in next {
+                if !learned.is_added() {
+                    let name = skills.0.get(&id).map_or(id.as_str(), |s| s.name.as_str());
+
+                    outbox.send_text(client.id, format!("You have learned {name}."));
+                }
+
+                learned.0.insert(id);
+            }
+        }
+    }
+
+    Ok(())
+}
+
 pub fn on_player_death(
     mut bevy: Commands,
-    mut hostiles: Query<(Entity, &CombatState), With<Hostile>>,
     mut outbox: EventWriter<Outbox>,
     mut proxy: EventWriter<ProxyCommand>,
-    mut players: Query<(Entity, &Client, &mut Stats), (With<Online>, With<CombatState>)>,
+    mut combat_events: EventWriter<CombatEvent>,
+    mut players: Query<
+        (
+            Entity,
+            &Client,
+            &Character,
+            &Parent,
+            Option<&Children>,
+            &mut Stats,
+        ),
+        (With<Online>, Without<Duel>),
+    >,
+    inventories: Query<&Children, With<Inventory>>,
     spawn_tiles: Query<Entity, With<DeathSpawn>>,
+    balance: Res<Balance>,
 ) {
-    for (player, client, mut stats) in players.iter_mut() {
+    for (player, client, character, tile, children, mut stats) in players.iter_mut() {
         if stats.status.health == 0 {
             outbox.send_text(client.id, "You have died.");
 
@@ -962,12 +1964,48 @@ pub fn on_player_death(
 
             stats.status.health = stats.max_health();
 
-            let hostiles_in_combat = hostiles
-                .iter_mut()
-                .filter(|(_, combat_state)| combat_state.target == player);
+            let penalty =
+                (stats.experience_to_level() as f32 * balance.death_experience_penalty) as u32;
+            let lost = penalty.min(stats.status.experience);
+
+            if lost > 0 {
+                stats.status.experience -= lost;
+
+                outbox.send_text(client.id, format!("You lose {lost} experience."));
+            }
+
+            combat_events.send(CombatEvent {
+                source: player,
+                trigger: CombatEventTrigger::Death,
+                kind: CombatEventKind::ApplyCondition(ApplyCondition {
+                    target: player,
+                    condition: balance.death_condition.clone(),
+                    duration: Some(balance.death_condition_duration),
+                }),
+            });
 
-            for (entity, _) in hostiles_in_combat {
-                bevy.entity(entity).remove::<CombatState>();
+            // Everything they were carrying stays behind with their body.
+            let carried = children
+                .iter()
+                .flat_map(|children| children.iter())
+                .filter_map(|child| inventories.get(*child).ok())
+                .flat_map(|items| items.iter().copied())
+                .collect::<Vec<_>>();
+
+            if !carried.is_empty() {
+                bevy.spawn((
+                    CorpseBundle::player(
+                        &character.name,
+                        character.id,
+                        balance.player_corpse_decay_timer,
+                    ),
+                    PlayerCorpse {
+                        owner: character.id,
+                        name: character.name.clone(),
+                    },
+                ))
+                .set_parent(tile.get())
+                .push_children(&carried);
             }
 
             if let Some(tile) = spawn_tiles.iter().next() {
@@ -982,6 +2020,91 @@ pub fn on_player_death(
     }
 }
 
+/// Ends a duel once either side is beaten down, leaving the loser on their feet,
+/// or quietly once the two have stopped fighting each other.
+pub fn end_duels(
+    mut bevy: Commands,
+    mut outbox: EventWriter<Outbox>,
+    mut duelists: Query<(
+        Entity,
+        &Duel,
+        &Character,
+        &Parent,
+        &mut Stats,
+        Option<&mut CombatState>,
+    )>,
+    clients: Query<&Client, With<Online>>,
+    tiles: Query<&Children, With<Tile>>,
+    balance: Res<Balance>,
+) {
+    let broken = duelists
+        .iter()
+        .filter(|(entity, duel, _, _, _, combat_state)| {
+            combat_state.is_none()
+                || duelists
+                    .get(duel.0)
+                    .map_or(true, |(_, other, _, _, _, _)| other.0 != *entity)
+        })
+        .map(|(entity, _, _, _, _, _)| entity)
+        .collect::<Vec<_>>();
+
+    for entity in broken.iter() {
+        bevy.entity(*entity).remove::<Duel>();
+    }
+
+    let beaten = duelists
+        .iter()
+        .filter(|(entity, _, _, _, stats, _)| {
+            !broken.contains(entity)
+                && stats.status.health as f32
+                    <= stats.max_health() as f32 * balance.duel_yield_threshold
+        })
+        .map(|(entity, duel, _, _, _, _)| (entity, duel.0))
+        .collect::<Vec<_>>();
+
+    let mut ended: Vec<Entity> = vec![];
+
+    for (loser, winner) in beaten {
+        if ended.contains(&loser) {
+            continue;
+        }
+
+        let Ok([mut loser_item, mut winner_item]) = duelists.get_many_mut([loser, winner]) else {
+            continue;
+        };
+
+        loser_item.4.status.health = loser_item.4.status.health.max(1);
+
+        if let Some(combat_state) = loser_item.5.as_mut() {
+            combat_state.threat.0.remove(&winner);
+        }
+
+        if let Some(combat_state) = winner_item.5.as_mut() {
+            combat_state.threat.0.remove(&loser);
+        }
+
+        for entity in [loser, winner] {
+            bevy.entity(entity).remove::<(Duel, QueuedAttack)>();
+        }
+
+        ended.extend([loser, winner]);
+
+        let message = format!(
+            "{} has bested {} in a duel.",
+            winner_item.2.name, loser_item.2.name
+        );
+
+        for client in tiles
+            .get(loser_item.3.get())
+            .iter()
+            .flat_map(|siblings| siblings.iter())
+            .filter_map(|sibling| clients.get(*sibling).ok())
+        {
+            outbox.send_text(client.id, message.clone());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rstest::*;
@@ -993,7 +2116,19 @@ mod tests {
         app_builder::AppBuilder, npc_builder::NpcBuilder, player_builder::PlayerBuilder,
     };
 
-    use crate::combat::components::{Approach, Distance};
+    use crate::combat::{
+        components::{AppliedCondition, Distance, Resistance},
+        events::{ApplyDamage, Dispel},
+    };
+    use crate::data::resources::{SkillNode, Stat};
+    use crate::items::components::{Corpse, LootRights, Slot};
+    use crate::npc::{
+        components::{Behavior, HostileAiTimer},
+        systems::handle_hostile_ai,
+    };
+    use crate::test::item_builder::ItemBuilder;
+    use crate::values::HOSTILE_AI_TICK;
+    use bevy::ecs::event::ManualEventReader;
 
     use super::*;
 
@@ -1008,87 +2143,1335 @@ mod tests {
 
         let npc = NpcBuilder::new()
             .name("Goat")
+            .short_name("goat")
             .combat(true)
             .tile(tile)
             .build(&mut app);
 
-        app.world.entity_mut(player).insert(CombatState {
-            target: npc,
-            distance: Distance::Near,
-            approach: Approach::Front,
-        });
+        app.world
+            .entity_mut(player)
+            .insert(CombatState::new(npc, Distance::Near));
 
         (app, player, client_id, npc)
     }
 
     #[rstest]
-    fn update_attack_timer_removes_component(setup: (App, Entity, ClientId, Entity)) {
-        let (mut app, player, _, _) = setup;
-        app.add_systems(Update, update_attack_timer);
-
-        let mut timer = Timer::from_seconds(1.0, TimerMode::Once);
-        timer.set_elapsed(Duration::from_secs(1));
-        app.world.entity_mut(player).insert(AttackTimer(timer));
+    #[case(Approach::Front, ExecutionPhase::OnBlock)]
+    #[case(Approach::Rear, ExecutionPhase::OnHit)]
+    fn rear_attacks_bypass_block(
+        setup: (App, Entity, ClientId, Entity),
+        #[case] approach: Approach,
+        #[case] expected: ExecutionPhase,
+    ) {
+        let (mut app, player, _, npc) = setup;
+        app.add_systems(Update, on_combat_event_attempt_block);
+
+        app.world.get_mut::<CombatState>(player).unwrap().approach = approach;
+
+        app.world.entity_mut(npc).insert((
+            CombatState::new(player, Distance::Near),
+            ManualBlock(Timer::from_seconds(10.0, TimerMode::Once)),
+        ));
+
+        let skill = app
+            .world
+            .resource::<Skills>()
+            .0
+            .get("punch")
+            .unwrap()
+            .clone();
+
+        app.world.send_event(CombatEvent {
+            source: player,
+            trigger: CombatEventTrigger::Skill(skill),
+            kind: CombatEventKind::AttemptBlock,
+        });
 
         app.update();
 
-        assert!(app.world.get::<AttackTimer>(player).is_none());
-    }
-
-    #[rstest]
-    fn on_npc_death_destroys_entity(setup: (App, Entity, ClientId, Entity)) {
-        let (mut app, _, _, npc) = setup;
-        app.add_systems(Update, on_hostile_death);
+        let events = app.world.resource::<Events<CombatEvent>>();
+        let mut reader = events.get_reader();
 
-        app.world.entity_mut(npc).insert(Stats::default());
-        app.update();
+        let phase = reader.iter(events).find_map(|event| match &event.kind {
+            CombatEventKind::ExecuteScripts(phase) => Some(phase.clone()),
+            _ => None,
+        });
 
-        assert!(app.world.get_entity(npc).is_none());
+        assert_eq!(phase, Some(expected));
     }
 
-    #[rstest]
-    fn on_npc_death_alerts_neighbors(setup: (App, Entity, ClientId, Entity)) {
-        let (mut app, _, client_id, npc) = setup;
-        app.add_systems(Update, on_hostile_death);
+    #[test]
+    fn seeded_rolls_repeat() {
+        let outcomes = |seed: u64| {
+            let mut app = AppBuilder::new().seed(seed).build();
+            app.add_systems(Update, on_combat_event_attempt_dodge);
 
-        app.world.entity_mut(npc).insert(Stats::default());
+            let zone = ZoneBuilder::new().build(&mut app);
+            let tile = TileBuilder::new().build(&mut app, zone);
 
-        app.update();
+            let (player, _, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+            let npc = NpcBuilder::new().combat(true).tile(tile).build(&mut app);
 
-        let content = get_message_content(&mut app, client_id).unwrap();
+            app.world
+                .entity_mut(player)
+                .insert(CombatState::new(npc, Distance::Near));
 
-        assert_eq!(content, "Goat has died.");
-    }
+            app.world.entity_mut(npc).insert((
+                CombatState::new(player, Distance::Near),
+                ManualDodge(Timer::from_seconds(10.0, TimerMode::Once)),
+            ));
 
-    #[rstest]
-    fn on_player_death_resets_state(setup: (App, Entity, ClientId, Entity)) {
-        let (mut app, player, _, _) = setup;
-        app.add_systems(Update, on_player_death);
+            let skill = app
+                .world
+                .resource::<Skills>()
+                .0
+                .get("punch")
+                .unwrap()
+                .clone();
+
+            (0..20)
+                .map(|_| {
+                    app.world.send_event(CombatEvent {
+                        source: player,
+                        trigger: CombatEventTrigger::Skill(skill.clone()),
+                        kind: CombatEventKind::AttemptDodge,
+                    });
 
-        app.world.entity_mut(player).insert(Stats::default());
-        app.update();
+                    app.update();
 
-        assert!(app.world.get::<CombatState>(player).is_none());
+                    let events = app.world.resource::<Events<CombatEvent>>();
 
-        assert_eq!(
-            app.world.get::<Stats>(player).unwrap().status.health,
-            app.world.get::<Stats>(player).unwrap().max_health()
-        );
+                    events.iter_current_update_events().any(|event| {
+                        matches!(
+                            event.kind,
+                            CombatEventKind::ExecuteScripts(ExecutionPhase::OnDodge)
+                        )
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(outcomes(42), outcomes(42));
     }
 
     #[rstest]
-    fn on_player_death_teleports_player(setup: (App, Entity, ClientId, Entity)) {
-        let (mut app, player, _, _) = setup;
-        app.add_systems(Update, on_player_death);
+    fn meters_tally_fights(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, npc) = setup;
+        app.add_systems(Update, (record_combat_stats, end_combat_meters));
 
-        let zone = ZoneBuilder::new().build(&mut app);
-        let tile = TileBuilder::new().build(&mut app, zone);
+        let skill = app
+            .world
+            .resource::<Skills>()
+            .0
+            .values()
+            .next()
+            .unwrap()
+            .clone();
+
+        app.world.send_event(CombatEvent {
+            source: player,
+            trigger: CombatEventTrigger::Skill(skill),
+            kind: CombatEventKind::ExecuteScripts(ExecutionPhase::OnDodge),
+        });
 
-        app.world.entity_mut(tile).insert(DeathSpawn);
+        app.world.send_event(DamageEvent {
+            source: player,
+            target: npc,
+            damage: 7,
+            kind: "physical".into(),
+            crit: true,
+            origin: Some("Punch".into()),
+            from_skill: true,
+        });
+
+        app.world.send_event(DamageEvent {
+            source: npc,
+            target: player,
+            damage: 3,
+            kind: "physical".into(),
+            crit: false,
+            origin: None,
+            from_skill: true,
+        });
 
-        app.world.entity_mut(player).insert(Stats::default());
         app.update();
 
-        assert_eq!(app.world.get::<Parent>(player).unwrap().get(), tile);
+        let fight = app
+            .world
+            .get::<CombatMeter>(player)
+            .unwrap()
+            .current
+            .clone()
+            .unwrap();
+
+        assert_eq!(fight.dealt.dodges, 1);
+        assert_eq!(fight.dealt.total, 7);
+        assert_eq!(fight.dealt.crits, 1);
+        assert_eq!(fight.dealt.by_skill.get("Punch"), Some(&7));
+        assert_eq!(fight.taken.total, 3);
+        assert_eq!(fight.taken.by_skill.get("other"), Some(&3));
+
+        app.world.entity_mut(player).remove::<CombatState>();
+        app.update();
+
+        let meter = app.world.get::<CombatMeter>(player).unwrap();
+
+        assert!(meter.current.is_none());
+        assert_eq!(meter.last, Some(fight));
+    }
+
+    fn reactions(app: &mut App, player: Entity, reaction: Reaction) {
+        let mut skill = app
+            .world
+            .resource::<Skills>()
+            .0
+            .get("punch")
+            .unwrap()
+            .clone();
+
+        skill.id = "riposte".into();
+        skill.commands = vec![];
+        skill.cost = 5;
+        skill.cooldown = 8;
+        skill.reaction = Some(reaction);
+
+        app.world
+            .resource_mut::<Skills>()
+            .0
+            .insert(skill.id.clone(), skill);
+
+        let mastery = app.world.get::<Character>(player).unwrap().mastery.clone();
+
+        app.world
+            .resource_mut::<Masteries>()
+            .0
+            .get_mut(&mastery)
+            .unwrap()
+            .skills
+            .push(SkillNode {
+                skill: "riposte".into(),
+                level: 0,
+                requires: vec![],
+            });
+
+        app.world
+            .get_mut::<LearnedSkills>(player)
+            .unwrap()
+            .0
+            .insert("riposte".into());
+
+        let mut stats = app.world.get_mut::<Stats>(player).unwrap();
+        stats.attributes.vitality = 10;
+        stats.status.health = stats.max_health();
+        stats.status.vigor = 10;
+    }
+
+    fn sent_reaction(app: &App) -> Option<(Entity, Entity, ExecutionPhase)> {
+        let events = app.world.resource::<Events<CombatEvent>>();
+
+        events
+            .iter_current_update_events()
+            .find_map(|event| match &event.kind {
+                CombatEventKind::ExecuteReaction(args) => {
+                    Some((event.source, args.target, args.phase.clone()))
+                }
+                _ => None,
+            })
+    }
+
+    #[rstest]
+    fn block_sets_off_reaction(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, npc) = setup;
+        app.add_systems(Update, trigger_reactions);
+
+        reactions(&mut app, player, Reaction::Block);
+
+        app.world
+            .entity_mut(npc)
+            .insert(CombatState::new(player, Distance::Near));
+
+        let punch = app
+            .world
+            .resource::<Skills>()
+            .0
+            .get("punch")
+            .unwrap()
+            .clone();
+
+        app.world.send_event(CombatEvent {
+            source: npc,
+            trigger: CombatEventTrigger::Skill(punch),
+            kind: CombatEventKind::ExecuteScripts(ExecutionPhase::OnBlock),
+        });
+
+        app.update();
+
+        assert_eq!(
+            sent_reaction(&app),
+            Some((player, npc, ExecutionPhase::OnBlockReaction))
+        );
+
+        assert_eq!(app.world.get::<Stats>(player).unwrap().status.vigor, 5);
+        assert!(app
+            .world
+            .get::<Cooldowns>(player)
+            .unwrap()
+            .0
+            .contains_key("riposte"));
+    }
+
+    #[rstest]
+    #[case(100, None)]
+    #[case(1, Some(ExecutionPhase::OnHitReaction))]
+    fn low_health_sets_off_reaction(
+        setup: (App, Entity, ClientId, Entity),
+        #[case] health: u32,
+        #[case] expected: Option<ExecutionPhase>,
+    ) {
+        let (mut app, player, _, npc) = setup;
+        app.add_systems(Update, trigger_reactions);
+
+        reactions(&mut app, player, Reaction::HitBelow(0.3));
+
+        app.world.get_mut::<Stats>(player).unwrap().status.health = health;
+
+        app.world.send_event(DamageEvent {
+            source: npc,
+            target: player,
+            damage: 1,
+            kind: "physical".into(),
+            crit: false,
+            origin: None,
+            from_skill: true,
+        });
+
+        app.update();
+
+        assert_eq!(sent_reaction(&app).map(|(_, _, phase)| phase), expected);
+    }
+
+    #[rstest]
+    #[case(true, Some(ExecutionPhase::OnHitReaction))]
+    #[case(false, None)]
+    fn only_skills_set_off_hit_reactions(
+        setup: (App, Entity, ClientId, Entity),
+        #[case] from_skill: bool,
+        #[case] expected: Option<ExecutionPhase>,
+    ) {
+        let (mut app, player, _, npc) = setup;
+        app.add_systems(Update, trigger_reactions);
+
+        reactions(&mut app, player, Reaction::Hit);
+
+        app.world.send_event(DamageEvent {
+            source: npc,
+            target: player,
+            damage: 1,
+            kind: "physical".into(),
+            crit: false,
+            origin: None,
+            from_skill,
+        });
+
+        app.update();
+
+        assert_eq!(sent_reaction(&app).map(|(_, _, phase)| phase), expected);
+    }
+
+    #[rstest]
+    fn ended_fight_keeps_other_reactions(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, npc) = setup;
+        app.add_systems(Update, trigger_reactions);
+
+        reactions(&mut app, player, Reaction::Hit);
+
+        let punch = app
+            .world
+            .resource::<Skills>()
+            .0
+            .get("punch")
+            .unwrap()
+            .clone();
+
+        // The goat has no combat state left to say who it was attacking.
+        app.world.send_event(CombatEvent {
+            source: npc,
+            trigger: CombatEventTrigger::Skill(punch),
+            kind: CombatEventKind::ExecuteScripts(ExecutionPhase::OnDodge),
+        });
+
+        app.world.send_event(DamageEvent {
+            source: npc,
+            target: player,
+            damage: 1,
+            kind: "physical".into(),
+            crit: false,
+            origin: None,
+            from_skill: true,
+        });
+
+        app.update();
+
+        assert_eq!(
+            sent_reaction(&app),
+            Some((player, npc, ExecutionPhase::OnHitReaction))
+        );
+    }
+
+    #[rstest]
+    fn apply_damage_reports_damage(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, npc) = setup;
+        app.add_event::<ApplyDamageResponse>();
+        app.add_systems(Update, on_combat_event_apply_damage);
+
+        let mut damage_kinds = DamageKinds::default();
+
+        damage_kinds.0.insert(
+            "physical".into(),
+            data::resources::DamageKind {
+                id: "physical".into(),
+                name: "Physical".into(),
+                description: "Physical attacks.".into(),
+                resistances: vec![],
+            },
+        );
+
+        app.insert_resource(damage_kinds);
+
+        app.world.send_event(CombatEvent {
+            source: player,
+            trigger: CombatEventTrigger::Movement,
+            kind: CombatEventKind::ApplyDamage(ApplyDamage {
+                target: npc,
+                damage: 5.0,
+                kind: "physical".into(),
+                with_callback: None,
+            }),
+        });
+
+        app.update();
+
+        let events = app.world.resource::<Events<DamageEvent>>();
+        let mut reader = events.get_reader();
+        let event = reader.iter(events).next().unwrap();
+
+        assert_eq!(event.source, player);
+        assert_eq!(event.target, npc);
+        assert!(event.damage >= 5);
+    }
+
+    #[rstest]
+    fn armor_reduces_damage(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, npc) = setup;
+        app.add_event::<ApplyDamageResponse>();
+        app.add_systems(Update, on_combat_event_apply_damage);
+
+        let mut damage_kinds = DamageKinds::default();
+
+        damage_kinds.0.insert(
+            "physical".into(),
+            data::resources::DamageKind {
+                id: "physical".into(),
+                name: "Physical".into(),
+                description: "Physical attacks.".into(),
+                resistances: vec!["armor".into()],
+            },
+        );
+
+        app.insert_resource(damage_kinds);
+
+        app.world.get_mut::<Stats>(npc).unwrap().balance = Arc::new(Balance {
+            crit_strike_chance_cap: 0.0,
+            ..Default::default()
+        });
+
+        let mut player_stats = app.world.get_mut::<Stats>(player).unwrap();
+
+        player_stats.balance = Arc::new(Balance::default());
+        player_stats.gear_resistance = Resistance(HashMap::from([("armor".into(), 5)]));
+
+        app.world.send_event(CombatEvent {
+            source: npc,
+            trigger: CombatEventTrigger::Movement,
+            kind: CombatEventKind::ApplyDamage(ApplyDamage {
+                target: player,
+                damage: 10.0,
+                kind: "physical".into(),
+                with_callback: None,
+            }),
+        });
+
+        app.update();
+
+        let events = app.world.resource::<Events<DamageEvent>>();
+        let mut reader = events.get_reader();
+        let event = reader.iter(events).next().unwrap();
+
+        assert_eq!(event.damage, 6);
+    }
+
+    #[rstest]
+    fn update_attack_timer_removes_component(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, _) = setup;
+        app.add_systems(Update, update_attack_timer);
+
+        let mut timer = Timer::from_seconds(1.0, TimerMode::Once);
+        timer.set_elapsed(Duration::from_secs(1));
+        app.world.entity_mut(player).insert(AttackTimer(timer));
+
+        app.update();
+
+        assert!(app.world.get::<AttackTimer>(player).is_none());
+    }
+
+    #[rstest]
+    fn on_npc_death_destroys_entity(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, _, _, npc) = setup;
+        app.add_systems(Update, on_hostile_death);
+
+        app.world.entity_mut(npc).insert(Stats::default());
+        app.update();
+
+        assert!(app.world.get_entity(npc).is_none());
+    }
+
+    #[rstest]
+    fn on_npc_death_alerts_neighbors(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, _, client_id, npc) = setup;
+        app.add_systems(Update, on_hostile_death);
+
+        app.world.entity_mut(npc).insert(Stats::default());
+
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(content, "Goat has died.");
+    }
+
+    #[rstest]
+    fn on_npc_death_leaves_corpse(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, npc) = setup;
+        app.add_systems(Update, on_hostile_death);
+
+        app.world.get_mut::<Hostile>(npc).unwrap().loot = Some("goat".into());
+        app.world
+            .entity_mut(npc)
+            .insert(CombatState::new(player, Distance::Near));
+
+        app.update();
+
+        let tile = app.world.get::<Parent>(player).unwrap().get();
+        let corpse = app
+            .world
+            .get::<Children>(tile)
+            .unwrap()
+            .iter()
+            .find(|child| app.world.get::<Corpse>(**child).is_some())
+            .copied()
+            .unwrap();
+
+        assert_eq!(
+            app.world.get::<Corpse>(corpse).unwrap().rights,
+            LootRights::Killer(app.world.get::<Character>(player).unwrap().id)
+        );
+        assert_eq!(app.world.get::<UnrolledLoot>(corpse).unwrap().0, "goat");
+        assert_eq!(
+            app.world.get::<Depiction>(corpse).unwrap().short_name,
+            "goat corpse"
+        );
+    }
+
+    #[rstest]
+    fn on_player_death_resets_state(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, _) = setup;
+        app.add_systems(Update, on_player_death);
+
+        app.world.entity_mut(player).insert(Stats::default());
+        app.update();
+
+        assert!(app.world.get::<CombatState>(player).is_none());
+
+        assert_eq!(
+            app.world.get::<Stats>(player).unwrap().status.health,
+            app.world.get::<Stats>(player).unwrap().max_health()
+        );
+    }
+
+    #[rstest]
+    fn on_player_death_teleports_player(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, _) = setup;
+        app.add_systems(Update, on_player_death);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        app.world.entity_mut(tile).insert(DeathSpawn);
+
+        app.world.entity_mut(player).insert(Stats::default());
+        app.update();
+
+        assert_eq!(app.world.get::<Parent>(player).unwrap().get(), tile);
+    }
+
+    #[test]
+    fn on_player_death_outside_combat() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, on_player_death);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (player, client_id, inventory) = PlayerBuilder::new()
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        let sword = ItemBuilder::new().build(&mut app);
+        app.world.entity_mut(inventory.unwrap()).add_child(sword);
+
+        // Bled out after the fight was over.
+        app.world.get_mut::<Stats>(player).unwrap().status.health = 0;
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "You have died."
+        );
+
+        let stats = app.world.get::<Stats>(player).unwrap();
+        assert_eq!(stats.status.health, stats.max_health());
+
+        let corpse = app.world.get::<Parent>(sword).unwrap().get();
+        assert!(app.world.get::<PlayerCorpse>(corpse).is_some());
+    }
+
+    #[rstest]
+    fn on_player_death_applies_penalty(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, _, _, _) = setup;
+        app.add_systems(Update, on_player_death);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (player, _, inventory) = PlayerBuilder::new()
+            .name("Astrid")
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        let sword = ItemBuilder::new().build(&mut app);
+        app.world.entity_mut(inventory.unwrap()).add_child(sword);
+
+        let mut stats = app.world.get_mut::<Stats>(player).unwrap();
+        stats.status.experience = 50;
+
+        app.world
+            .entity_mut(player)
+            .insert(CombatState::new(player, Distance::Near));
+
+        app.update();
+
+        let stats = app.world.get::<Stats>(player).unwrap();
+        let balance = Balance::default();
+        let penalty =
+            (stats.experience_to_level() as f32 * balance.death_experience_penalty) as u32;
+
+        assert_eq!(stats.status.experience, 50 - penalty);
+
+        let corpse = app.world.get::<Parent>(sword).unwrap().get();
+        let owner = app.world.get::<Character>(player).unwrap().id;
+
+        assert_eq!(app.world.get::<Parent>(corpse).unwrap().get(), tile);
+        assert_eq!(
+            app.world.get::<Corpse>(corpse).unwrap().rights,
+            LootRights::Owner(owner)
+        );
+        assert_eq!(app.world.get::<PlayerCorpse>(corpse).unwrap().owner, owner);
+
+        let events = app.world.resource::<Events<CombatEvent>>();
+        let mut reader = events.get_reader();
+
+        assert!(reader.iter(events).any(|event| matches!(
+            &event.kind,
+            CombatEventKind::ApplyCondition(args)
+                if args.target == player && args.condition == balance.death_condition
+        )));
+    }
+
+    #[test]
+    fn duel_ends_before_death() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, (end_duels, on_player_death));
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (winner, client_id, _) = PlayerBuilder::new()
+            .name("Ashur")
+            .tile(tile)
+            .build(&mut app);
+
+        let (loser, _, _) = PlayerBuilder::new().name("Bau").tile(tile).build(&mut app);
+
+        app.world
+            .entity_mut(winner)
+            .insert((CombatState::new(loser, Distance::Near), Duel(loser)));
+
+        app.world.get_mut::<Stats>(winner).unwrap().status.health = 100;
+
+        app.world.entity_mut(loser).insert((
+            CombatState::new(winner, Distance::Near),
+            Duel(winner),
+            Stats::default(),
+        ));
+
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "Ashur has bested Bau in a duel."
+        );
+
+        assert_eq!(app.world.get::<Stats>(loser).unwrap().status.health, 1);
+        assert_eq!(app.world.get::<Parent>(loser).unwrap().get(), tile);
+        assert!(app.world.get::<Duel>(winner).is_none());
+        assert!(app.world.get::<Duel>(loser).is_none());
+        assert!(!app
+            .world
+            .get::<CombatState>(winner)
+            .unwrap()
+            .threat
+            .contains(&loser));
+    }
+    #[rstest]
+    fn attack_speed_changes_mid_fight(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, _) = setup;
+        app.add_systems(Update, (start_auto_attacks, update_auto_attack_speed));
+        app.add_systems(PostUpdate, apply_modifiers);
+
+        app.update();
+
+        let before = app
+            .world
+            .get::<AutoAttackTimer>(player)
+            .unwrap()
+            .0
+            .duration();
+
+        app.world
+            .get_mut::<Modifiers>(player)
+            .unwrap()
+            .0
+            .insert("haste".into(), Modifier::flat(Stat::AttackSpeed, 50.0));
+
+        app.update();
+        app.update();
+
+        let speed = app.world.get::<Stats>(player).unwrap().auto_attack_speed();
+        let after = app
+            .world
+            .get::<AutoAttackTimer>(player)
+            .unwrap()
+            .0
+            .duration();
+
+        assert!(after < before);
+        assert_eq!(after, Duration::from_secs_f32(speed));
+    }
+
+    #[rstest]
+    fn expired_buffs_lower_vitals(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, _) = setup;
+        app.add_systems(PostUpdate, apply_modifiers);
+
+        app.world
+            .get_mut::<Modifiers>(player)
+            .unwrap()
+            .0
+            .insert("fortified".into(), Modifier::flat(Stat::Vitality, 5.0));
+
+        app.update();
+
+        let mut stats = app.world.get_mut::<Stats>(player).unwrap();
+        stats.status.health = stats.max_health();
+
+        app.world
+            .get_mut::<Modifiers>(player)
+            .unwrap()
+            .0
+            .remove("fortified");
+
+        app.update();
+
+        let stats = app.world.get::<Stats>(player).unwrap();
+
+        assert_eq!(stats.status.health, stats.max_health());
+    }
+
+    #[rstest]
+    fn conditions_hold_their_modifiers(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, _) = setup;
+        app.add_systems(
+            Update,
+            (on_combat_event_apply_condition, update_condition_timer),
+        );
+        app.add_systems(PostUpdate, apply_modifiers);
+
+        app.world
+            .get_mut::<Stats>(player)
+            .unwrap()
+            .attributes
+            .strength = 10;
+
+        let mut conditions = data::resources::Conditions::default();
+        conditions.0.insert(
+            "weakened".into(),
+            data::resources::Condition {
+                id: "weakened".into(),
+                name: "Weakened".into(),
+                description: "".into(),
+                scripts: vec![],
+                modifiers: vec![(Stat::Strength, -3.0)],
+                percent_modifiers: vec![],
+                tick: None,
+                stacking: data::resources::Stacking::Refresh,
+                tags: vec![],
+                flags: vec![],
+            },
+        );
+        app.insert_resource(conditions);
+
+        app.world.send_event(CombatEvent {
+            source: player,
+            trigger: CombatEventTrigger::Death,
+            kind: CombatEventKind::ApplyCondition(ApplyCondition {
+                target: player,
+                condition: "weakened".into(),
+                duration: Some(1.0),
+            }),
+        });
+
+        app.update();
+
+        let modifiers = app.world.get::<Modifiers>(player).unwrap();
+        assert_eq!(modifiers.sum_stat(&Stat::Strength), -3.0);
+        assert_eq!(app.world.get::<Stats>(player).unwrap().strength(), 7);
+
+        app.world
+            .get_mut::<Conditions>(player)
+            .unwrap()
+            .0
+            .get_mut("weakened")
+            .unwrap()[0]
+            .duration
+            .as_mut()
+            .unwrap()
+            .set_elapsed(Duration::from_secs(1));
+
+        app.update();
+
+        assert!(app.world.get::<Conditions>(player).unwrap().0.is_empty());
+        assert_eq!(
+            app.world
+                .get::<Modifiers>(player)
+                .unwrap()
+                .sum_stat(&Stat::Strength),
+            0.0
+        );
+        assert_eq!(app.world.get::<Stats>(player).unwrap().strength(), 10);
+    }
+
+    #[rstest]
+    fn conditions_tick_and_dispel(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, npc) = setup;
+        app.add_systems(
+            Update,
+            (update_condition_timer, on_combat_event_dispel).chain(),
+        );
+
+        let mut conditions = data::resources::Conditions::default();
+        conditions.0.insert(
+            "bleeding".into(),
+            data::resources::Condition {
+                id: "bleeding".into(),
+                name: "Bleeding".into(),
+                description: "".into(),
+                scripts: vec![],
+                modifiers: vec![],
+                percent_modifiers: vec![],
+                tick: Some(3.0),
+                stacking: data::resources::Stacking::Stack(5),
+                tags: vec!["bleed".into()],
+                flags: vec![],
+            },
+        );
+        app.insert_resource(conditions);
+
+        app.world
+            .get_mut::<Conditions>(player)
+            .unwrap()
+            .0
+            .insert("bleeding".into(), vec![AppliedCondition::new(npc, None)]);
+
+        app.update();
+
+        app.world
+            .get_mut::<Conditions>(player)
+            .unwrap()
+            .0
+            .get_mut("bleeding")
+            .unwrap()[0]
+            .tick
+            .as_mut()
+            .unwrap()
+            .set_elapsed(Duration::from_secs(3));
+
+        app.world.send_event(CombatEvent {
+            source: npc,
+            trigger: CombatEventTrigger::Movement,
+            kind: CombatEventKind::Dispel(Dispel {
+                target: player,
+                id: None,
+                tag: Some("bleed".into()),
+            }),
+        });
+
+        app.update();
+
+        let events = app.world.resource::<Events<CombatEvent>>();
+        let mut reader = events.get_reader();
+
+        let phases = reader
+            .iter(events)
+            .filter_map(|event| match &event.kind {
+                CombatEventKind::ExecuteCondition(args) if args.target == player => {
+                    Some(args.phase.to_string())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(phases, vec!["on_tick", "on_end"]);
+        assert!(app.world.get::<Conditions>(player).unwrap().0.is_empty());
+    }
+
+    fn revive(app: &mut App, entity: Entity) {
+        let mut stats = app.world.get_mut::<Stats>(entity).unwrap();
+        stats.status.health = stats.max_health();
+    }
+
+    #[rstest]
+    fn skittish_hostile_can_still_be_hit(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, npc) = setup;
+        app.add_systems(
+            Update,
+            (
+                handle_hostile_ai,
+                on_combat_event_attempt_dodge,
+                on_combat_event_attempt_block,
+            )
+                .chain(),
+        );
+
+        // Dodges on every turn it's able to.
+        app.world.resource_mut::<Balance>().hostile_dodge_chance = 1.0;
+        app.world.get_mut::<Hostile>(npc).unwrap().behavior = Behavior::Skittish;
+
+        revive(&mut app, npc);
+
+        app.world.entity_mut(npc).insert((
+            HostileAiTimer(Timer::from_seconds(HOSTILE_AI_TICK, TimerMode::Repeating)),
+            CombatState::new(player, Distance::Near),
+        ));
+
+        let skill = app
+            .world
+            .resource::<Skills>()
+            .0
+            .get("punch")
+            .unwrap()
+            .clone();
+
+        let mut reader = ManualEventReader::<CombatEvent>::default();
+        let mut outcomes = vec![];
+
+        for _ in 0..5 {
+            app.world
+                .get_mut::<HostileAiTimer>(npc)
+                .unwrap()
+                .0
+                .set_elapsed(Duration::from_secs_f32(HOSTILE_AI_TICK));
+
+            app.world.send_event(CombatEvent {
+                source: player,
+                trigger: CombatEventTrigger::Skill(skill.clone()),
+                kind: CombatEventKind::AttemptDodge,
+            });
+
+            app.update();
+
+            let events = app.world.resource::<Events<CombatEvent>>();
+
+            outcomes.extend(reader.iter(events).filter_map(|event| match &event.kind {
+                CombatEventKind::ExecuteScripts(phase) if event.source == player => {
+                    Some(phase.clone())
+                }
+                _ => None,
+            }));
+        }
+
+        // Every attack gets an answer, and its dodge only spares it one.
+        assert_eq!(outcomes.len(), 5);
+        assert!(
+            outcomes
+                .iter()
+                .filter(|phase| **phase == ExecutionPhase::OnDodge)
+                .count()
+                <= 1
+        );
+    }
+
+    #[rstest]
+    fn hostile_targets_highest_threat(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, npc) = setup;
+        app.add_systems(Update, update_threat_tables);
+
+        let tile = app.world.get::<Parent>(player).unwrap().get();
+        let (healer, _, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+
+        app.world
+            .entity_mut(healer)
+            .insert(CombatState::new(npc, Distance::Near));
+
+        let mut combat_state = CombatState::new(player, Distance::Near);
+        combat_state.threat.add(player, 5.0);
+        combat_state.threat.add(healer, 8.0);
+
+        app.world.entity_mut(npc).insert(combat_state);
+
+        for entity in [player, healer, npc] {
+            revive(&mut app, entity);
+        }
+
+        app.update();
+
+        assert_eq!(app.world.get::<CombatState>(npc).unwrap().target, healer);
+    }
+
+    #[rstest]
+    fn taunt_takes_top_threat(setup: (App, Entity, ClientId, Entity)) {
+        let (_, player, _, npc) = setup;
+
+        let mut combat_state = CombatState::new(npc, Distance::Near);
+        combat_state.threat.add(npc, 20.0);
+        combat_state.threat.add(player, 5.0);
+        let bonus = Balance::default().taunt_threat_bonus;
+        combat_state.threat.taunt(player, bonus);
+
+        assert_eq!(combat_state.threat.highest(), Some(player));
+        assert_eq!(combat_state.threat.0[&player], 20.0 + bonus);
+    }
+
+    #[rstest]
+    fn threat_drops_opponents_who_leave(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, npc) = setup;
+        app.add_systems(Update, update_threat_tables);
+
+        app.world
+            .entity_mut(npc)
+            .insert(CombatState::new(player, Distance::Near));
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let elsewhere = TileBuilder::new().build(&mut app, zone);
+
+        app.world.entity_mut(player).set_parent(elsewhere);
+
+        for entity in [player, npc] {
+            revive(&mut app, entity);
+        }
+
+        app.update();
+
+        assert!(app.world.get::<CombatState>(npc).is_none());
+        assert!(app.world.get::<CombatState>(player).is_none());
+    }
+
+    #[rstest]
+    fn combat_continues_after_first_enemy_dies(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, npc) = setup;
+        app.add_systems(Update, (on_hostile_death, update_threat_tables).chain());
+
+        let tile = app.world.get::<Parent>(player).unwrap().get();
+        let wolf = NpcBuilder::new()
+            .name("Wolf")
+            .combat(true)
+            .tile(tile)
+            .build(&mut app);
+
+        app.world
+            .get_mut::<CombatState>(player)
+            .unwrap()
+            .threat
+            .add(wolf, 1.0);
+
+        app.world
+            .entity_mut(npc)
+            .insert(CombatState::new(player, Distance::Near));
+        app.world
+            .entity_mut(wolf)
+            .insert(CombatState::new(player, Distance::Near));
+
+        for entity in [player, wolf] {
+            revive(&mut app, entity);
+        }
+
+        app.update();
+        app.update();
+
+        let combat_state = app.world.get::<CombatState>(player).unwrap();
+
+        assert_eq!(combat_state.target, wolf);
+        assert!(!combat_state.threat.contains(&npc));
+    }
+
+    #[rstest]
+    fn npc_death_splits_experience_with_party(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, npc) = setup;
+        app.add_systems(Update, on_hostile_death);
+
+        let tile = app.world.get::<Parent>(player).unwrap().get();
+        let (member, _, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+        let (bystander, _, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+
+        let mut party = Party::new(app.world.get::<Character>(player).unwrap());
+        party.join(app.world.get::<Character>(member).unwrap());
+        app.world.spawn(party);
+
+        app.world
+            .entity_mut(npc)
+            .insert(CombatState::new(player, Distance::Near));
+
+        let reward = app.world.get::<Stats>(npc).unwrap().experience_reward();
+
+        app.update();
+
+        let share = reward.div_ceil(2);
+
+        assert_eq!(
+            app.world.get::<Stats>(player).unwrap().status.experience,
+            share
+        );
+        assert_eq!(
+            app.world.get::<Stats>(member).unwrap().status.experience,
+            share
+        );
+        assert_eq!(
+            app.world.get::<Stats>(bystander).unwrap().status.experience,
+            0
+        );
+    }
+
+    #[rstest]
+    fn learns_skills_by_level(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, client_id, _) = setup;
+        app.add_systems(Update, learn_skills);
+
+        app.world
+            .resource_mut::<Masteries>()
+            .0
+            .get_mut("freelancer")
+            .unwrap()
+            .skills
+            .push(SkillNode {
+                skill: "kick".into(),
+                level: 2,
+                requires: vec!["punch".into()],
+            });
+
+        app.update();
+
+        let learned = &app.world.get::<LearnedSkills>(player).unwrap().0;
+
+        assert!(learned.contains("punch"));
+        assert!(!learned.contains("kick"));
+        assert!(get_message_content(&mut app, client_id).is_none());
+
+        app.world.get_mut::<Stats>(player).unwrap().level = 2;
+        app.update();
+
+        assert!(app
+            .world
+            .get::<LearnedSkills>(player)
+            .unwrap()
+            .0
+            .contains("kick"));
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "You have learned kick."
+        );
+    }
+
+    #[rstest]
+    #[case(
+        1.0,
+        Distance::Far,
+        "You put some distance between yourself and the goat."
+    )]
+    #[case(0.0, Distance::Near, "The goat keeps pace with you.")]
+    fn reposition_contested_by_fleet(
+        setup: (App, Entity, ClientId, Entity),
+        #[case] chance: f32,
+        #[case] expected: Distance,
+        #[case] message: &str,
+    ) {
+        let (mut app, player, client_id, npc) = setup;
+        app.add_systems(
+            Update,
+            (update_repositioning, on_combat_event_set_distance).chain(),
+        );
+
+        app.world
+            .entity_mut(npc)
+            .insert(CombatState::new(player, Distance::Near));
+
+        app.world.get_mut::<Stats>(player).unwrap().balance = Arc::new(Balance {
+            base_reposition_chance: chance,
+            reposition_chance_cap: 1.0,
+            ..Default::default()
+        });
+
+        let mut timer = Timer::from_seconds(1.0, TimerMode::Once);
+        timer.set_elapsed(Duration::from_secs(1));
+
+        app.world.entity_mut(player).insert(Repositioning {
+            distance: Distance::Far,
+            timer,
+        });
+
+        app.update();
+
+        assert!(app.world.get::<Repositioning>(player).is_none());
+        assert_eq!(
+            app.world.get::<CombatState>(player).unwrap().distance,
+            expected
+        );
+        assert_eq!(get_message_content(&mut app, client_id).unwrap(), message);
+    }
+
+    #[rstest]
+    fn wielded_weapon_sets_attack_speed(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, _) = setup;
+        app.add_systems(Update, start_auto_attacks);
+
+        let dagger = ItemBuilder::new()
+            .name("Dagger")
+            .equippable(Slot::MainHand)
+            .build(&mut app);
+
+        app.world.entity_mut(dagger).insert(Weapon {
+            min_damage: 1,
+            max_damage: 2,
+            kind: "physical".into(),
+            speed: 0.5,
+            auto_attack: None,
+        });
+
+        app.world.entity_mut(player).add_child(dagger);
+        app.world
+            .get_mut::<Equipment>(player)
+            .unwrap()
+            .0
+            .insert(Slot::MainHand, dagger);
+
+        app.update();
+
+        let speed = app.world.get::<Stats>(player).unwrap().auto_attack_speed();
+
+        assert_eq!(
+            app.world
+                .get::<AutoAttackTimer>(player)
+                .unwrap()
+                .0
+                .duration(),
+            Duration::from_secs_f32(speed * 0.5)
+        );
+    }
+
+    #[rstest]
+    fn wielding_mid_fight_sets_attack_speed(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, _, _) = setup;
+        app.add_systems(Update, (start_auto_attacks, update_auto_attack_speed));
+
+        app.update();
+
+        let dagger = ItemBuilder::new()
+            .name("Dagger")
+            .equippable(Slot::MainHand)
+            .build(&mut app);
+
+        app.world.entity_mut(dagger).insert(Weapon {
+            min_damage: 1,
+            max_damage: 2,
+            kind: "physical".into(),
+            speed: 0.5,
+            auto_attack: None,
+        });
+
+        app.world.entity_mut(player).add_child(dagger);
+        app.world
+            .get_mut::<Equipment>(player)
+            .unwrap()
+            .0
+            .insert(Slot::MainHand, dagger);
+
+        app.update();
+
+        let speed = app.world.get::<Stats>(player).unwrap().auto_attack_speed();
+
+        assert_eq!(
+            app.world
+                .get::<AutoAttackTimer>(player)
+                .unwrap()
+                .0
+                .duration(),
+            Duration::from_secs_f32(speed * 0.5)
+        );
+    }
+
+    #[rstest]
+    fn out_of_range_fails_with_reason(setup: (App, Entity, ClientId, Entity)) {
+        let (mut app, player, client_id, _) = setup;
+        app.add_systems(Update, on_combat_event_attempt_hit);
+
+        app.world.get_mut::<CombatState>(player).unwrap().distance = Distance::Far;
+
+        let punch = app
+            .world
+            .resource::<Skills>()
+            .0
+            .get("punch")
+            .unwrap()
+            .clone();
+
+        app.world.send_event(CombatEvent {
+            source: player,
+            trigger: CombatEventTrigger::Skill(punch),
+            kind: CombatEventKind::AttemptHit,
+        });
+
+        app.update();
+
+        let events = app.world.resource::<Events<CombatEvent>>();
+
+        assert!(!events
+            .iter_current_update_events()
+            .any(|event| matches!(event.kind, CombatEventKind::ExecuteScripts(_))));
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "You are too far away to use Punch."
+        );
     }
 }
@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+
+use crate::{
+    data::resources::ControlFlag, input::events::Command, player::components::Character,
+    visual::components::Depiction,
+};
+
+use super::components::{CombatState, Distance};
+
+/// Adds the opponent to the entity's threat table, putting the entity in
+/// combat with them if it isn't already fighting someone else.
+pub fn engage(
+    bevy: &mut Commands,
+    entity: Entity,
+    combat_state: Option<Mut<CombatState>>,
+    opponent: Entity,
+    distance: Distance,
+) {
+    match combat_state {
+        Some(mut combat_state) => combat_state.threat.add(opponent, 0.0),
+        None => {
+            bevy.entity(entity)
+                .insert(CombatState::new(opponent, distance));
+        }
+    }
+}
+
+/// How a fighter is named mid-sentence: players by name, anything else as
+/// "the" whatever it is.
+pub fn fighter_name(character: Option<&Character>, depiction: Option<&Depiction>) -> String {
+    character
+        .map(|character| character.name.clone())
+        .or_else(|| depiction.map(|depiction| format!("the {}", depiction.short_name)))
+        .unwrap_or_else(|| "someone".into())
+}
+
+/// Upper cases the first letter, for names that start a sentence.
+pub fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// The crowd control flag, if any, that stops the command from being carried
+/// out. Anything that doesn't take action, like talking or looking around, is
+/// always allowed.
+pub fn restricting_flag(command: &Command, flags: &[ControlFlag]) -> Option<ControlFlag> {
+    flags.iter().copied().find(|flag| match flag {
+        ControlFlag::Stunned => matches!(
+            command,
+            Command::Advance
+                | Command::Attack(_)
+                | Command::Block
+                | Command::Close(_)
+                | Command::Dodge
+                | Command::Drop(_)
+                | Command::Duel(_)
+                | Command::Enter(_)
+                | Command::Give(_)
+                | Command::Movement(_)
+                | Command::Open(_)
+                | Command::Place(_)
+                | Command::Remove(_)
+                | Command::Retreat
+                | Command::Sit(_)
+                | Command::Stand
+                | Command::Take(_)
+                | Command::UseSkill(_)
+                | Command::Wear(_)
+                | Command::Wield(_)
+        ),
+        ControlFlag::Rooted => matches!(
+            command,
+            Command::Advance | Command::Enter(_) | Command::Movement(_) | Command::Retreat
+        ),
+        ControlFlag::Silenced => matches!(command, Command::UseSkill(_)),
+        ControlFlag::Disarmed => matches!(command, Command::Attack(_) | Command::Wield(_)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::events::DuelAction;
+
+    use super::*;
+
+    #[test]
+    fn restricts_commands() {
+        let rooted = [ControlFlag::Rooted];
+
+        assert_eq!(
+            restricting_flag(&Command::Movement(("north".into(), false)), &rooted),
+            Some(ControlFlag::Rooted)
+        );
+        assert_eq!(restricting_flag(&Command::Dodge, &rooted), None);
+
+        let stunned_and_silenced = [ControlFlag::Stunned, ControlFlag::Silenced];
+
+        assert_eq!(
+            restricting_flag(
+                &Command::UseSkill(("punch".into(), None)),
+                &stunned_and_silenced
+            ),
+            Some(ControlFlag::Stunned)
+        );
+        assert_eq!(
+            restricting_flag(&Command::Say("help".into()), &stunned_and_silenced),
+            None
+        );
+        assert_eq!(
+            restricting_flag(
+                &Command::Give(("coin".into(), false, "Bau".into())),
+                &stunned_and_silenced
+            ),
+            Some(ControlFlag::Stunned)
+        );
+
+        let stunned = [ControlFlag::Stunned];
+
+        for command in [
+            Command::Wear("cap".into()),
+            Command::Wield("knife".into()),
+            Command::Remove("cap".into()),
+            Command::Duel(DuelAction::Accept),
+        ] {
+            assert_eq!(
+                restricting_flag(&command, &stunned),
+                Some(ControlFlag::Stunned)
+            );
+        }
+
+        let disarmed = [ControlFlag::Disarmed];
+
+        assert_eq!(
+            restricting_flag(&Command::Wield("knife".into()), &disarmed),
+            Some(ControlFlag::Disarmed)
+        );
+        assert_eq!(
+            restricting_flag(&Command::Wear("cap".into()), &disarmed),
+            None
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use bevy::prelude::*;
+use bevy_mod_sysfail::sysfail;
+use bevy_nest::prelude::*;
+use regex::Regex;
+
+use crate::{
+    data::{resources::Balance, systems::read_balance},
+    input::events::{Command, ParseError, ParsedCommand},
+    keycard::{Keycard, RELOAD},
+    player::components::{Client, Online},
+};
+
+static REGEX: OnceLock<Regex> = OnceLock::new();
+
+pub fn handle_reload(content: &str) -> Result<Command, ParseError> {
+    let regex = REGEX.get_or_init(|| Regex::new(r"^reload( (?P<what>.*))?$").unwrap());
+
+    match regex.captures(content) {
+        None => Err(ParseError::WrongCommand),
+        Some(captures) => {
+            let what = captures
+                .name("what")
+                .map(|m| m.as_str().trim().to_lowercase())
+                .filter(|m| !m.is_empty())
+                .ok_or(ParseError::InvalidArguments("Reload what?".into()))?;
+
+            Ok(Command::Reload(what))
+        }
+    }
+}
+
+#[sysfail(log)]
+pub fn reload(
+    mut commands: EventReader<ParsedCommand>,
+    mut outbox: EventWriter<Outbox>,
+    mut balance: ResMut<Balance>,
+    players: Query<(&Client, &Keycard), With<Online>>,
+) -> Result<(), anyhow::Error> {
+    for command in commands.iter() {
+        if let Command::Reload(what) = &command.command {
+            let (client, keycard) = players
+                .iter()
+                .find(|(c, _)| c.id == command.from)
+                .context("Player not found")?;
+
+            if !keycard.can(RELOAD) {
+                continue;
+            }
+
+            match what.as_str() {
+                "balance" => match read_balance() {
+                    Ok(reloaded) => {
+                        *balance = reloaded;
+
+                        outbox.send_text(client.id, "Balance reloaded.");
+                    }
+                    Err(err) => {
+                        outbox.send_text(client.id, format!("Failed to reload balance: {err}"));
+                    }
+                },
+                _ => {
+                    outbox.send_text(client.id, format!("You can't reload {what}."));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{
+        app_builder::AppBuilder,
+        player_builder::PlayerBuilder,
+        tile_builder::{TileBuilder, ZoneBuilder},
+        utils::{get_message_content, send_message},
+    };
+
+    #[test]
+    fn parses() {
+        let balance = handle_reload("reload balance");
+        assert_eq!(balance, Ok(Command::Reload("balance".into())));
+
+        let nothing = handle_reload("reload");
+        assert_eq!(
+            nothing,
+            Err(ParseError::InvalidArguments("Reload what?".into()))
+        );
+    }
+
+    #[test]
+    fn balance_file_parses() {
+        assert!(ron::from_str::<Balance>(include_str!("../../../assets/balance.ron")).is_ok());
+    }
+
+    #[test]
+    fn balance_keeps_what_the_file_leaves_out() {
+        let balance = Balance::from_ron("(base_health: 250.0)").unwrap();
+
+        assert_eq!(balance.base_health, 250.0);
+        assert_eq!(balance.base_vigor, Balance::default().base_vigor);
+    }
+
+    #[test]
+    fn unknown_target() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, reload);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (_, client_id, _) = PlayerBuilder::new()
+            .role(Keycard::admin())
+            .tile(tile)
+            .build(&mut app);
+
+        send_message(&mut app, client_id, "reload everything");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(content, "You can't reload everything.");
+    }
+
+    #[test]
+    fn forbidden() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, reload);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (_, client_id, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+
+        send_message(&mut app, client_id, "reload balance");
+        app.update();
+
+        assert!(get_message_content(&mut app, client_id).is_none());
+    }
+}
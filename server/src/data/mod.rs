@@ -1,3 +1,4 @@
+pub mod commands;
 pub mod plugin;
 pub mod resources;
 mod systems;
@@ -1,8 +1,15 @@
+use std::collections::BTreeSet;
+
 use bevy::{prelude::*, utils::HashMap};
-use serde::Deserialize;
+use rand::{seq::SliceRandom, Rng};
+use ron::Value;
+use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumIter};
 
-use crate::combat::components::Distance;
+use crate::{
+    combat::components::{Approach, Distance},
+    lua::events::ExecutionPhase,
+};
 
 #[derive(Debug, Deserialize)]
 pub struct DamageKind {
@@ -36,7 +43,43 @@ pub struct Mastery {
     pub dexterity: u32,
     pub intelligence: u32,
     pub auto_attack: String,
-    pub skills: Vec<String>,
+    /// The mastery's skill tree, roots first.
+    #[serde(default)]
+    pub skills: Vec<SkillNode>,
+}
+
+impl Mastery {
+    pub fn node(&self, skill: &str) -> Option<&SkillNode> {
+        self.skills.iter().find(|node| node.skill == skill)
+    }
+
+    /// Skills in the tree a character can learn but hasn't yet.
+    pub fn learnable<'a>(
+        &'a self,
+        level: u32,
+        learned: &'a BTreeSet<String>,
+    ) -> impl Iterator<Item = &'a SkillNode> {
+        self.skills
+            .iter()
+            .filter(move |node| !learned.contains(&node.skill) && node.can_learn(level, learned))
+    }
+}
+
+/// A skill in a mastery's tree and what it takes to learn it.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SkillNode {
+    pub skill: String,
+    #[serde(default)]
+    pub level: u32,
+    /// Skills that have to be learned first.
+    #[serde(default)]
+    pub requires: Vec<String>,
+}
+
+impl SkillNode {
+    pub fn can_learn(&self, level: u32, learned: &BTreeSet<String>) -> bool {
+        level >= self.level && self.requires.iter().all(|id| learned.contains(id))
+    }
 }
 
 /// A collection of all masteries.
@@ -55,14 +98,86 @@ pub struct Skill {
     pub distance: Distance,
     pub dodge_difficulty: f32,
     pub block_difficulty: f32,
+    #[serde(default)]
+    pub approach: SkillApproach,
+    /// Makes the skill go off by itself in answer to an attack, rather than
+    /// being used.
+    #[serde(default)]
+    pub reaction: Option<Reaction>,
+    /// An item prototype used up from the inventory every time the skill is
+    /// used, like arrows or throwing knives.
+    #[serde(default)]
+    pub ammo: Option<String>,
     pub scripts: Vec<String>,
 }
 
+impl Skill {
+    pub fn reaches(&self, distance: Distance) -> bool {
+        self.distance == Distance::Either || self.distance == distance
+    }
+
+    /// Why the skill can't be used from where its user is standing.
+    pub fn out_of_range(&self) -> String {
+        match self.distance {
+            Distance::Far => format!("You are too close to use {}.", self.name),
+            _ => format!("You are too far away to use {}.", self.name),
+        }
+    }
+}
+
+/// Where a skill has to be, or would rather be, used from.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum SkillApproach {
+    #[default]
+    Any,
+    /// Can't be used from anywhere else.
+    Requires(Approach),
+    /// Harder to dodge and block when used from here.
+    Prefers(Approach),
+}
+
+/// What sets off a reactive skill. Reactions run for the defender, with
+/// whoever attacked them as the target.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+pub enum Reaction {
+    Block,
+    Dodge,
+    Hit,
+    /// Taking damage that leaves health below this fraction of its maximum.
+    HitBelow(f32),
+}
+
+impl Reaction {
+    pub fn phase(&self) -> ExecutionPhase {
+        match self {
+            Self::Block => ExecutionPhase::OnBlockReaction,
+            Self::Dodge => ExecutionPhase::OnDodgeReaction,
+            Self::Hit | Self::HitBelow(_) => ExecutionPhase::OnHitReaction,
+        }
+    }
+}
+
+impl SkillApproach {
+    pub fn allows(&self, approach: Approach) -> bool {
+        !matches!(self, Self::Requires(required) if *required != approach)
+    }
+
+    /// Extra dodge and block difficulty for using the skill from the approach.
+    pub fn difficulty(&self, approach: Approach, balance: &Balance) -> f32 {
+        match self {
+            Self::Prefers(preferred) if *preferred == approach => {
+                balance.preferred_approach_difficulty
+            }
+            _ => 0.0,
+        }
+    }
+}
+
 /// A collection of all available skills.
 #[derive(Default, Resource)]
 pub struct Skills(pub HashMap<String, Skill>);
 
-#[derive(Debug, Clone, Reflect, PartialEq, EnumIter, Display)]
+#[derive(Debug, Clone, Reflect, PartialEq, Eq, Hash, EnumIter, Display, Serialize, Deserialize)]
 pub enum Stat {
     Vitality,
     Stamina,
@@ -83,13 +198,278 @@ pub enum Stat {
     CritStrikeDamage,
 }
 
+/// How a modifier's amount is applied to its stat.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Reflect, Serialize, Deserialize)]
+pub enum ModifierKind {
+    /// Added to the stat.
+    #[default]
+    Flat,
+    /// Scales the stat, after every flat modifier has been added.
+    Percent,
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+pub struct Modifier {
+    pub stat: Stat,
+    pub amount: f32,
+    #[serde(default)]
+    pub kind: ModifierKind,
+}
+
+impl Modifier {
+    pub fn flat(stat: Stat, amount: f32) -> Self {
+        Self {
+            stat,
+            amount,
+            kind: ModifierKind::Flat,
+        }
+    }
+
+    pub fn percent(stat: Stat, amount: f32) -> Self {
+        Self {
+            stat,
+            amount,
+            kind: ModifierKind::Percent,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Condition {
     pub id: String,
     pub name: String,
     pub description: String,
     pub scripts: Vec<String>,
+    /// Stat modifiers held for as long as the condition lasts, multiplied by
+    /// its stacks.
+    #[serde(default)]
+    pub modifiers: Vec<(Stat, f32)>,
+    /// Like `modifiers`, but each amount is a fraction of the stat, so `0.1`
+    /// is ten percent more.
+    #[serde(default)]
+    pub percent_modifiers: Vec<(Stat, f32)>,
+    /// Seconds between each run of its `on_tick` scripts.
+    #[serde(default)]
+    pub tick: Option<f32>,
+    #[serde(default)]
+    pub stacking: Stacking,
+    /// Used to dispel whole groups of conditions at once.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Crowd control imposed on whoever has the condition.
+    #[serde(default)]
+    pub flags: Vec<ControlFlag>,
+}
+
+/// Crowd control a condition can impose, restricting what its target can do.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum ControlFlag {
+    /// Can't act at all.
+    Stunned,
+    /// Can't move or change distance.
+    Rooted,
+    /// Can't use skills.
+    Silenced,
+    /// Can't attack.
+    Disarmed,
+}
+
+impl ControlFlag {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::Stunned => "You are stunned and can't act.",
+            Self::Rooted => "You are rooted in place.",
+            Self::Silenced => "You are silenced and can't use skills.",
+            Self::Disarmed => "You are disarmed and can't attack.",
+        }
+    }
+}
+
+/// What happens when a condition is applied to something that already has it.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum Stacking {
+    /// The existing condition starts over.
+    #[default]
+    Refresh,
+    /// Adds a stack, up to the given maximum, and starts over.
+    Stack(u32),
+    /// Each source keeps its own instance, which starts over when they
+    /// reapply it.
+    UniquePerSource,
+}
+
+impl Condition {
+    /// The condition's modifiers keyed by the condition ID, so they can be
+    /// found and removed again when it ends.
+    pub fn keyed_modifiers(&self) -> impl Iterator<Item = (String, Modifier)> + '_ {
+        let flat = self
+            .modifiers
+            .iter()
+            .enumerate()
+            .map(|(index, (stat, amount))| {
+                (
+                    format!("{}:{index}", self.id),
+                    Modifier::flat(stat.clone(), *amount),
+                )
+            });
+
+        let percent = self
+            .percent_modifiers
+            .iter()
+            .enumerate()
+            .map(|(index, (stat, amount))| {
+                (
+                    format!("{}:percent:{index}", self.id),
+                    Modifier::percent(stat.clone(), *amount),
+                )
+            });
+
+        flat.chain(percent)
+    }
 }
 
 #[derive(Default, Resource)]
 pub struct Conditions(pub HashMap<String, Condition>);
+
+/// A weighted table of item prototypes a hostile may drop when it dies.
+#[derive(Debug, Deserialize, Clone)]
+pub struct LootTable {
+    pub id: String,
+    pub rolls: (u8, u8),
+    pub entries: Vec<LootEntry>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct LootEntry {
+    pub prototype: String,
+    pub weight: u32,
+}
+
+impl LootTable {
+    /// Picks between `rolls.0` and `rolls.1` prototypes, each chosen by weight.
+    pub fn roll(&self, rng: &mut impl Rng) -> Vec<String> {
+        let amount = rng.gen_range(self.rolls.0..=self.rolls.1.max(self.rolls.0));
+
+        (0..amount)
+            .filter_map(|_| {
+                self.entries
+                    .choose_weighted(rng, |entry| entry.weight)
+                    .ok()
+                    .map(|entry| entry.prototype.clone())
+            })
+            .collect()
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct LootTables(pub HashMap<String, LootTable>);
+
+/// The `balance.ron` the server was built with.
+const BUNDLED_BALANCE: &str = include_str!("../../assets/balance.ron");
+
+/// Combat tuning, read from `balance.ron` so it can be changed without a
+/// rebuild. Anything missing from the file keeps its bundled value.
+#[derive(Resource, Debug, Clone, Deserialize, PartialEq)]
+pub struct Balance {
+    pub base_health: f32,
+    pub max_health_stat_contribution: f32,
+    pub max_health_level_contribution: f32,
+    pub base_health_regen: u32,
+    pub health_regen_stat_contribution: f32,
+    pub base_vigor: f32,
+    pub max_vigor_stat_contribution: f32,
+    pub max_vigor_level_contribution: f32,
+    pub base_vigor_regen: u32,
+    pub vigor_regen_stat_contribution: f32,
+    pub base_attack_speed: f32,
+    pub attack_speed_factor: f32,
+    pub attack_speed_cap: f32,
+    pub auto_attack_speed_factor: f32,
+    pub base_auto_attack_damage: u32,
+    pub auto_attack_level_contribution: u32,
+    pub auto_attack_stat_contribution: f32,
+    pub base_flee_chance: f32,
+    pub flee_chance_dominance_contribution: f32,
+    pub flee_chance_fleet_contribution: f32,
+    pub flee_cooldown: f32,
+    pub reposition_time: f32,
+    pub base_reposition_chance: f32,
+    pub reposition_chance_fleet_contribution: f32,
+    pub reposition_chance_cap: f32,
+    pub base_dodge_chance: f32,
+    pub dodge_chance_dexterity_contribution: f32,
+    pub dodge_chance_stat_contribution: f32,
+    pub dodge_chance_cap: f32,
+    pub base_dodge_rate: f32,
+    pub dodge_rate_dexterity_contribution: f32,
+    pub dodge_rate_stat_contribution: f32,
+    pub dodge_rate_cap: f32,
+    pub manual_dodge_timer: f32,
+    pub base_block_chance: f32,
+    pub block_chance_strength_contribution: f32,
+    pub block_chance_stat_contribution: f32,
+    pub block_chance_cap: f32,
+    pub base_block_rate: f32,
+    pub block_rate_strength_contribution: f32,
+    pub block_rate_stat_contribution: f32,
+    pub block_rate_cap: f32,
+    pub manual_block_timer: f32,
+    pub base_crit_strike_chance: f32,
+    pub crit_strike_stat_contribution: f32,
+    pub crit_strike_chance_cap: f32,
+    pub base_crit_damage_multiplier: f32,
+    pub crit_damage_stat_contribution: f32,
+    pub resistance_factor: f32,
+    pub resistance_cap: f32,
+    pub heal_threat_factor: f32,
+    pub taunt_threat_bonus: f32,
+    pub rear_crit_strike_bonus: f32,
+    pub rear_dodge_difficulty: f32,
+    pub preferred_approach_difficulty: f32,
+    pub experience_per_level: u32,
+    pub base_experience_reward: u32,
+    pub experience_reward_level_contribution: u32,
+    /// Health fraction below which aggressive hostiles start blocking.
+    pub aggressive_block_threshold: f32,
+    /// Health fraction at or below which skittish hostiles try to run.
+    pub skittish_flee_threshold: f32,
+    /// Chance a hostile that's able to dodge chooses to on its turn.
+    pub hostile_dodge_chance: f32,
+    pub aggression_grace_period: f32,
+    pub aggression_step: f32,
+    pub aggression_level_difference: u32,
+    pub corpse_decay_timer: f32,
+    /// Fraction of the experience needed for the next level lost on death.
+    pub death_experience_penalty: f32,
+    pub death_condition: String,
+    pub death_condition_duration: f32,
+    pub player_corpse_decay_timer: f32,
+    /// Health fraction at which a duelist yields.
+    pub duel_yield_threshold: f32,
+}
+
+impl Balance {
+    /// Reads tuning from RON, filling in whatever it leaves out from the
+    /// bundled file.
+    pub fn from_ron(ron: &str) -> Result<Self, anyhow::Error> {
+        let (Value::Map(mut values), Value::Map(overrides)) = (
+            ron::from_str::<Value>(BUNDLED_BALANCE)?,
+            ron::from_str::<Value>(ron)?,
+        ) else {
+            anyhow::bail!("Balance should be a struct of values");
+        };
+
+        for (key, value) in overrides.into_iter() {
+            values.insert(key, value);
+        }
+
+        Ok(Value::Map(values).into_rust()?)
+    }
+}
+
+impl Default for Balance {
+    fn default() -> Self {
+        ron::from_str(BUNDLED_BALANCE).expect("Bundled balance.ron is invalid")
+    }
+}
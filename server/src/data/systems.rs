@@ -2,10 +2,23 @@ use bevy::{asset::FileAssetIo, prelude::*, utils::HashMap};
 use walkdir::WalkDir;
 
 use super::resources::{
-    Condition, Conditions, DamageKind, DamageKinds, Masteries, Mastery, Resistance, Resistances,
-    Skill, Skills,
+    Balance, Condition, Conditions, DamageKind, DamageKinds, LootTable, LootTables, Masteries,
+    Mastery, Resistance, Resistances, Skill, Skills,
 };
 
+/// Reads `balance.ron` fresh from disk.
+pub fn read_balance() -> Result<Balance, anyhow::Error> {
+    let path = FileAssetIo::get_base_path().join("assets/balance.ron");
+
+    debug!("Loading balance from: {:?}", path);
+
+    Balance::from_ron(&std::fs::read_to_string(path)?)
+}
+
+pub fn load_balance(mut balance: ResMut<Balance>) {
+    *balance = read_balance().expect("Failed to load balance");
+}
+
 pub fn load_damage_kinds(mut damage_kinds: ResMut<DamageKinds>) {
     let path = FileAssetIo::get_base_path().join("assets/damage-types.ron");
 
@@ -147,3 +160,37 @@ pub fn load_conditions(mut conditions: ResMut<Conditions>) {
         conditions.0.insert(parsed.id.clone(), parsed);
     }
 }
+
+pub fn load_loot_tables(mut loot_tables: ResMut<LootTables>) {
+    let path = FileAssetIo::get_base_path().join("assets");
+
+    debug!("Loading loot tables from: {:?}", path);
+
+    for entry in WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| {
+            e.path()
+                .file_name()
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .ends_with(".loot.ron")
+        })
+    {
+        let path = entry.path();
+
+        let contents = std::fs::read_to_string(path)
+            .expect("Failed to load loot table definition")
+            .as_str()
+            .to_string();
+
+        let parsed = ron::from_str::<LootTable>(contents.as_str())
+            .expect("Failed to parse loot table definition");
+
+        debug!("Loaded loot table: {:?}", parsed.id);
+
+        loot_tables.0.insert(parsed.id.clone(), parsed);
+    }
+}
@@ -7,6 +7,7 @@ use bevy_nest::prelude::*;
 pub enum ChatChannel {
     Chat,
     Novice,
+    Party,
 }
 
 impl Display for ChatChannel {
@@ -14,6 +15,7 @@ impl Display for ChatChannel {
         match self {
             Self::Chat => write!(f, "chat"),
             Self::Novice => write!(f, "novice"),
+            Self::Party => write!(f, "party"),
         }
     }
 }
@@ -23,10 +25,27 @@ impl ChatChannel {
         match self {
             Self::Chat => "cyan".into(),
             Self::Novice => "green".into(),
+            Self::Party => "magenta".into(),
         }
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum PartyAction {
+    List,
+    Invite(String),
+    Accept,
+    Leave,
+    Kick(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DuelAction {
+    Challenge(String),
+    Accept,
+    Decline,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Command {
     Advance,
@@ -35,31 +54,41 @@ pub enum Command {
     Block,
     Chat((ChatChannel, String)),
     Close(Option<String>),
+    CombatStats,
     Config((Option<String>, Option<String>)),
     Describe(Option<String>),
     Dodge,
     Drop((String, bool)),
+    Duel(DuelAction),
     Emote(String),
     Enter(Option<String>),
+    Equipment,
     Examine(String),
+    Give((String, bool, String)),
     Inventory,
     Look(Option<String>),
     Map,
     Menu(String),
     Movement((String, bool)),
     Open(Option<String>),
+    Party(PartyAction),
     Place((String, String)),
     Quit,
+    Reload(String),
+    Remove(String),
     Retreat,
     Roll(String),
     Say(String),
     Scan((bool, Option<String>)),
     Sit(Option<String>),
+    Skills,
     Stand,
     Take((String, bool, Option<String>)),
     Time,
     UseSkill((String, Option<String>)),
+    Wear(String),
     Who,
+    Wield(String),
     Yell(String),
 }
 
@@ -4,10 +4,16 @@ use bevy_mod_sysfail::sysfail;
 use bevy_nest::prelude::*;
 
 use crate::{
-    combat::commands::{
-        advance::handle_advance, attack::handle_attack, block::handle_block, dodge::handle_dodge,
-        retreat::handle_retreat, use_skill::handle_use_skill,
+    combat::{
+        commands::{
+            advance::handle_advance, attack::handle_attack, block::handle_block,
+            combat_stats::handle_combat_stats, dodge::handle_dodge, duel::handle_duel,
+            retreat::handle_retreat, skills::handle_skills, use_skill::handle_use_skill,
+        },
+        components::Conditions,
+        utils::restricting_flag,
     },
+    data::{self, commands::reload::handle_reload},
     interact::{
         commands::{
             examine::handle_examine, place::handle_place, quit::handle_quit, roll::handle_roll,
@@ -15,8 +21,16 @@ use crate::{
         },
         components::InMenu,
     },
-    items::commands::{drop::handle_drop, inventory::handle_inventory},
+    items::commands::{
+        drop::handle_drop,
+        equip::{handle_wear, handle_wield},
+        equipment::handle_equipment,
+        give::handle_give,
+        inventory::handle_inventory,
+        remove::handle_remove,
+    },
     menu::commands::menu::handle_menu,
+    party::commands::party::handle_party,
     player::{
         commands::{config::handle_config, describe::handle_describe},
         components::{Client, Online},
@@ -41,7 +55,8 @@ pub fn parse_command(
     mut inbox: EventReader<Inbox>,
     mut outbox: EventWriter<Outbox>,
     mut commands: EventWriter<ParsedCommand>,
-    clients: Query<(&Client, Option<&InMenu>), With<Online>>,
+    clients: Query<(&Client, Option<&InMenu>, Option<&Conditions>), With<Online>>,
+    conditions: Res<data::resources::Conditions>,
 ) -> Result<(), anyhow::Error> {
     for (input, content) in inbox.iter().filter_map(|m| {
         if let Message::Text(content) = &m.content {
@@ -50,9 +65,9 @@ pub fn parse_command(
             None
         }
     }) {
-        let (client, in_menu) = clients
+        let (client, in_menu, applied_conditions) = clients
             .iter()
-            .find(|(c, _)| c.id == input.from)
+            .find(|(c, _, _)| c.id == input.from)
             .context("Client not found")?;
 
         let handlers: Vec<Box<dyn Fn(&str) -> Result<Command, ParseError>>> = if in_menu.is_some() {
@@ -67,15 +82,22 @@ pub fn parse_command(
                 Box::new(handle_announce),
                 Box::new(handle_attack),
                 Box::new(handle_block),
+                // Party comes before chat so its subcommands aren't sent
+                // to the party channel.
+                Box::new(handle_party),
                 Box::new(handle_chat),
                 Box::new(handle_close),
+                Box::new(handle_combat_stats),
                 Box::new(handle_config),
                 Box::new(handle_describe),
                 Box::new(handle_dodge),
                 Box::new(handle_drop),
+                Box::new(handle_duel),
                 Box::new(handle_emote),
                 Box::new(handle_enter),
+                Box::new(handle_equipment),
                 Box::new(handle_examine),
+                Box::new(handle_give),
                 Box::new(handle_inventory),
                 Box::new(handle_look),
                 Box::new(handle_map),
@@ -83,15 +105,20 @@ pub fn parse_command(
                 Box::new(handle_open),
                 Box::new(handle_place),
                 Box::new(handle_quit),
+                Box::new(handle_reload),
+                Box::new(handle_remove),
                 Box::new(handle_retreat),
                 Box::new(handle_roll),
                 Box::new(handle_say),
                 Box::new(handle_scan),
                 Box::new(handle_sit),
+                Box::new(handle_skills),
                 Box::new(handle_stand),
                 Box::new(handle_take),
                 Box::new(handle_time),
+                Box::new(handle_wear),
                 Box::new(handle_who),
+                Box::new(handle_wield),
                 Box::new(handle_yell),
                 // Attack is last because the commands are a catch-all and
                 // defined via ron files.
@@ -107,6 +134,16 @@ pub fn parse_command(
             Some(Ok(command)) => {
                 debug!("Parsed command: {:?}", command);
 
+                let flags = applied_conditions
+                    .map(|applied| applied.flags(&conditions))
+                    .unwrap_or_default();
+
+                if let Some(flag) = restricting_flag(&command, &flags) {
+                    outbox.send_text(client.id, flag.message());
+
+                    continue;
+                }
+
                 commands.send(ParsedCommand {
                     from: client.id,
                     command,
@@ -10,17 +10,25 @@ use thiserror::Error;
 use crate::{
     input::events::{Command, ParseError, ParsedCommand},
     interact::components::{Interaction, Interactions},
-    items::components::{Inventory, Item, Surface},
+    items::{
+        components::{Dropped, Inventory, Item, Stack},
+        utils::{self, find_path, move_items, split_count, split_path, HolderQuery, StackQuery},
+    },
     player::components::{Client, Online},
     spatial::components::Tile,
-    visual::components::Depiction,
+    visual::{components::Depiction, utils::counted_name_list},
 };
 
 static REGEX: OnceLock<Regex> = OnceLock::new();
 
 pub fn handle_place(content: &str) -> Result<Command, ParseError> {
+    // Without "on", "against" or "in" there's no telling where a longer
+    // object ends, so it's taken to be a single word and maybe a count.
     let regex = REGEX.get_or_init(|| {
-        Regex::new(r"^place( (?P<object>.*?))?( (on|against|in))?( (?P<target>.*))?$").unwrap()
+        Regex::new(
+            r"^place( (?P<object>.*?) (on|against|in) (?P<target>.+)| (?P<word>(\d+ )?\S+)( (?P<rest>.+))?)?$",
+        )
+        .unwrap()
     });
 
     match regex.captures(content) {
@@ -28,11 +36,13 @@ pub fn handle_place(content: &str) -> Result<Command, ParseError> {
         Some(captures) => {
             let object = captures
                 .name("object")
+                .or_else(|| captures.name("word"))
                 .map(|m| m.as_str().trim())
                 .ok_or(ParseError::InvalidArguments("Place what?".into()))?;
 
             let target = captures
                 .name("target")
+                .or_else(|| captures.name("rest"))
                 .map(|m| m.as_str().trim())
                 .ok_or(ParseError::InvalidArguments("Place where?".into()))?;
 
@@ -53,7 +63,7 @@ pub struct ItemQuery {
     item: &'static Item,
     depiction: &'static Depiction,
     interactions: Option<&'static Interactions>,
-    children: Option<&'static Children>,
+    stack: Option<&'static Stack>,
 }
 
 #[sysfail(log)]
@@ -65,7 +75,8 @@ pub fn place(
     inventories: Query<InventoryQuery>,
     tiles: Query<&Children, With<Tile>>,
     items: Query<ItemQuery>,
-    surfaces: Query<&Surface>,
+    holders: Query<HolderQuery>,
+    stacks: Query<StackQuery>,
 ) -> Result<(), anyhow::Error> {
     for command in commands.iter() {
         if let Command::Place((object, target)) = &command.command {
@@ -81,8 +92,10 @@ pub fn place(
                 .find_map(|child| inventories.get(*child).ok())
                 .context("Inventory not found")?;
 
-            let object = match get_object(object, &inventory, &items) {
-                Ok(object) => object,
+            let (count, object) = split_count(object);
+
+            let objects = match get_objects(object, &inventory, &items) {
+                Ok(objects) => objects,
                 Err(err) => {
                     outbox.send_text(client.id, err.to_string());
 
@@ -90,8 +103,8 @@ pub fn place(
                 }
             };
 
-            let target = match get_target(target, siblings, &items) {
-                Ok(target) => target,
+            let target_path = match get_target(target, siblings, &inventory, &holders) {
+                Ok(target_path) => target_path,
                 Err(err) => {
                     outbox.send_text(client.id, err.to_string());
 
@@ -99,12 +112,18 @@ pub fn place(
                 }
             };
 
-            match place_object(
+            // Only what's left out in the world needs saving apart from its holder.
+            let on_ground = siblings.contains(&target_path[0]);
+
+            match place_objects(
                 &mut bevy,
-                target,
-                object,
-                &surfaces.get(target).ok(),
+                &target_path,
+                &objects,
+                count,
+                on_ground,
+                &holders,
                 &items,
+                &stacks,
             ) {
                 Ok(msg) => outbox.send_text(client.id, msg),
                 Err(err) => outbox.send_text(client.id, err.to_string()),
@@ -123,27 +142,33 @@ enum ObjectError {
     NotPlacable(String),
 }
 
-fn get_object(
+/// Every carried item matching the target, in the order they're carried.
+fn get_objects(
     target: &str,
     inventory: &InventoryQueryItem,
     items: &Query<ItemQuery>,
-) -> Result<Entity, ObjectError> {
-    let object = inventory
+) -> Result<Vec<Entity>, ObjectError> {
+    let objects = inventory
         .children
         .iter()
         .flat_map(|children| children.iter())
         .filter_map(|child| items.get(*child).ok())
-        .find(|item| item.depiction.matches_query(&item.entity, target))
-        .ok_or(ObjectError::NotFound(target.into()))?;
+        .filter(|item| item.depiction.matches_query(&item.entity, target))
+        .collect::<Vec<_>>();
+
+    if objects.is_empty() {
+        return Err(ObjectError::NotFound(target.into()));
+    }
 
-    if !object
-        .interactions
-        .map_or(false, |i| i.0.contains(&Interaction::Place))
-    {
+    if let Some(object) = objects.iter().find(|object| {
+        !object
+            .interactions
+            .map_or(false, |i| i.0.contains(&Interaction::Place))
+    }) {
         return Err(ObjectError::NotPlacable(object.depiction.name.clone()));
     }
 
-    Ok(object.entity)
+    Ok(objects.iter().map(|object| object.entity).collect())
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -152,18 +177,22 @@ enum TargetError {
     NotFound(String),
 }
 
+/// The path to the target, outermost first. It can be nearby or carried.
 fn get_target(
     target: &str,
-    children: &Children,
-    items: &Query<ItemQuery>,
-) -> Result<Entity, TargetError> {
-    let target = children
-        .iter()
-        .filter_map(|child| items.get(*child).ok())
-        .find(|item| item.depiction.matches_query(&item.entity, target))
-        .ok_or(TargetError::NotFound(target.into()))?;
+    siblings: &Children,
+    inventory: &InventoryQueryItem,
+    holders: &Query<HolderQuery>,
+) -> Result<Vec<Entity>, TargetError> {
+    let roots = siblings.iter().chain(
+        inventory
+            .children
+            .iter()
+            .flat_map(|children| children.iter()),
+    );
 
-    Ok(target.entity)
+    find_path(&split_path(target), roots.copied(), holders)
+        .ok_or(TargetError::NotFound(target.into()))
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -172,42 +201,90 @@ enum PlaceError {
     AtCapacity(String),
     #[error("You can't place the {0} on the {1}.")]
     NotPlacable(String, String),
+    #[error("You can't place the {0} inside itself.")]
+    InsideItself(String),
 }
 
-fn place_object(
+#[allow(clippy::too_many_arguments)]
+fn place_objects(
     bevy: &mut Commands,
-    target: Entity,
-    object: Entity,
-    surface: &Option<&Surface>,
+    target_path: &[Entity],
+    objects: &[Entity],
+    count: Option<u32>,
+    on_ground: bool,
+    holders: &Query<HolderQuery>,
     items: &Query<ItemQuery>,
+    stacks: &Query<StackQuery>,
 ) -> Result<String, anyhow::Error> {
-    let Some(surface) = surface else {
+    let target = holders.get(*target_path.last().context("Target not found")?)?;
+    let object = items.get(*objects.first().context("Object not found")?)?;
+    let count = count.unwrap_or(1);
+
+    // The items the count reaches into, and how many of them that is.
+    let mut moving = Vec::new();
+    let mut quantity = 0;
+
+    for item in objects.iter().filter_map(|object| items.get(*object).ok()) {
+        if quantity >= count {
+            break;
+        }
+
+        quantity += utils::quantity(item.stack).min(count - quantity);
+        moving.push(item);
+    }
+
+    let present = target
+        .children
+        .map_or_else(Vec::new, |children| children.to_vec());
+
+    let Some(capacity) = target.capacity() else {
         Err(PlaceError::NotPlacable(
-            items.get(object)?.depiction.name.clone(),
-            items.get(target)?.depiction.name.clone(),
+            object.depiction.name.clone(),
+            target.depiction.name.clone(),
         ))?
     };
 
-    let target = items.get(target)?;
-    let object = items.get(object)?;
+    if moving.iter().any(|item| target_path.contains(&item.entity)) {
+        Err(PlaceError::InsideItself(object.depiction.name.clone()))?
+    }
 
-    if target.children.map_or(false, |children| {
-        children
-            .iter()
-            .filter_map(|child| items.get(*child).ok())
-            .map(|item| item.item.size.value())
-            .sum::<u8>()
-            + object.item.size.value()
-            > surface.capacity
-    }) {
+    // Merging into stacks already there takes up no more room.
+    let room = present
+        .iter()
+        .filter_map(|entity| items.get(*entity).ok())
+        .filter(|item| item.depiction.name == object.depiction.name)
+        .filter_map(|item| item.stack.map(Stack::room))
+        .sum::<u32>();
+
+    let needed = if object.stack.is_some() && room >= quantity {
+        0
+    } else {
+        moving.iter().map(|item| item.item.size.value()).sum()
+    };
+
+    if target.used(holders) + needed > capacity {
         Err(PlaceError::AtCapacity(target.depiction.name.clone()))?
     }
 
-    bevy.entity(object.entity).set_parent(target.entity);
+    let moved = move_items(bevy, objects, Some(count), target.entity, &present, stacks);
+
+    if on_ground {
+        for entity in &moved.arrived {
+            bevy.entity(*entity).insert(Dropped::placed());
+        }
+    }
+
+    let object_names = if quantity > 1 {
+        counted_name_list(&moved.names, None, false)
+    } else {
+        format!("the {}", object.depiction.name)
+    };
 
     Ok(format!(
-        "You place the {} {} the {}.",
-        object.depiction.name, surface.kind, target.depiction.name,
+        "You place {} {} the {}.",
+        object_names,
+        target.preposition(),
+        target.depiction.name,
     ))
 }
 
@@ -234,6 +311,33 @@ mod tests {
             Ok(Command::Place(("rock".into(), "pile".into())))
         );
 
+        let count = handle_place("place 2 coins in pouch");
+        assert_eq!(
+            count,
+            Ok(Command::Place(("2 coins".into(), "pouch".into())))
+        );
+
+        let no_preposition = handle_place("place rock pile");
+        assert_eq!(
+            no_preposition,
+            Ok(Command::Place(("rock".into(), "pile".into())))
+        );
+
+        let count_no_preposition = handle_place("place 2 coins pouch");
+        assert_eq!(
+            count_no_preposition,
+            Ok(Command::Place(("2 coins".into(), "pouch".into())))
+        );
+
+        let long_object = handle_place("place dinner plate on wooden table");
+        assert_eq!(
+            long_object,
+            Ok(Command::Place((
+                "dinner plate".into(),
+                "wooden table".into()
+            )))
+        );
+
         let no_object = handle_place("place");
         assert_eq!(
             no_object,
@@ -282,6 +386,117 @@ mod tests {
         assert!(app.world.get::<Children>(table).unwrap().contains(&plate));
     }
 
+    #[test]
+    fn into_carried_container() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, place);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let pouch = ItemBuilder::new()
+            .name("pouch")
+            .is_container(1)
+            .interactions(vec![Interaction::Place])
+            .build(&mut app);
+        let coin = ItemBuilder::new()
+            .name("coin")
+            .interactions(vec![Interaction::Place])
+            .build(&mut app);
+
+        let (_, client_id, inventory) = PlayerBuilder::new()
+            .has_inventory()
+            .tile(tile)
+            .build(&mut app);
+
+        app.world
+            .entity_mut(inventory.unwrap())
+            .push_children(&[pouch, coin]);
+
+        send_message(&mut app, client_id, "place coin in pouch");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(content, "You place the coin in the pouch.");
+        assert!(app.world.get::<Children>(pouch).unwrap().contains(&coin));
+        assert!(app.world.get::<Dropped>(coin).is_none());
+    }
+
+    #[test]
+    fn merges_into_full_container() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, place);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let pouch = ItemBuilder::new()
+            .name("pouch")
+            .is_container(1)
+            .build(&mut app);
+        let in_pouch = ItemBuilder::new()
+            .name("coin")
+            .interactions(vec![Interaction::Place])
+            .stack(1, 10)
+            .build(&mut app);
+        let coins = ItemBuilder::new()
+            .name("coin")
+            .interactions(vec![Interaction::Place])
+            .stack(4, 10)
+            .build(&mut app);
+
+        app.world.entity_mut(pouch).add_child(in_pouch);
+
+        let (_, client_id, inventory) = PlayerBuilder::new()
+            .has_inventory()
+            .tile(tile)
+            .build(&mut app);
+
+        app.world
+            .entity_mut(inventory.unwrap())
+            .push_children(&[pouch, coins]);
+
+        send_message(&mut app, client_id, "place 2 coins in pouch");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(content, "You place 2 coins in the pouch.");
+        assert_eq!(app.world.get::<Stack>(in_pouch).unwrap().quantity, 3);
+        assert_eq!(app.world.get::<Stack>(coins).unwrap().quantity, 2);
+        assert_eq!(app.world.get::<Children>(pouch).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn not_inside_itself() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, place);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let pouch = ItemBuilder::new()
+            .name("pouch")
+            .is_container(1)
+            .interactions(vec![Interaction::Place])
+            .build(&mut app);
+
+        let (_, client_id, inventory) = PlayerBuilder::new()
+            .has_inventory()
+            .tile(tile)
+            .build(&mut app);
+
+        app.world.entity_mut(inventory.unwrap()).add_child(pouch);
+
+        send_message(&mut app, client_id, "place pouch in pouch");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(content, "You can't place the pouch inside itself.");
+    }
+
     #[test]
     fn object_not_found() {
         let mut app = AppBuilder::new().build();
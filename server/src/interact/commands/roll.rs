@@ -11,6 +11,7 @@ use crate::{
     input::events::{Command, ParseError, ParsedCommand},
     player::components::{Character, Client, Online},
     spatial::components::Tile,
+    world::resources::GameRng,
 };
 
 static REGEX: OnceLock<Regex> = OnceLock::new();
@@ -38,6 +39,7 @@ pub fn roll(
     mut outbox: EventWriter<Outbox>,
     players: Query<(&Client, &Character, &Parent), With<Online>>,
     tiles: Query<&Children, With<Tile>>,
+    mut rng: ResMut<GameRng>,
 ) -> Result<(), anyhow::Error> {
     for command in commands.iter() {
         if let Command::Roll(roll) = &command.command {
@@ -56,7 +58,7 @@ pub fn roll(
                 }
             };
 
-            match roller.roll() {
+            match roller.roll_with(&mut *rng) {
                 Ok(result) => {
                     siblings
                         .iter()
@@ -10,17 +10,21 @@ use thiserror::Error;
 use crate::{
     input::events::{Command, ParseError, ParsedCommand},
     interact::components::{Interaction, Interactions},
-    items::components::{Inventory, Item, Surface},
-    player::components::{Client, Online},
+    items::{
+        components::{Corpse, Dropped, Inventory, Item},
+        utils::{find_path, move_items, split_count, split_path, HolderQuery, StackQuery},
+    },
+    party::components::Party,
+    player::components::{Character, Client, Online},
     spatial::components::Tile,
-    visual::{components::Depiction, utils::name_list},
+    visual::{components::Depiction, utils::counted_name_list},
 };
 
 static REGEX: OnceLock<Regex> = OnceLock::new();
 
 pub fn handle_take(content: &str) -> Result<Command, ParseError> {
     let regex = REGEX.get_or_init(|| {
-        Regex::new(r"^(take|get)( (?P<all>all))?( (?P<target>.*?))?( (from))?( (?P<source>.*))?$")
+        Regex::new(r"^(take|get)( (?P<all>all))?( (?P<target>.*?))?( from (?P<source>.*))?$")
             .unwrap()
     });
 
@@ -48,52 +52,79 @@ pub struct ItemQuery {
     entity: Entity,
     depiction: &'static Depiction,
     interactions: Option<&'static Interactions>,
-    children: Option<&'static Children>,
     with_item: With<Item>,
 }
 
-#[derive(WorldQuery)]
-pub struct SurfaceQuery {
-    entity: Entity,
-    surface: &'static Surface,
-}
-
 #[sysfail(log)]
 pub fn take(
     mut bevy: Commands,
     mut commands: EventReader<ParsedCommand>,
     mut outbox: EventWriter<Outbox>,
-    mut players: Query<(&Client, &Parent, &Children), With<Online>>,
-    inventories: Query<Entity, With<Inventory>>,
+    mut players: Query<(&Client, &Character, &Parent, &Children), With<Online>>,
+    inventories: Query<(Entity, Option<&Children>), With<Inventory>>,
     tiles: Query<&Children, With<Tile>>,
     items: Query<ItemQuery>,
-    surfaces: Query<SurfaceQuery>,
+    holders: Query<HolderQuery>,
+    stacks: Query<StackQuery>,
+    corpses: Query<(&Depiction, &Corpse)>,
+    parties: Query<&Party>,
 ) -> Result<(), anyhow::Error> {
     for command in commands.iter() {
         if let Command::Take((target, all, source)) = &command.command {
-            let (client, tile, children) = players
+            let (client, character, tile, children) = players
                 .iter_mut()
-                .find(|(c, _, _)| c.id == command.from)
+                .find(|(c, _, _, _)| c.id == command.from)
                 .context("Player not found")?;
 
             let siblings = tiles.get(tile.get())?;
 
-            let inventory = children
+            let (inventory, carried) = children
                 .iter()
                 .find_map(|child| inventories.get(*child).ok())
                 .context("Inventory not found")?;
 
-            let to_search = get_searchable_items(siblings, source, &surfaces, &items);
-            let mut items_found = search_items(target, &to_search, &items);
+            // What's being taken from, outermost first. It can be nearby or carried.
+            let source_path = source.as_ref().map(|source| {
+                let roots = siblings
+                    .iter()
+                    .chain(carried.iter().flat_map(|carried| carried.iter()));
+
+                find_path(&split_path(source), roots.copied(), &holders).unwrap_or_default()
+            });
+
+            let corpse = source_path
+                .iter()
+                .flatten()
+                .find_map(|entity| corpses.get(*entity).ok());
+
+            if let Some((depiction, corpse)) = corpse {
+                if !corpse.can_loot(character.id, parties.iter()) {
+                    outbox.send_text(
+                        client.id,
+                        TakeError::LootRights(depiction.short_name.clone()).to_string(),
+                    );
+
+                    continue;
+                }
+            }
+
+            let (count, target) = split_count(target);
+            let to_search = get_searchable_items(siblings, &source_path, &holders, &items);
+            let items_found = search_items(target, &to_search, &items);
+
+            let carried = carried.map_or_else(Vec::new, |carried| carried.to_vec());
 
             match take_item(
                 &mut bevy,
                 target,
+                count,
                 all,
                 source,
-                &mut items_found,
+                &items_found,
                 &items,
+                &stacks,
                 inventory,
+                &carried,
             ) {
                 Ok(msg) => outbox.send_text(client.id, msg),
                 Err(err) => outbox.send_text(client.id, err.to_string()),
@@ -106,23 +137,20 @@ pub fn take(
 
 fn get_searchable_items(
     siblings: &Children,
-    source: &Option<String>,
-    surfaces: &Query<SurfaceQuery>,
+    source_path: &Option<Vec<Entity>>,
+    holders: &Query<HolderQuery>,
     items: &Query<ItemQuery>,
 ) -> Vec<Entity> {
-    if let Some(source) = source {
-        // Find the specific surface item by source and return its children.
-        siblings
-            .iter()
-            .filter_map(|sibling| items.get(*sibling).ok())
-            .find(|item| {
-                surfaces.get(item.entity).is_ok()
-                    && item.depiction.matches_query(&item.entity, source)
-            })
-            .and_then(|item| item.children)
+    if let Some(source_path) = source_path {
+        // Whatever's in or on the end of the path, if it can hold anything.
+        source_path
+            .last()
+            .and_then(|source| holders.get(*source).ok())
+            .filter(|source| source.capacity().is_some())
+            .and_then(|source| source.children)
             .map_or_else(Vec::new, |children| children.iter().copied().collect())
     } else {
-        // Return all entities in siblings.j
+        // Return all entities in siblings.
         siblings
             .iter()
             .filter_map(|sibling| items.get(*sibling).ok())
@@ -146,16 +174,22 @@ enum TakeError {
     NotFound(String),
     #[error("You can't take that.")]
     NotTakeable(#[from] ValidateError),
+    #[error("The {0} isn't yours to loot.")]
+    LootRights(String),
 }
 
+#[allow(clippy::too_many_arguments)]
 fn take_item(
     bevy: &mut Commands,
     target: &str,
+    count: Option<u32>,
     all: &bool,
     source: &Option<String>,
-    items_found: &mut Vec<Entity>,
+    items_found: &[Entity],
     items: &Query<ItemQuery>,
+    stacks: &Query<StackQuery>,
     inventory: Entity,
+    carried: &[Entity],
 ) -> Result<String, anyhow::Error> {
     if items_found.is_empty() {
         let target = source.as_deref().unwrap_or(target);
@@ -164,20 +198,14 @@ fn take_item(
 
     validate_items(items_found, items)?;
 
-    if !*all {
-        items_found.truncate(1);
-    }
+    let count = if *all { None } else { Some(count.unwrap_or(1)) };
+    let moved = move_items(bevy, items_found, count, inventory, carried, stacks);
 
-    for &item in items_found.iter() {
-        bevy.entity(item).set_parent(inventory);
+    for &item in &moved.arrived {
+        bevy.entity(item).remove::<Dropped>();
     }
 
-    let item_names = items_found
-        .iter()
-        .filter_map(|&item| items.get(item).ok().map(|item| item.depiction.name.clone()))
-        .collect::<Vec<_>>();
-
-    let item_names = name_list(&item_names, None, true);
+    let item_names = counted_name_list(&moved.names, None, true);
 
     Ok(format!("You take {item_names}."))
 }
@@ -206,7 +234,10 @@ fn validate_items(items_found: &[Entity], items: &Query<ItemQuery>) -> Result<()
 #[cfg(test)]
 mod tests {
     use crate::{
-        items::components::SurfaceKind,
+        items::{
+            bundles::CorpseBundle,
+            components::{Stack, SurfaceKind},
+        },
         test::{
             app_builder::AppBuilder,
             item_builder::ItemBuilder,
@@ -232,6 +263,16 @@ mod tests {
             Ok(Command::Take(("rock".into(), false, Some("pile".into()))))
         );
 
+        let count = handle_take("take 5 arrows from quiver");
+        assert_eq!(
+            count,
+            Ok(Command::Take((
+                "5 arrows".into(),
+                false,
+                Some("quiver".into())
+            )))
+        );
+
         let no_object = handle_take("take");
         assert_eq!(
             no_object,
@@ -398,6 +439,199 @@ mod tests {
             .contains(&plate),);
     }
 
+    #[test]
+    fn from_nested_container() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, take);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let backpack = ItemBuilder::new()
+            .name("backpack")
+            .is_container(10)
+            .tile(tile)
+            .build(&mut app);
+
+        let pouch = ItemBuilder::new()
+            .name("pouch")
+            .is_container(2)
+            .build(&mut app);
+
+        let coin = ItemBuilder::new()
+            .name("coin")
+            .interactions(vec![Interaction::Take])
+            .build(&mut app);
+
+        app.world.entity_mut(backpack).add_child(pouch);
+        app.world.entity_mut(pouch).add_child(coin);
+
+        let (_, client_id, inventory) = PlayerBuilder::new()
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        send_message(&mut app, client_id, "take coin from pouch in backpack");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(content, "You take a coin.");
+        assert!(app.world.get::<Children>(pouch).is_none());
+        assert!(app
+            .world
+            .get::<Children>(inventory.unwrap())
+            .unwrap()
+            .contains(&coin));
+    }
+
+    #[test]
+    fn count_from_a_stack() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, take);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let arrows = ItemBuilder::new()
+            .name("arrow")
+            .interactions(vec![Interaction::Take])
+            .stack(10, 20)
+            .tile(tile)
+            .build(&mut app);
+
+        let (_, client_id, inventory) = PlayerBuilder::new()
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        send_message(&mut app, client_id, "take 3 arrows");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(content, "You take 3 arrows.");
+        assert_eq!(app.world.get::<Stack>(arrows).unwrap().quantity, 7);
+
+        let carried = app.world.get::<Children>(inventory.unwrap()).unwrap()[0];
+
+        assert_ne!(carried, arrows);
+        assert_eq!(app.world.get::<Stack>(carried).unwrap().quantity, 3);
+        assert_eq!(
+            app.world.get::<Depiction>(carried).unwrap().name,
+            "arrow".to_string()
+        );
+    }
+
+    #[test]
+    fn merges_into_carried_stack() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, take);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let arrows = ItemBuilder::new()
+            .name("arrow")
+            .interactions(vec![Interaction::Take])
+            .stack(5, 20)
+            .tile(tile)
+            .build(&mut app);
+
+        let carried = ItemBuilder::new()
+            .name("arrow")
+            .interactions(vec![Interaction::Take])
+            .stack(2, 20)
+            .build(&mut app);
+
+        let (_, client_id, inventory) = PlayerBuilder::new()
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        app.world.entity_mut(inventory.unwrap()).add_child(carried);
+
+        send_message(&mut app, client_id, "take all arrows");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(content, "You take 5 arrows.");
+        assert!(app.world.get_entity(arrows).is_none());
+        assert_eq!(app.world.get::<Stack>(carried).unwrap().quantity, 7);
+        assert_eq!(
+            app.world.get::<Children>(inventory.unwrap()).unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn corpse_loot_rights() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, take);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (killer, _, _) = PlayerBuilder::new()
+            .id(1)
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        let (member, member_id, member_inventory) = PlayerBuilder::new()
+            .id(2)
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        let (_, stranger_id, stranger_inventory) = PlayerBuilder::new()
+            .id(3)
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        let mut party = Party::new(app.world.get::<Character>(killer).unwrap());
+        party.join(app.world.get::<Character>(member).unwrap());
+        app.world.spawn(party);
+
+        let corpse = app
+            .world
+            .spawn(CorpseBundle::hostile("Goat", "goat", Some(1), 300.0))
+            .set_parent(tile)
+            .id();
+
+        let pelt = ItemBuilder::new()
+            .name("pelt")
+            .interactions(vec![Interaction::Take])
+            .build(&mut app);
+
+        app.world.entity_mut(corpse).add_child(pelt);
+
+        send_message(&mut app, stranger_id, "take pelt from corpse");
+        app.update();
+
+        let content = get_message_content(&mut app, stranger_id).unwrap();
+
+        assert_eq!(content, "The goat corpse isn't yours to loot.");
+        assert!(app
+            .world
+            .get::<Children>(stranger_inventory.unwrap())
+            .is_none());
+
+        send_message(&mut app, member_id, "take pelt from corpse");
+        app.update();
+
+        let content = get_message_content(&mut app, member_id).unwrap();
+
+        assert_eq!(content, "You take a pelt.");
+        assert!(app
+            .world
+            .get::<Children>(member_inventory.unwrap())
+            .unwrap()
+            .contains(&pelt));
+    }
+
     #[test]
     fn not_found() {
         let mut app = AppBuilder::new().build();
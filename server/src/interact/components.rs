@@ -3,11 +3,11 @@ use std::fmt::{Display, Formatter};
 use bevy::prelude::*;
 use bevy_proto::prelude::*;
 
-#[derive(Component, Schematic, Reflect)]
+#[derive(Component, Schematic, Reflect, Clone)]
 #[reflect(Schematic)]
 pub struct Interactions(pub Vec<Interaction>);
 
-#[derive(PartialEq, Reflect, Debug)]
+#[derive(Clone, PartialEq, Reflect, Debug)]
 pub enum Interaction {
     Attack,
     Place,
@@ -1,9 +1,9 @@
 use bevy::prelude::*;
 use bevy_proto::prelude::*;
 
-use crate::visual::components::Depiction;
+use crate::{values::CORPSE_CAPACITY, visual::components::Depiction};
 
-use super::components::Item;
+use super::components::{Corpse, Item, LootRights, Size, Surface, SurfaceKind};
 
 #[derive(Bundle, Schematic, Reflect)]
 #[reflect(Schematic)]
@@ -11,3 +11,60 @@ pub struct ItemBundle {
     pub item: Item,
     pub depiction: Depiction,
 }
+
+#[derive(Bundle)]
+pub struct CorpseBundle {
+    pub item: Item,
+    pub depiction: Depiction,
+    pub surface: Surface,
+    pub corpse: Corpse,
+}
+
+impl CorpseBundle {
+    /// A hostile's corpse, reserved for whoever killed it until it decays.
+    pub fn hostile(name: &str, short_name: &str, killer: Option<i64>, decay: f32) -> Self {
+        Self {
+            item: Item { size: Size::Large },
+            depiction: Depiction {
+                name: format!("{name} Corpse"),
+                short_name: format!("{short_name} corpse"),
+                description: format!(
+                    "The lifeless body of a {short_name} lies crumpled on the ground."
+                ),
+                tags: vec!["corpse".into(), "body".into(), short_name.into()],
+                visible: true,
+            },
+            surface: Surface {
+                kind: SurfaceKind::Interior,
+                capacity: CORPSE_CAPACITY,
+            },
+            corpse: Corpse {
+                rights: killer.map_or(LootRights::Anyone, LootRights::Killer),
+                decay: Timer::from_seconds(decay, TimerMode::Once),
+            },
+        }
+    }
+
+    /// A player's corpse, holding everything they carried. Only they may
+    /// loot it, for as long as it lasts.
+    pub fn player(name: &str, owner: i64, decay: f32) -> Self {
+        Self {
+            item: Item { size: Size::Large },
+            depiction: Depiction {
+                name: format!("Corpse of {name}"),
+                short_name: format!("{}'s corpse", name.to_lowercase()),
+                description: format!("The lifeless body of {name} lies crumpled on the ground."),
+                tags: vec!["corpse".into(), "body".into(), name.to_lowercase()],
+                visible: true,
+            },
+            surface: Surface {
+                kind: SurfaceKind::Interior,
+                capacity: u8::MAX,
+            },
+            corpse: Corpse {
+                rights: LootRights::Owner(owner),
+                decay: Timer::from_seconds(decay, TimerMode::Once),
+            },
+        }
+    }
+}
@@ -9,10 +9,13 @@ use thiserror::Error;
 
 use crate::{
     input::events::{Command, ParseError, ParsedCommand},
-    items::components::{Inventory, Item},
+    items::{
+        components::{Dropped, Inventory, Item},
+        utils::{find_path, move_items, split_count, split_path, HolderQuery, StackQuery},
+    },
     player::components::{Client, Online},
     spatial::components::Tile,
-    visual::{components::Depiction, utils::name_list},
+    visual::{components::Depiction, utils::counted_name_list},
 };
 
 static REGEX: OnceLock<Regex> = OnceLock::new();
@@ -50,8 +53,10 @@ pub fn drop(
     mut outbox: EventWriter<Outbox>,
     mut players: Query<(&Client, &Parent, &Children), With<Online>>,
     inventories: Query<Option<&Children>, With<Inventory>>,
-    tiles: Query<Entity, With<Tile>>,
+    tiles: Query<(Entity, Option<&Children>), With<Tile>>,
     items: Query<ItemQuery>,
+    holders: Query<HolderQuery>,
+    stacks: Query<StackQuery>,
 ) -> Result<(), anyhow::Error> {
     for command in commands.iter() {
         if let Command::Drop((target, all)) = &command.command {
@@ -60,16 +65,27 @@ pub fn drop(
                 .find(|(c, _, _)| c.id == command.from)
                 .context("Player not found")?;
 
-            let tile = tiles.get(tile.get())?;
+            let (tile, on_tile) = tiles.get(tile.get())?;
+            let on_tile = on_tile.map_or_else(Vec::new, |children| children.to_vec());
 
             let items_in_inventory = children
                 .iter()
                 .find_map(|child| inventories.get(*child).ok())
                 .context("Inventory not found")?;
 
-            let mut items_found = search_items(target, &items, &items_in_inventory);
-
-            match drop_item(&mut bevy, target, all, &mut items_found, &items, &tile) {
+            let (count, target) = split_count(target);
+            let items_found = search_items(target, &items, &items_in_inventory, &holders);
+
+            match drop_item(
+                &mut bevy,
+                target,
+                count,
+                all,
+                &items_found,
+                &stacks,
+                tile,
+                &on_tile,
+            ) {
                 Ok(msg) => outbox.send_text(client.id, msg),
                 Err(err) => outbox.send_text(client.id, err.to_string()),
             }
@@ -79,16 +95,36 @@ pub fn drop(
     Ok(())
 }
 
+/// Finds the target among what's carried, or inside something carried when
+/// given a path like `coin in pouch`.
 fn search_items(
     target: &str,
     items: &Query<ItemQuery>,
     items_in_inventory: &Option<&Children>,
+    holders: &Query<HolderQuery>,
 ) -> Vec<Entity> {
-    items_in_inventory
+    let mut path = split_path(target);
+    let name = path.pop().unwrap_or(target);
+
+    let carried = items_in_inventory
         .iter()
         .flat_map(|children| children.iter())
-        .filter_map(|sibling| items.get(*sibling).ok())
-        .filter(|item| item.depiction.matches_query(&item.entity, target))
+        .copied();
+
+    let to_search = if path.is_empty() {
+        carried.collect()
+    } else {
+        find_path(&path, carried, holders)
+            .and_then(|path| path.last().copied())
+            .and_then(|holder| holders.get(holder).ok())
+            .and_then(|holder| holder.children)
+            .map_or_else(Vec::new, |children| children.iter().copied().collect())
+    };
+
+    to_search
+        .iter()
+        .filter_map(|item| items.get(*item).ok())
+        .filter(|item| item.depiction.matches_query(&item.entity, name))
         .map(|item| item.entity)
         .collect()
 }
@@ -99,47 +135,44 @@ enum DropError {
     NotFound(String),
 }
 
+#[allow(clippy::too_many_arguments)]
 fn drop_item(
     bevy: &mut Commands,
     target: &str,
+    count: Option<u32>,
     all: &bool,
-    items_found: &mut Vec<Entity>,
-    items: &Query<ItemQuery>,
-    tile: &Entity,
+    items_found: &[Entity],
+    stacks: &Query<StackQuery>,
+    tile: Entity,
+    on_tile: &[Entity],
 ) -> Result<String, DropError> {
     if items_found.is_empty() {
         return Err(DropError::NotFound(target.to_string()));
     }
 
-    if !*all {
-        items_found.truncate(1);
+    let count = if *all { None } else { Some(count.unwrap_or(1)) };
+    let moved = move_items(bevy, items_found, count, tile, on_tile, stacks);
+
+    for entity in &moved.arrived {
+        bevy.entity(*entity).insert(Dropped::loose());
     }
 
-    items_found.iter().for_each(|entity| {
-        bevy.entity(*entity).set_parent(*tile);
-    });
-
-    let item_names = name_list(
-        &items_found
-            .iter()
-            .filter_map(|entity| items.get(*entity).ok())
-            .map(|item| item.depiction.name.clone())
-            .collect::<Vec<String>>(),
-        None,
-        true,
-    );
+    let item_names = counted_name_list(&moved.names, None, true);
 
     Ok(format!("You drop {item_names}."))
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::test::{
-        app_builder::AppBuilder,
-        item_builder::ItemBuilder,
-        player_builder::PlayerBuilder,
-        tile_builder::{TileBuilder, ZoneBuilder},
-        utils::{get_message_content, send_message},
+    use crate::{
+        items::components::Stack,
+        test::{
+            app_builder::AppBuilder,
+            item_builder::ItemBuilder,
+            player_builder::PlayerBuilder,
+            tile_builder::{TileBuilder, ZoneBuilder},
+            utils::{get_message_content, send_message},
+        },
     };
 
     use super::*;
@@ -184,6 +217,7 @@ mod tests {
         let content = get_message_content(&mut app, client_id).unwrap();
 
         assert_eq!(content, "You drop a stick.");
+        assert!(app.world.get::<Dropped>(stick).is_some());
         assert_eq!(
             app.world
                 .get::<Children>(inventory.unwrap())
@@ -193,6 +227,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn from_container() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, drop);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let pouch = ItemBuilder::new()
+            .name("pouch")
+            .is_container(2)
+            .build(&mut app);
+        let coin = ItemBuilder::new().name("coin").build(&mut app);
+
+        app.world.entity_mut(pouch).add_child(coin);
+
+        let (_, client_id, inventory) = PlayerBuilder::new()
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        app.world.entity_mut(inventory.unwrap()).add_child(pouch);
+
+        send_message(&mut app, client_id, "drop coin in pouch");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(content, "You drop a coin.");
+        assert!(app.world.get::<Children>(tile).unwrap().contains(&coin));
+    }
+
+    #[test]
+    fn count_from_a_stack() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, drop);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let coins = ItemBuilder::new().name("coin").stack(5, 50).build(&mut app);
+        let on_ground = ItemBuilder::new()
+            .name("coin")
+            .stack(49, 50)
+            .tile(tile)
+            .build(&mut app);
+
+        let (_, client_id, inventory) = PlayerBuilder::new()
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        app.world.entity_mut(inventory.unwrap()).add_child(coins);
+
+        send_message(&mut app, client_id, "drop 2.coin");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(content, "You drop 2 coins.");
+        assert_eq!(app.world.get::<Stack>(coins).unwrap().quantity, 3);
+        assert_eq!(app.world.get::<Stack>(on_ground).unwrap().quantity, 50);
+
+        // What didn't fit on the full stack starts a new one.
+        let dropped = *app
+            .world
+            .get::<Children>(tile)
+            .unwrap()
+            .iter()
+            .find(|child| **child != on_ground && app.world.get::<Stack>(**child).is_some())
+            .unwrap();
+
+        assert_eq!(app.world.get::<Stack>(dropped).unwrap().quantity, 1);
+        assert!(app.world.get::<Dropped>(dropped).is_some());
+    }
+
     #[test]
     fn by_tag() {
         let mut app = AppBuilder::new().build();
@@ -0,0 +1,341 @@
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use bevy::{ecs::query::WorldQuery, prelude::*};
+use bevy_mod_sysfail::sysfail;
+use bevy_nest::prelude::*;
+use regex::Regex;
+use thiserror::Error;
+
+use crate::{
+    input::events::{Command, ParseError, ParsedCommand},
+    items::components::{Equipment, Equippable, Inventory, Item},
+    player::components::{Client, Online},
+    visual::components::Depiction,
+};
+
+static WEAR_REGEX: OnceLock<Regex> = OnceLock::new();
+static WIELD_REGEX: OnceLock<Regex> = OnceLock::new();
+
+pub fn handle_wear(content: &str) -> Result<Command, ParseError> {
+    let regex = WEAR_REGEX.get_or_init(|| Regex::new(r"^wear( (?P<target>.*))?$").unwrap());
+
+    match regex.captures(content) {
+        None => Err(ParseError::WrongCommand),
+        Some(captures) => {
+            let target = captures
+                .name("target")
+                .map(|m| m.as_str().trim())
+                .ok_or(ParseError::InvalidArguments("Wear what?".into()))?;
+
+            Ok(Command::Wear(target.to_string()))
+        }
+    }
+}
+
+pub fn handle_wield(content: &str) -> Result<Command, ParseError> {
+    let regex = WIELD_REGEX.get_or_init(|| Regex::new(r"^wield( (?P<target>.*))?$").unwrap());
+
+    match regex.captures(content) {
+        None => Err(ParseError::WrongCommand),
+        Some(captures) => {
+            let target = captures
+                .name("target")
+                .map(|m| m.as_str().trim())
+                .ok_or(ParseError::InvalidArguments("Wield what?".into()))?;
+
+            Ok(Command::Wield(target.to_string()))
+        }
+    }
+}
+
+#[derive(WorldQuery)]
+pub struct ItemQuery {
+    entity: Entity,
+    depiction: &'static Depiction,
+    equippable: Option<&'static Equippable>,
+    with_item: With<Item>,
+}
+
+#[sysfail(log)]
+pub fn equip(
+    mut bevy: Commands,
+    mut commands: EventReader<ParsedCommand>,
+    mut outbox: EventWriter<Outbox>,
+    mut players: Query<(Entity, &Client, &Children, &mut Equipment), With<Online>>,
+    inventories: Query<(Entity, Option<&Children>), With<Inventory>>,
+    items: Query<ItemQuery>,
+) -> Result<(), anyhow::Error> {
+    for command in commands.iter() {
+        let (target, wield) = match &command.command {
+            Command::Wear(target) => (target, false),
+            Command::Wield(target) => (target, true),
+            _ => continue,
+        };
+
+        let (player, client, children, mut equipment) = players
+            .iter_mut()
+            .find(|(_, c, _, _)| c.id == command.from)
+            .context("Player not found")?;
+
+        let (inventory, in_inventory) = children
+            .iter()
+            .find_map(|child| inventories.get(*child).ok())
+            .context("Inventory not found")?;
+
+        match equip_item(target, wield, &in_inventory, &equipment, &items) {
+            Ok((item, previous)) => {
+                let equippable = item.equippable.context("Equippable not found")?;
+
+                let mut message = String::new();
+
+                if let Some(previous) = previous {
+                    bevy.entity(previous.entity).set_parent(inventory);
+
+                    message.push_str(&format!(
+                        "You remove the {} and ",
+                        previous.depiction.short_name
+                    ));
+                } else {
+                    message.push_str("You ");
+                }
+
+                message.push_str(&format!(
+                    "{} the {}.",
+                    if wield { "wield" } else { "wear" },
+                    item.depiction.short_name
+                ));
+
+                bevy.entity(item.entity).set_parent(player);
+                equipment.0.insert(equippable.slot, item.entity);
+
+                outbox.send_text(client.id, message);
+            }
+            Err(err) => outbox.send_text(client.id, err.to_string()),
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Error, Debug, PartialEq)]
+enum EquipError {
+    #[error("You don't have a {0}.")]
+    NotFound(String),
+    #[error("You can't wear the {0}.")]
+    CannotWear(String),
+    #[error("You can't wield the {0}.")]
+    CannotWield(String),
+    #[error("The {0} is meant to be wielded, not worn.")]
+    MeantToWield(String),
+    #[error("The {0} is meant to be worn, not wielded.")]
+    MeantToWear(String),
+}
+
+/// Finds the item to equip along with whatever it would replace.
+fn equip_item<'a>(
+    target: &str,
+    wield: bool,
+    in_inventory: &Option<&Children>,
+    equipment: &Equipment,
+    items: &'a Query<ItemQuery>,
+) -> Result<(ItemQueryItem<'a>, Option<ItemQueryItem<'a>>), EquipError> {
+    let item = in_inventory
+        .iter()
+        .flat_map(|children| children.iter())
+        .filter_map(|child| items.get(*child).ok())
+        .find(|item| item.depiction.matches_query(&item.entity, target))
+        .ok_or_else(|| EquipError::NotFound(target.to_string()))?;
+
+    let name = item.depiction.short_name.clone();
+
+    let Some(equippable) = item.equippable else {
+        return Err(match wield {
+            true => EquipError::CannotWield(name),
+            false => EquipError::CannotWear(name),
+        });
+    };
+
+    match (wield, equippable.slot.is_held()) {
+        (true, false) => return Err(EquipError::MeantToWear(name)),
+        (false, true) => return Err(EquipError::MeantToWield(name)),
+        _ => {}
+    }
+
+    let previous = equipment
+        .0
+        .get(&equippable.slot)
+        .and_then(|entity| items.get(*entity).ok());
+
+    Ok((item, previous))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        items::components::Slot,
+        test::{
+            app_builder::AppBuilder,
+            item_builder::ItemBuilder,
+            player_builder::PlayerBuilder,
+            tile_builder::{TileBuilder, ZoneBuilder},
+            utils::{clear_messages, get_message_content, send_message},
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn parses() {
+        assert_eq!(handle_wear("wear cap"), Ok(Command::Wear("cap".into())));
+        assert_eq!(
+            handle_wield("wield sword"),
+            Ok(Command::Wield("sword".into()))
+        );
+        assert_eq!(
+            handle_wear("wear"),
+            Err(ParseError::InvalidArguments("Wear what?".into()))
+        );
+        assert_eq!(
+            handle_wield("wield"),
+            Err(ParseError::InvalidArguments("Wield what?".into()))
+        );
+    }
+
+    #[test]
+    fn wears_an_item() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, equip);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (player, client_id, inventory) = PlayerBuilder::new()
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        let cap = ItemBuilder::new()
+            .name("cap")
+            .short_name("leather cap")
+            .equippable(Slot::Head)
+            .build(&mut app);
+
+        app.world.entity_mut(inventory.unwrap()).add_child(cap);
+
+        send_message(&mut app, client_id, "wear cap");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(content, "You wear the leather cap.");
+        assert_eq!(app.world.get::<Parent>(cap).unwrap().get(), player);
+        assert_eq!(
+            app.world
+                .get::<Equipment>(player)
+                .unwrap()
+                .0
+                .get(&Slot::Head),
+            Some(&cap)
+        );
+    }
+
+    #[test]
+    fn swaps_an_item() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, equip);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (player, client_id, inventory) = PlayerBuilder::new()
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        let stick = ItemBuilder::new()
+            .name("stick")
+            .short_name("stick")
+            .equippable(Slot::MainHand)
+            .build(&mut app);
+
+        let sword = ItemBuilder::new()
+            .name("sword")
+            .short_name("sword")
+            .equippable(Slot::MainHand)
+            .build(&mut app);
+
+        app.world.entity_mut(player).add_child(stick);
+        app.world.entity_mut(inventory.unwrap()).add_child(sword);
+        app.world
+            .get_mut::<Equipment>(player)
+            .unwrap()
+            .0
+            .insert(Slot::MainHand, stick);
+
+        send_message(&mut app, client_id, "wield sword");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(content, "You remove the stick and wield the sword.");
+        assert_eq!(
+            app.world.get::<Parent>(stick).unwrap().get(),
+            inventory.unwrap()
+        );
+        assert_eq!(
+            app.world
+                .get::<Equipment>(player)
+                .unwrap()
+                .0
+                .get(&Slot::MainHand),
+            Some(&sword)
+        );
+    }
+
+    #[test]
+    fn wrong_verb() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, equip);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (_, client_id, inventory) = PlayerBuilder::new()
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        let sword = ItemBuilder::new()
+            .name("sword")
+            .short_name("sword")
+            .equippable(Slot::MainHand)
+            .build(&mut app);
+
+        let rock = ItemBuilder::new()
+            .name("rock")
+            .short_name("rock")
+            .build(&mut app);
+
+        app.world.entity_mut(inventory.unwrap()).add_child(sword);
+        app.world.entity_mut(inventory.unwrap()).add_child(rock);
+
+        send_message(&mut app, client_id, "wear sword");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "The sword is meant to be wielded, not worn."
+        );
+
+        clear_messages(&mut app);
+
+        send_message(&mut app, client_id, "wield rock");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "You can't wield the rock."
+        );
+    }
+}
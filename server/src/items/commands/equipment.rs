@@ -0,0 +1,137 @@
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use bevy::prelude::*;
+use bevy_mod_sysfail::sysfail;
+use bevy_nest::prelude::*;
+use regex::Regex;
+
+use crate::{
+    input::events::{Command, ParseError, ParsedCommand},
+    items::components::{Equipment, Item, Slot},
+    player::components::{Client, Online},
+    visual::components::Depiction,
+};
+
+static REGEX: OnceLock<Regex> = OnceLock::new();
+
+pub fn handle_equipment(content: &str) -> Result<Command, ParseError> {
+    let regex = REGEX.get_or_init(|| Regex::new(r"^(equipment|eq)$").unwrap());
+
+    match regex.is_match(content) {
+        false => Err(ParseError::WrongCommand),
+        true => Ok(Command::Equipment),
+    }
+}
+
+#[sysfail(log)]
+pub fn equipment(
+    mut commands: EventReader<ParsedCommand>,
+    mut outbox: EventWriter<Outbox>,
+    players: Query<(&Client, &Equipment), With<Online>>,
+    items: Query<&Depiction, With<Item>>,
+) -> Result<(), anyhow::Error> {
+    for command in commands.iter() {
+        if let Command::Equipment = &command.command {
+            let (client, equipment) = players
+                .iter()
+                .find(|(c, _)| c.id == command.from)
+                .context("Player not found")?;
+
+            if equipment.0.is_empty() {
+                outbox.send_text(client.id, "You aren't wearing or wielding anything.");
+
+                continue;
+            }
+
+            let lines = Slot::ALL
+                .iter()
+                .map(|slot| {
+                    let name = equipment
+                        .0
+                        .get(slot)
+                        .and_then(|item| items.get(*item).ok())
+                        .map_or("nothing", |depiction| depiction.name.as_str());
+
+                    format!("{:<10} {name}", format!("{slot}:"))
+                })
+                .collect::<Vec<_>>();
+
+            outbox.send_text(client.id, lines.join("\n"));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::{
+        app_builder::AppBuilder,
+        item_builder::ItemBuilder,
+        player_builder::PlayerBuilder,
+        utils::{get_message_content, send_message},
+    };
+
+    use super::*;
+
+    #[test]
+    fn parses() {
+        assert_eq!(handle_equipment("equipment"), Ok(Command::Equipment));
+        assert_eq!(handle_equipment("eq"), Ok(Command::Equipment));
+    }
+
+    #[test]
+    fn nothing_equipped() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, equipment);
+
+        let (_, client_id, _) = PlayerBuilder::new().build(&mut app);
+
+        send_message(&mut app, client_id, "eq");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(content, "You aren't wearing or wielding anything.");
+    }
+
+    #[test]
+    fn lists_slots() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, equipment);
+
+        let (player, client_id, _) = PlayerBuilder::new().build(&mut app);
+
+        let sword = ItemBuilder::new()
+            .name("Rusty Sword")
+            .equippable(Slot::MainHand)
+            .build(&mut app);
+
+        app.world.entity_mut(player).add_child(sword);
+        app.world
+            .get_mut::<Equipment>(player)
+            .unwrap()
+            .0
+            .insert(Slot::MainHand, sword);
+
+        send_message(&mut app, client_id, "equipment");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(
+            content,
+            [
+                "head:      nothing",
+                "body:      nothing",
+                "hands:     nothing",
+                "legs:      nothing",
+                "feet:      nothing",
+                "main hand: Rusty Sword",
+                "off hand:  nothing",
+            ]
+            .join("\n")
+        );
+    }
+}
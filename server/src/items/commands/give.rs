@@ -0,0 +1,384 @@
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use bevy::{ecs::query::WorldQuery, prelude::*};
+use bevy_mod_sysfail::sysfail;
+use bevy_nest::prelude::*;
+use regex::Regex;
+use thiserror::Error;
+
+use crate::{
+    input::events::{Command, ParseError, ParsedCommand},
+    items::{
+        components::{Inventory, Item},
+        utils::{move_items, split_count, StackQuery},
+    },
+    player::components::{Character, Client, Online},
+    visual::{components::Depiction, utils::counted_name_list},
+};
+
+static REGEX: OnceLock<Regex> = OnceLock::new();
+
+pub fn handle_give(content: &str) -> Result<Command, ParseError> {
+    let regex = REGEX.get_or_init(|| {
+        Regex::new(r"^give( (?P<all>all))?( (?P<target>.*?))?( to (?P<recipient>.*))?$").unwrap()
+    });
+
+    match regex.captures(content) {
+        None => Err(ParseError::WrongCommand),
+        Some(captures) => {
+            let target = captures
+                .name("target")
+                .map(|m| m.as_str().trim())
+                .filter(|target| !target.is_empty())
+                .ok_or(ParseError::InvalidArguments("Give what?".into()))?;
+
+            let recipient = captures
+                .name("recipient")
+                .map(|m| m.as_str().trim())
+                .ok_or(ParseError::InvalidArguments("Give it to whom?".into()))?;
+
+            let all = captures.name("all").is_some();
+
+            Ok(Command::Give((target.into(), all, recipient.into())))
+        }
+    }
+}
+
+#[derive(WorldQuery)]
+pub struct PlayerQuery {
+    entity: Entity,
+    client: &'static Client,
+    character: &'static Character,
+    parent: &'static Parent,
+    children: &'static Children,
+    with_online: With<Online>,
+}
+
+#[derive(WorldQuery)]
+pub struct ItemQuery {
+    entity: Entity,
+    depiction: &'static Depiction,
+    with_item: With<Item>,
+}
+
+#[sysfail(log)]
+pub fn give(
+    mut bevy: Commands,
+    mut commands: EventReader<ParsedCommand>,
+    mut outbox: EventWriter<Outbox>,
+    players: Query<PlayerQuery>,
+    inventories: Query<(Entity, Option<&Children>), With<Inventory>>,
+    items: Query<ItemQuery>,
+    stacks: Query<StackQuery>,
+) -> Result<(), anyhow::Error> {
+    for command in commands.iter() {
+        if let Command::Give((target, all, recipient)) = &command.command {
+            let player = players
+                .iter()
+                .find(|p| p.client.id == command.from)
+                .context("Player not found")?;
+
+            let Some(recipient) = players.iter().find(|p| {
+                p.parent.get() == player.parent.get()
+                    && p.character.name.eq_ignore_ascii_case(recipient)
+            }) else {
+                outbox.send_text(
+                    player.client.id,
+                    GiveError::RecipientNotFound(recipient.clone()).to_string(),
+                );
+
+                continue;
+            };
+
+            if recipient.entity == player.entity {
+                outbox.send_text(player.client.id, GiveError::ToSelf.to_string());
+
+                continue;
+            }
+
+            let (_, carried) = player
+                .children
+                .iter()
+                .find_map(|child| inventories.get(*child).ok())
+                .context("Inventory not found")?;
+
+            let (inventory, received) = recipient
+                .children
+                .iter()
+                .find_map(|child| inventories.get(*child).ok())
+                .context("Recipient inventory not found")?;
+
+            let (count, target) = split_count(target);
+
+            let items_found = carried
+                .iter()
+                .flat_map(|children| children.iter())
+                .filter_map(|child| items.get(*child).ok())
+                .filter(|item| item.depiction.matches_query(&item.entity, target))
+                .map(|item| item.entity)
+                .collect::<Vec<_>>();
+
+            if items_found.is_empty() {
+                outbox.send_text(
+                    player.client.id,
+                    GiveError::NotFound(target.into()).to_string(),
+                );
+
+                continue;
+            }
+
+            let count = if *all { None } else { Some(count.unwrap_or(1)) };
+            let received = received.map_or_else(Vec::new, |children| children.to_vec());
+            let moved = move_items(
+                &mut bevy,
+                &items_found,
+                count,
+                inventory,
+                &received,
+                &stacks,
+            );
+
+            let item_names = counted_name_list(&moved.names, None, true);
+
+            outbox.send_text(
+                player.client.id,
+                format!("You give {item_names} to {}.", recipient.character.name),
+            );
+
+            outbox.send_text(
+                recipient.client.id,
+                format!("{} gives you {item_names}.", player.character.name),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Error, Debug, PartialEq)]
+enum GiveError {
+    #[error("You don't have a {0}.")]
+    NotFound(String),
+    #[error("You don't see {0} here.")]
+    RecipientNotFound(String),
+    #[error("You can't give things to yourself.")]
+    ToSelf,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        items::components::Stack,
+        test::{
+            app_builder::AppBuilder,
+            item_builder::ItemBuilder,
+            player_builder::PlayerBuilder,
+            tile_builder::{TileBuilder, ZoneBuilder},
+            utils::{get_message_content, send_message},
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn parses() {
+        let object = handle_give("give rock to Bau");
+        assert_eq!(
+            object,
+            Ok(Command::Give(("rock".into(), false, "Bau".into())))
+        );
+
+        let count = handle_give("give 5 arrows to Bau");
+        assert_eq!(
+            count,
+            Ok(Command::Give(("5 arrows".into(), false, "Bau".into())))
+        );
+
+        let all = handle_give("give all arrows to Bau");
+        assert_eq!(
+            all,
+            Ok(Command::Give(("arrows".into(), true, "Bau".into())))
+        );
+
+        let no_object = handle_give("give");
+        assert_eq!(
+            no_object,
+            Err(ParseError::InvalidArguments("Give what?".into()))
+        );
+
+        let no_recipient = handle_give("give rock");
+        assert_eq!(
+            no_recipient,
+            Err(ParseError::InvalidArguments("Give it to whom?".into()))
+        );
+    }
+
+    #[test]
+    fn by_name() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, give);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (_, client_id, inventory) = PlayerBuilder::new()
+            .name("Ashur")
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        let (_, recipient_id, recipient_inventory) = PlayerBuilder::new()
+            .name("Bau")
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        let stick = ItemBuilder::new().name("stick").build(&mut app);
+        app.world.entity_mut(inventory.unwrap()).add_child(stick);
+
+        send_message(&mut app, client_id, "give stick to bau");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "You give a stick to Bau."
+        );
+        assert_eq!(
+            get_message_content(&mut app, recipient_id).unwrap(),
+            "Ashur gives you a stick."
+        );
+        assert!(app
+            .world
+            .get::<Children>(recipient_inventory.unwrap())
+            .unwrap()
+            .contains(&stick));
+    }
+
+    #[test]
+    fn count_merges_into_stack() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, give);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (_, client_id, inventory) = PlayerBuilder::new()
+            .name("Ashur")
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        let (_, _, recipient_inventory) = PlayerBuilder::new()
+            .name("Bau")
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        let arrows = ItemBuilder::new()
+            .name("arrow")
+            .stack(10, 20)
+            .build(&mut app);
+        let received = ItemBuilder::new()
+            .name("arrow")
+            .stack(2, 20)
+            .build(&mut app);
+
+        app.world.entity_mut(inventory.unwrap()).add_child(arrows);
+        app.world
+            .entity_mut(recipient_inventory.unwrap())
+            .add_child(received);
+
+        send_message(&mut app, client_id, "give 5 arrows to Bau");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "You give 5 arrows to Bau."
+        );
+        assert_eq!(app.world.get::<Stack>(arrows).unwrap().quantity, 5);
+        assert_eq!(app.world.get::<Stack>(received).unwrap().quantity, 7);
+    }
+
+    #[test]
+    fn recipient_not_here() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, give);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+        let other_tile = TileBuilder::new().build(&mut app, zone);
+
+        let (_, client_id, _) = PlayerBuilder::new()
+            .name("Ashur")
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        PlayerBuilder::new()
+            .name("Bau")
+            .tile(other_tile)
+            .has_inventory()
+            .build(&mut app);
+
+        send_message(&mut app, client_id, "give stick to Bau");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "You don't see Bau here."
+        );
+    }
+
+    #[test]
+    fn not_to_self() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, give);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (_, client_id, _) = PlayerBuilder::new()
+            .name("Ashur")
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        send_message(&mut app, client_id, "give stick to ashur");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "You can't give things to yourself."
+        );
+    }
+
+    #[test]
+    fn not_found() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, give);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (_, client_id, _) = PlayerBuilder::new()
+            .name("Ashur")
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        PlayerBuilder::new()
+            .name("Bau")
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        send_message(&mut app, client_id, "give sword to Bau");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "You don't have a sword."
+        );
+    }
+}
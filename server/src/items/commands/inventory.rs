@@ -9,7 +9,10 @@ use thiserror::Error;
 
 use crate::{
     input::events::{Command, ParseError, ParsedCommand},
-    items::components::{Inventory, Item},
+    items::{
+        components::{Inventory, Item, Stack},
+        utils::quantified,
+    },
     player::components::{Client, Online},
     visual::components::Depiction,
 };
@@ -29,6 +32,8 @@ pub fn handle_inventory(content: &str) -> Result<Command, ParseError> {
 pub struct ItemQuery {
     entity: Entity,
     depiction: &'static Depiction,
+    children: Option<&'static Children>,
+    stack: Option<&'static Stack>,
     with_item: With<Item>,
 }
 
@@ -96,14 +101,35 @@ fn get_items(
 fn list_items(in_inventory: Vec<Entity>, items: &Query<ItemQuery>) -> String {
     let names = in_inventory
         .iter()
-        .filter_map(|item| items.get(*item).ok())
-        .map(|item| item.depiction.name.clone())
+        .filter_map(|item| describe_item(*item, items))
         .collect::<Vec<_>>()
         .join(", ");
 
     format!("You are carrying: {names}")
 }
 
+/// The item's name, followed by whatever is inside it.
+fn describe_item(item: Entity, items: &Query<ItemQuery>) -> Option<String> {
+    let item = items.get(item).ok()?;
+
+    let mut contents = item
+        .children
+        .iter()
+        .flat_map(|children| children.iter())
+        .filter_map(|child| describe_item(*child, items))
+        .collect::<Vec<_>>();
+
+    let name = quantified(&item.depiction.name, item.stack);
+
+    if contents.is_empty() {
+        return Some(name);
+    }
+
+    contents.sort();
+
+    Some(format!("{} ({})", name, contents.join(", ")))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test::{
@@ -161,4 +187,38 @@ mod tests {
 
         assert_eq!(content, "You are carrying: stick");
     }
+
+    #[test]
+    fn carrying_a_container() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, inventory);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let pouch = ItemBuilder::new()
+            .name("pouch")
+            .is_container(2)
+            .build(&mut app);
+        let coin = ItemBuilder::new().name("coin").build(&mut app);
+        let stick = ItemBuilder::new().name("stick").build(&mut app);
+
+        app.world.entity_mut(pouch).add_child(coin);
+
+        let (_, client_id, inventory) = PlayerBuilder::new()
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        app.world
+            .entity_mut(inventory.unwrap())
+            .push_children(&[stick, pouch]);
+
+        send_message(&mut app, client_id, "inventory");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(content, "You are carrying: pouch (coin), stick");
+    }
 }
@@ -1,2 +1,6 @@
 pub mod drop;
+pub mod equip;
+pub mod equipment;
+pub mod give;
 pub mod inventory;
+pub mod remove;
@@ -0,0 +1,168 @@
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use bevy::prelude::*;
+use bevy_mod_sysfail::sysfail;
+use bevy_nest::prelude::*;
+use regex::Regex;
+
+use crate::{
+    input::events::{Command, ParseError, ParsedCommand},
+    items::components::{Equipment, Inventory, Item},
+    player::components::{Client, Online},
+    visual::components::Depiction,
+};
+
+static REGEX: OnceLock<Regex> = OnceLock::new();
+
+pub fn handle_remove(content: &str) -> Result<Command, ParseError> {
+    let regex = REGEX.get_or_init(|| Regex::new(r"^remove( (?P<target>.*))?$").unwrap());
+
+    match regex.captures(content) {
+        None => Err(ParseError::WrongCommand),
+        Some(captures) => {
+            let target = captures
+                .name("target")
+                .map(|m| m.as_str().trim())
+                .ok_or(ParseError::InvalidArguments("Remove what?".into()))?;
+
+            Ok(Command::Remove(target.to_string()))
+        }
+    }
+}
+
+#[sysfail(log)]
+pub fn remove(
+    mut bevy: Commands,
+    mut commands: EventReader<ParsedCommand>,
+    mut outbox: EventWriter<Outbox>,
+    mut players: Query<(&Client, &Children, &mut Equipment), With<Online>>,
+    inventories: Query<Entity, With<Inventory>>,
+    items: Query<(Entity, &Depiction), With<Item>>,
+) -> Result<(), anyhow::Error> {
+    for command in commands.iter() {
+        if let Command::Remove(target) = &command.command {
+            let (client, children, mut equipment) = players
+                .iter_mut()
+                .find(|(c, _, _)| c.id == command.from)
+                .context("Player not found")?;
+
+            let inventory = children
+                .iter()
+                .find_map(|child| inventories.get(*child).ok())
+                .context("Inventory not found")?;
+
+            let Some((slot, (item, depiction))) = equipment
+                .0
+                .iter()
+                .filter_map(|(slot, item)| items.get(*item).ok().map(|item| (*slot, item)))
+                .find(|(_, (entity, depiction))| depiction.matches_query(entity, target))
+            else {
+                outbox.send_text(
+                    client.id,
+                    format!("You aren't wearing or wielding a {target}."),
+                );
+
+                continue;
+            };
+
+            equipment.0.remove(&slot);
+            bevy.entity(item).set_parent(inventory);
+
+            outbox.send_text(
+                client.id,
+                format!("You remove the {}.", depiction.short_name),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        items::components::Slot,
+        test::{
+            app_builder::AppBuilder,
+            item_builder::ItemBuilder,
+            player_builder::PlayerBuilder,
+            tile_builder::{TileBuilder, ZoneBuilder},
+            utils::{get_message_content, send_message},
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn parses() {
+        assert_eq!(
+            handle_remove("remove cap"),
+            Ok(Command::Remove("cap".into()))
+        );
+        assert_eq!(
+            handle_remove("remove"),
+            Err(ParseError::InvalidArguments("Remove what?".into()))
+        );
+    }
+
+    #[test]
+    fn removes_an_item() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, remove);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (player, client_id, inventory) = PlayerBuilder::new()
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        let cap = ItemBuilder::new()
+            .name("cap")
+            .short_name("leather cap")
+            .equippable(Slot::Head)
+            .build(&mut app);
+
+        app.world.entity_mut(player).add_child(cap);
+        app.world
+            .get_mut::<Equipment>(player)
+            .unwrap()
+            .0
+            .insert(Slot::Head, cap);
+
+        send_message(&mut app, client_id, "remove cap");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(content, "You remove the leather cap.");
+        assert_eq!(
+            app.world.get::<Parent>(cap).unwrap().get(),
+            inventory.unwrap()
+        );
+        assert!(app.world.get::<Equipment>(player).unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn not_equipped() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, remove);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (_, client_id, _) = PlayerBuilder::new()
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        send_message(&mut app, client_id, "remove cap");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(content, "You aren't wearing or wielding a cap.");
+    }
+}
@@ -1,17 +1,100 @@
 use std::fmt::{self, Display, Formatter};
 
-use bevy::prelude::*;
+use bevy::{
+    ecs::world::EntityMut,
+    prelude::*,
+    reflect::{serde::TypedReflectDeserializer, FromType, TypeRegistryInternal},
+    utils::HashMap,
+};
 use bevy_proto::prelude::*;
+use serde::{de::DeserializeSeed, Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{data::resources::Stat, party::components::Party, values::LITTER_CLEANUP_TIMER};
 
 #[derive(Component)]
 pub struct Inventory;
 
-#[derive(Component, Schematic, Reflect)]
+#[derive(Component, Schematic, Reflect, Clone)]
 #[reflect(Schematic)]
 pub struct Item {
     pub size: Size,
 }
 
+/// Identical items kept together as one, such as a bundle of arrows. Items
+/// with the same name merge into a stack as they're moved, up to its `max`.
+#[derive(Component, Schematic, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Schematic)]
+pub struct Stack {
+    pub quantity: u32,
+    pub max: u32,
+}
+
+impl Stack {
+    /// How much more the stack can take before it's full.
+    pub fn room(&self) -> u32 {
+        self.max.saturating_sub(self.quantity)
+    }
+}
+
+/// A stable id for an item instance, kept across saves so it's the same
+/// item after a restart rather than a fresh copy of its prototype.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ItemId(pub Uuid);
+
+/// The serialized state of an item's persisted components, keyed by type
+/// name. Kept current as they change so saving doesn't need world access.
+#[derive(Component, Clone, Debug, Default)]
+pub struct ItemState(pub HashMap<String, serde_json::Value>);
+
+/// Reflected type data for components whose state belongs to the item
+/// instance and is saved with it, rather than coming from its prototype.
+#[derive(Clone)]
+pub struct ReflectPersist {
+    insert: fn(&mut EntityMut, &dyn Reflect),
+}
+
+impl ReflectPersist {
+    pub fn insert(&self, entity: &mut EntityMut, component: &dyn Reflect) {
+        (self.insert)(entity, component);
+    }
+}
+
+impl<C: Component + FromReflect> FromType<C> for ReflectPersist {
+    fn from_type() -> Self {
+        Self {
+            insert: |entity, component| {
+                if let Some(component) = C::from_reflect(component) {
+                    entity.insert(component);
+                }
+            },
+        }
+    }
+}
+
+impl ItemState {
+    /// Lays saved state back over an item spawned from its prototype.
+    pub fn restore(self, entity: &mut EntityMut, registry: &TypeRegistryInternal) {
+        for (name, value) in self.0.iter() {
+            let Some(registration) = registry.get_with_name(name) else {
+                warn!("Unknown persisted component: {name}");
+                continue;
+            };
+
+            let Some(persist) = registration.data::<ReflectPersist>() else {
+                continue;
+            };
+
+            match TypedReflectDeserializer::new(registration, registry).deserialize(value) {
+                Ok(component) => persist.insert(entity, &*component),
+                Err(err) => warn!("Failed to restore {name}: {err}"),
+            }
+        }
+
+        entity.insert(self);
+    }
+}
+
 #[derive(Copy, Clone, Reflect)]
 pub enum Size {
     Small,
@@ -29,6 +112,113 @@ impl Size {
     }
 }
 
+/// Gear that can be worn or wielded, granting its modifiers and resistances
+/// to whoever has it equipped.
+#[derive(Component, Schematic, Reflect)]
+#[reflect(Schematic)]
+pub struct Equippable {
+    pub slot: Slot,
+    /// Flat amounts added to each stat while equipped.
+    #[reflect(default)]
+    pub modifiers: Vec<(Stat, f32)>,
+    /// Resistance ratings added while equipped, keyed by resistance id.
+    #[reflect(default)]
+    pub resistances: HashMap<String, u32>,
+}
+
+/// An item that can be fought with. Wielded in the main hand, it decides
+/// the wielder's auto-attack and the damage their attacks deal.
+#[derive(Component, Schematic, Reflect, Clone, Debug)]
+#[reflect(Schematic)]
+pub struct Weapon {
+    pub min_damage: u32,
+    pub max_damage: u32,
+    /// The id of the damage kind dealt, from `DamageKinds`.
+    pub kind: String,
+    /// Scales how long the wielder waits between auto-attacks, so below
+    /// `1.0` is quicker than fighting bare-handed.
+    pub speed: f32,
+    /// A skill to auto-attack with in place of the mastery's.
+    #[reflect(default)]
+    pub auto_attack: Option<String>,
+}
+
+/// Prefixes the ids of modifiers granted by equipped gear.
+pub const GEAR_MODIFIER: &str = "equipment:";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum Slot {
+    Head,
+    Body,
+    Hands,
+    Legs,
+    Feet,
+    MainHand,
+    OffHand,
+}
+
+impl Slot {
+    pub const ALL: [Self; 7] = [
+        Self::Head,
+        Self::Body,
+        Self::Hands,
+        Self::Legs,
+        Self::Feet,
+        Self::MainHand,
+        Self::OffHand,
+    ];
+
+    /// Whether the slot is held rather than worn.
+    pub const fn is_held(self) -> bool {
+        matches!(self, Self::MainHand | Self::OffHand)
+    }
+
+    /// Every modifier granted by the gear in this slot starts with this.
+    pub fn modifier_prefix(self) -> String {
+        format!("{GEAR_MODIFIER}{self:?}:")
+    }
+
+    /// The id of the gear's modifier at `index` while it's in this slot.
+    pub fn modifier_id(self, index: usize) -> String {
+        format!("{}{index}", self.modifier_prefix())
+    }
+}
+
+impl Display for Slot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Head => write!(f, "head"),
+            Self::Body => write!(f, "body"),
+            Self::Hands => write!(f, "hands"),
+            Self::Legs => write!(f, "legs"),
+            Self::Feet => write!(f, "feet"),
+            Self::MainHand => write!(f, "main hand"),
+            Self::OffHand => write!(f, "off hand"),
+        }
+    }
+}
+
+/// The items a character has worn or wielded, one per slot. Equipped items
+/// are children of the character rather than of their inventory.
+#[derive(Component, Default, Debug, Clone)]
+pub struct Equipment(pub HashMap<Slot, Entity>);
+
+impl Equipment {
+    pub fn slot_of(&self, item: Entity) -> Option<Slot> {
+        self.0
+            .iter()
+            .find(|(_, equipped)| **equipped == item)
+            .map(|(slot, _)| *slot)
+    }
+
+    /// The weapon in the main hand, if any.
+    pub fn weapon<'a>(&self, weapons: &'a Query<&Weapon>) -> Option<&'a Weapon> {
+        self.0
+            .get(&Slot::MainHand)
+            .and_then(|item| weapons.get(*item).ok())
+    }
+}
+
 #[derive(Component, Schematic, Reflect)]
 #[reflect(Schematic)]
 pub struct Surface {
@@ -58,8 +248,81 @@ impl Display for SurfaceKind {
     }
 }
 
+/// A portable item that holds others, such as a bag. Its capacity is in
+/// the same units as an item's `Size`.
+#[derive(Component, Schematic, Reflect)]
+#[reflect(Schematic)]
+pub struct Container {
+    pub capacity: u8,
+}
+
 #[derive(Component, Schematic, Reflect)]
 #[reflect(Schematic)]
 pub struct Seat {
     pub phrase: String,
 }
+
+/// The remains of something that died, holding whatever it dropped until it
+/// rots away.
+#[derive(Component)]
+pub struct Corpse {
+    pub rights: LootRights,
+    pub decay: Timer,
+}
+
+/// Who may take items from a corpse.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LootRights {
+    Anyone,
+    /// The character who earned the loot, along with their party.
+    Killer(i64),
+    /// The character who died, and nobody else.
+    Owner(i64),
+}
+
+impl Corpse {
+    pub fn can_loot<'a>(&self, id: i64, mut parties: impl Iterator<Item = &'a Party>) -> bool {
+        match self.rights {
+            LootRights::Anyone => true,
+            LootRights::Killer(killer) => {
+                killer == id || parties.any(|party| party.contains(killer) && party.contains(id))
+            }
+            LootRights::Owner(owner) => owner == id,
+        }
+    }
+}
+
+/// Marks a player's corpse, which is saved with the world so a restart
+/// doesn't take their belongings with it.
+#[derive(Component)]
+pub struct PlayerCorpse {
+    pub owner: i64,
+    pub name: String,
+}
+
+/// An item a character left in the world, outside of anything that saves
+/// it already. It's saved with the world and may be cleaned up in time.
+#[derive(Component)]
+pub struct Dropped {
+    pub cleanup: Option<Timer>,
+}
+
+impl Dropped {
+    /// Left on the ground, where it's litter.
+    pub fn loose() -> Self {
+        Self {
+            cleanup: LITTER_CLEANUP_TIMER
+                .map(|seconds| Timer::from_seconds(seconds, TimerMode::Once)),
+        }
+    }
+
+    /// Put somewhere on purpose, where it stays.
+    pub fn placed() -> Self {
+        Self { cleanup: None }
+    }
+}
+
+/// A loot table waiting to be rolled into a corpse once its prototypes are
+/// ready to spawn.
+#[derive(Component)]
+pub struct UnrolledLoot(pub String);
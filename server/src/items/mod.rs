@@ -2,3 +2,5 @@ pub mod bundles;
 pub mod commands;
 pub mod components;
 pub mod plugin;
+pub mod systems;
+pub mod utils;
@@ -1,9 +1,12 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, reflect::GetTypeRegistration, utils::HashMap};
+
+use crate::data::resources::Stat;
 
 use super::{
     bundles::ItemBundle,
-    commands::{drop::*, inventory::*},
+    commands::{drop::*, equip::*, equipment::*, give::*, inventory::*, remove::*},
     components::*,
+    systems::*,
 };
 
 pub struct ItemPlugin;
@@ -13,10 +16,53 @@ impl Plugin for ItemPlugin {
         app.register_type::<ItemBundle>()
             .register_type::<Item>()
             .register_type::<Surface>()
+            .register_type::<Container>()
             .register_type::<Seat>()
             .register_type::<SurfaceKind>()
-            .register_type::<Size>();
+            .register_type::<Size>()
+            .register_type::<Equippable>()
+            .register_type::<Weapon>()
+            .register_type::<Slot>()
+            .register_type::<Stat>()
+            .register_type::<(Stat, f32)>()
+            .register_type::<Vec<(Stat, f32)>>()
+            .register_type::<HashMap<String, u32>>()
+            .persist_item_state::<Stack>();
+
+        app.add_systems(
+            Update,
+            (
+                inventory,
+                drop,
+                give,
+                equip,
+                remove,
+                equipment,
+                apply_equipment,
+                assign_item_ids,
+                roll_corpse_loot,
+                decay_corpses,
+                clean_up_litter,
+            ),
+        );
+    }
+}
+
+pub trait PersistItemState {
+    /// Saves the component with each item instance that has it, restoring
+    /// it over the prototype's on load.
+    fn persist_item_state<C>(&mut self) -> &mut Self
+    where
+        C: Component + Reflect + FromReflect + GetTypeRegistration;
+}
 
-        app.add_systems(Update, (inventory, drop));
+impl PersistItemState for App {
+    fn persist_item_state<C>(&mut self) -> &mut Self
+    where
+        C: Component + Reflect + FromReflect + GetTypeRegistration,
+    {
+        self.register_type::<C>()
+            .register_type_data::<C, ReflectPersist>()
+            .add_systems(Update, track_item_state::<C>)
     }
 }
@@ -0,0 +1,469 @@
+use bevy::{prelude::*, reflect::serde::TypedReflectSerializer, utils::HashMap};
+use bevy_nest::prelude::*;
+use bevy_proto::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    combat::components::{Modifiers, Resistance, Stats},
+    data::resources::{LootTables, Modifier},
+    player::components::{Client, Online},
+    spatial::components::Tile,
+    visual::components::Depiction,
+    world::resources::GameRng,
+};
+
+use super::components::{
+    Corpse, Dropped, Equipment, Equippable, Item, ItemId, ItemState, UnrolledLoot, GEAR_MODIFIER,
+};
+
+pub fn assign_item_ids(mut bevy: Commands, items: Query<Entity, (With<Item>, Without<ItemId>)>) {
+    for item in items.iter() {
+        bevy.entity(item).insert(ItemId(Uuid::new_v4()));
+    }
+}
+
+/// Serializes a persisted component into its item's `ItemState` whenever
+/// it changes.
+pub fn track_item_state<C: Component + Reflect>(
+    mut bevy: Commands,
+    mut items: Query<(Entity, &C, Option<&mut ItemState>), (With<Item>, Changed<C>)>,
+    registry: Res<AppTypeRegistry>,
+) {
+    let registry = registry.read();
+
+    for (entity, component, state) in items.iter_mut() {
+        let value = match serde_json::to_value(TypedReflectSerializer::new(component, &registry)) {
+            Ok(value) => value,
+            Err(err) => {
+                warn!("Failed to serialize {}: {err}", component.type_name());
+                continue;
+            }
+        };
+
+        let name = component.type_name().to_string();
+
+        if let Some(mut state) = state {
+            state.0.insert(name, value);
+        } else {
+            bevy.entity(entity)
+                .insert(ItemState(HashMap::from([(name, value)])));
+        }
+    }
+}
+
+/// Rolls a corpse's loot table and spawns the results inside it, waiting
+/// until every prototype the table could pick has loaded.
+pub fn roll_corpse_loot(
+    mut bevy: Commands,
+    mut proto: ProtoCommands,
+    prototypes: Prototypes,
+    loot_tables: Res<LootTables>,
+    corpses: Query<(Entity, &UnrolledLoot), With<Corpse>>,
+    mut rng: ResMut<GameRng>,
+) {
+    for (corpse, loot) in corpses.iter() {
+        let Some(table) = loot_tables.0.get(&loot.0) else {
+            warn!("Loot table not found: {}", loot.0);
+            bevy.entity(corpse).remove::<UnrolledLoot>();
+
+            continue;
+        };
+
+        if !table
+            .entries
+            .iter()
+            .all(|entry| prototypes.is_ready(&entry.prototype))
+        {
+            continue;
+        }
+
+        for prototype in table.roll(&mut *rng) {
+            let item = proto.spawn(prototype).id();
+            bevy.entity(item).set_parent(corpse);
+        }
+
+        bevy.entity(corpse).remove::<UnrolledLoot>();
+    }
+}
+
+pub fn clean_up_litter(
+    mut bevy: Commands,
+    mut items: Query<(Entity, &mut Dropped)>,
+    time: Res<Time>,
+) {
+    for (entity, mut dropped) in items.iter_mut() {
+        if dropped
+            .cleanup
+            .as_mut()
+            .is_some_and(|timer| timer.tick(time.delta()).just_finished())
+        {
+            bevy.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn decay_corpses(
+    mut bevy: Commands,
+    mut outbox: EventWriter<Outbox>,
+    mut corpses: Query<(Entity, &Depiction, &Parent, &mut Corpse)>,
+    players: Query<&Client, With<Online>>,
+    tiles: Query<&Children, With<Tile>>,
+    time: Res<Time>,
+) {
+    for (entity, depiction, tile, mut corpse) in corpses.iter_mut() {
+        if !corpse.decay.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        if let Ok(siblings) = tiles.get(tile.get()) {
+            for client in siblings.iter().filter_map(|s| players.get(*s).ok()) {
+                outbox.send_text(
+                    client.id,
+                    format!("The {} rots away.", depiction.short_name),
+                );
+            }
+        }
+
+        bevy.entity(entity).despawn_recursive();
+    }
+}
+
+/// Hands equipped gear's modifiers and resistances to whoever has it on,
+/// replacing whatever their previous gear gave them. Gear restored on login
+/// isn't ready until its prototype is spawned, so that counts as a change too.
+pub fn apply_equipment(
+    mut characters: Query<(Entity, &mut Equipment, &mut Modifiers, &mut Stats)>,
+    ready: Query<&Parent, Added<Equippable>>,
+    gear: Query<&Equippable>,
+    items: Query<(), With<Item>>,
+) {
+    let ready = ready.iter().map(|parent| parent.get()).collect::<Vec<_>>();
+
+    for (entity, mut equipment, mut modifiers, mut stats) in characters.iter_mut() {
+        if !equipment.is_changed() && !ready.contains(&entity) {
+            continue;
+        }
+
+        // Gear that's been despawned out from under its wearer isn't worn.
+        let gone = equipment
+            .0
+            .iter()
+            .filter(|(_, item)| !items.contains(**item))
+            .map(|(slot, _)| *slot)
+            .collect::<Vec<_>>();
+
+        for slot in gone {
+            equipment.0.remove(&slot);
+        }
+
+        // Until restored gear is spawned, its saved modifiers stand in for it,
+        // so health and vigor aren't clamped down in the meantime.
+        let pending = equipment
+            .0
+            .iter()
+            .filter(|(_, item)| !gear.contains(**item))
+            .map(|(slot, _)| slot.modifier_prefix())
+            .collect::<Vec<_>>();
+
+        modifiers.0.retain(|id, _| {
+            !id.starts_with(GEAR_MODIFIER) || pending.iter().any(|slot| id.starts_with(slot))
+        });
+
+        let mut resistance = HashMap::new();
+
+        for (slot, item) in equipment.0.iter() {
+            let Ok(equippable) = gear.get(*item) else {
+                continue;
+            };
+
+            for (index, (stat, amount)) in equippable.modifiers.iter().enumerate() {
+                modifiers.0.insert(
+                    slot.modifier_id(index),
+                    Modifier::flat(stat.clone(), *amount),
+                );
+            }
+
+            for (id, amount) in equippable.resistances.iter() {
+                *resistance.entry(id.clone()).or_default() += amount;
+            }
+        }
+
+        stats.gear_resistance = Resistance(resistance);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        data::resources::Stat,
+        items::{
+            bundles::CorpseBundle,
+            components::{LootRights, Slot},
+            plugin::PersistItemState,
+        },
+        test::{
+            app_builder::AppBuilder,
+            item_builder::ItemBuilder,
+            player_builder::PlayerBuilder,
+            tile_builder::{TileBuilder, ZoneBuilder},
+            utils::get_message_content,
+        },
+        values::LITTER_CLEANUP_TIMER,
+    };
+
+    #[test]
+    fn corpses_decay() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, decay_corpses);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (_, client_id, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+
+        let corpse = app
+            .world
+            .spawn(CorpseBundle::hostile("Goat", "goat", None, 300.0))
+            .set_parent(tile)
+            .id();
+
+        let pelt = ItemBuilder::new().build(&mut app);
+        app.world.entity_mut(pelt).set_parent(corpse);
+
+        app.world
+            .get_mut::<Corpse>(corpse)
+            .unwrap()
+            .decay
+            .set_elapsed(Duration::from_secs(300));
+
+        app.update();
+
+        assert!(app.world.get_entity(corpse).is_none());
+        assert!(app.world.get_entity(pelt).is_none());
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "The goat corpse rots away."
+        );
+    }
+
+    #[test]
+    fn litter_is_cleaned_up() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, clean_up_litter);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let rock = ItemBuilder::new().tile(tile).build(&mut app);
+        let vase = ItemBuilder::new().tile(tile).build(&mut app);
+
+        let mut loose = Dropped::loose();
+        loose
+            .cleanup
+            .as_mut()
+            .unwrap()
+            .set_elapsed(Duration::from_secs_f32(LITTER_CLEANUP_TIMER.unwrap()));
+
+        app.world.entity_mut(rock).insert(loose);
+        app.world.entity_mut(vase).insert(Dropped::placed());
+
+        app.update();
+
+        assert!(app.world.get_entity(rock).is_none());
+        assert!(app.world.get_entity(vase).is_some());
+    }
+
+    #[test]
+    fn player_corpses_stay_theirs() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, decay_corpses);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let corpse = app
+            .world
+            .spawn(CorpseBundle::player("Astrid", 1, 600.0))
+            .set_parent(tile)
+            .id();
+
+        app.world
+            .get_mut::<Corpse>(corpse)
+            .unwrap()
+            .decay
+            .set_elapsed(Duration::from_secs(599));
+
+        app.update();
+
+        let corpse = app.world.get::<Corpse>(corpse).unwrap();
+
+        assert_eq!(corpse.rights, LootRights::Owner(1));
+    }
+
+    #[test]
+    fn equipment_grants_modifiers_and_resistances() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, apply_equipment);
+
+        let (player, _, _) = PlayerBuilder::new().build(&mut app);
+
+        let cap = ItemBuilder::new()
+            .equippable(Slot::Head)
+            .modifier(Stat::Vitality, 2.0)
+            .resistance("armor", 3)
+            .build(&mut app);
+
+        app.world.entity_mut(player).add_child(cap);
+        app.world
+            .get_mut::<Equipment>(player)
+            .unwrap()
+            .0
+            .insert(Slot::Head, cap);
+
+        app.update();
+
+        let modifier = app
+            .world
+            .get::<Modifiers>(player)
+            .unwrap()
+            .0
+            .get(&Slot::Head.modifier_id(0))
+            .cloned()
+            .unwrap();
+
+        assert_eq!(modifier, Modifier::flat(Stat::Vitality, 2.0));
+        assert_eq!(
+            app.world
+                .get::<Stats>(player)
+                .unwrap()
+                .gear_resistance
+                .0
+                .get("armor"),
+            Some(&3)
+        );
+
+        app.world.get_mut::<Equipment>(player).unwrap().0.clear();
+        app.update();
+
+        assert!(app.world.get::<Modifiers>(player).unwrap().0.is_empty());
+        assert!(app
+            .world
+            .get::<Stats>(player)
+            .unwrap()
+            .gear_resistance
+            .0
+            .is_empty());
+    }
+
+    #[test]
+    fn missing_gear_only_drops_its_slot() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, apply_equipment);
+
+        let (player, _, _) = PlayerBuilder::new().build(&mut app);
+
+        let cap = ItemBuilder::new()
+            .equippable(Slot::Head)
+            .modifier(Stat::Vitality, 2.0)
+            .build(&mut app);
+
+        let gloves = ItemBuilder::new()
+            .equippable(Slot::Hands)
+            .modifier(Stat::Strength, 1.0)
+            .build(&mut app);
+
+        app.world.entity_mut(player).push_children(&[cap, gloves]);
+        app.world
+            .get_mut::<Equipment>(player)
+            .unwrap()
+            .0
+            .extend([(Slot::Head, cap), (Slot::Hands, gloves)]);
+
+        app.update();
+
+        app.world.entity_mut(gloves).despawn();
+        app.world
+            .get_mut::<Equipment>(player)
+            .unwrap()
+            .set_changed();
+        app.update();
+
+        let modifiers = &app.world.get::<Modifiers>(player).unwrap().0;
+
+        assert!(modifiers.contains_key(&Slot::Head.modifier_id(0)));
+        assert!(!modifiers.contains_key(&Slot::Hands.modifier_id(0)));
+        assert!(!app
+            .world
+            .get::<Equipment>(player)
+            .unwrap()
+            .0
+            .contains_key(&Slot::Hands));
+    }
+
+    #[test]
+    fn pending_gear_keeps_saved_modifiers() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, apply_equipment);
+
+        let (player, _, _) = PlayerBuilder::new().build(&mut app);
+
+        let cap = ItemBuilder::new()
+            .equippable(Slot::Head)
+            .modifier(Stat::Vitality, 2.0)
+            .build(&mut app);
+
+        let restored = ItemBuilder::new().build(&mut app);
+
+        app.world.entity_mut(player).push_children(&[cap, restored]);
+        app.world.get_mut::<Modifiers>(player).unwrap().0.insert(
+            Slot::Hands.modifier_id(0),
+            Modifier::flat(Stat::Strength, 1.0),
+        );
+        app.world
+            .get_mut::<Equipment>(player)
+            .unwrap()
+            .0
+            .extend([(Slot::Head, cap), (Slot::Hands, restored)]);
+
+        app.update();
+
+        let modifiers = &app.world.get::<Modifiers>(player).unwrap().0;
+
+        assert!(modifiers.contains_key(&Slot::Head.modifier_id(0)));
+        assert_eq!(
+            modifiers.get(&Slot::Hands.modifier_id(0)),
+            Some(&Modifier::flat(Stat::Strength, 1.0))
+        );
+    }
+
+    #[derive(Component, Reflect, Debug, PartialEq)]
+    struct Charges(u32);
+
+    #[test]
+    fn item_state_restores_over_prototype() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, assign_item_ids);
+        app.persist_item_state::<Charges>();
+
+        let wand = ItemBuilder::new().build(&mut app);
+        app.world.entity_mut(wand).insert(Charges(3));
+
+        app.update();
+
+        assert!(app.world.get::<ItemId>(wand).is_some());
+
+        let state = app.world.get::<ItemState>(wand).cloned().unwrap();
+
+        let respawned = ItemBuilder::new().build(&mut app);
+        app.world.entity_mut(respawned).insert(Charges(10));
+
+        let registry = app.world.resource::<AppTypeRegistry>().clone();
+        state.restore(&mut app.world.entity_mut(respawned), &registry.read());
+
+        assert_eq!(app.world.get::<Charges>(respawned), Some(&Charges(3)));
+    }
+}
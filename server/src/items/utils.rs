@@ -0,0 +1,345 @@
+use std::sync::OnceLock;
+
+use bevy::{ecs::query::WorldQuery, prelude::*};
+use inflector::string::pluralize::to_plural;
+use regex::Regex;
+
+use crate::{interact::components::Interactions, visual::components::Depiction};
+
+use super::components::{Container, Item, ItemState, Stack, Surface};
+
+#[derive(WorldQuery)]
+pub struct HolderQuery {
+    pub entity: Entity,
+    pub item: &'static Item,
+    pub depiction: &'static Depiction,
+    pub children: Option<&'static Children>,
+    pub container: Option<&'static Container>,
+    pub surface: Option<&'static Surface>,
+}
+
+impl HolderQueryItem<'_> {
+    /// How much the item can hold, if things can be put in or on it at all.
+    pub fn capacity(&self) -> Option<u8> {
+        self.container
+            .map(|container| container.capacity)
+            .or(self.surface.map(|surface| surface.capacity))
+    }
+
+    /// How much of its capacity is taken by what's already in it.
+    pub fn used(&self, holders: &Query<HolderQuery>) -> u8 {
+        self.children
+            .iter()
+            .flat_map(|children| children.iter())
+            .filter_map(|child| holders.get(*child).ok())
+            .map(|item| item.item.size.value())
+            .sum()
+    }
+
+    /// How something is put into it, such as "in" or "on".
+    pub fn preposition(&self) -> String {
+        match self.surface {
+            Some(surface) if self.container.is_none() => surface.kind.to_string(),
+            _ => "in".into(),
+        }
+    }
+}
+
+static PATH_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Splits a path like `coin in pouch in backpack` into its names, outermost
+/// first.
+pub fn split_path(path: &str) -> Vec<&str> {
+    let regex = PATH_REGEX.get_or_init(|| Regex::new(r"\s+(in|on|from)\s+").unwrap());
+
+    let mut names = regex
+        .split(path.trim())
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+
+    names.reverse();
+    names
+}
+
+/// Follows the names of a split path from `roots` through whatever holds
+/// each step, returning every item along the way, outermost first.
+pub fn find_path(
+    names: &[&str],
+    roots: impl IntoIterator<Item = Entity>,
+    holders: &Query<HolderQuery>,
+) -> Option<Vec<Entity>> {
+    let mut candidates = roots.into_iter().collect::<Vec<_>>();
+    let mut found = Vec::with_capacity(names.len());
+
+    for (index, name) in names.iter().enumerate() {
+        let last = index == names.len() - 1;
+
+        let item = candidates
+            .iter()
+            .filter_map(|candidate| holders.get(*candidate).ok())
+            .filter(|item| last || item.capacity().is_some())
+            .find(|item| item.depiction.matches_query(&item.entity, name))?;
+
+        found.push(item.entity);
+
+        candidates = item
+            .children
+            .map(|children| children.iter().copied().collect())
+            .unwrap_or_default();
+    }
+
+    (!found.is_empty()).then_some(found)
+}
+
+static COUNT_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Splits a count off the front of an item argument, as in `5 arrows` or
+/// `2.coin`.
+pub fn split_count(target: &str) -> (Option<u32>, &str) {
+    let regex = COUNT_REGEX.get_or_init(|| Regex::new(r"^(\d+)(\.|\s+)(.+)$").unwrap());
+
+    regex
+        .captures(target.trim())
+        .and_then(|captures| {
+            let count = captures.get(1)?.as_str().parse().ok()?;
+            let name = captures.get(3)?.as_str().trim();
+
+            Some((Some(count), name))
+        })
+        .unwrap_or((None, target.trim()))
+}
+
+/// How many of the item there are, which is one unless it's a stack.
+pub fn quantity(stack: Option<&Stack>) -> u32 {
+    stack.map_or(1, |stack| stack.quantity)
+}
+
+/// The name with the quantity in front when the item is a stack of more
+/// than one, as in `5 arrows`.
+pub fn quantified(name: &str, stack: Option<&Stack>) -> String {
+    match stack {
+        Some(stack) if stack.quantity > 1 => format!("{} {}", stack.quantity, to_plural(name)),
+        _ => name.to_string(),
+    }
+}
+
+#[derive(WorldQuery)]
+pub struct StackQuery {
+    pub entity: Entity,
+    pub depiction: &'static Depiction,
+    pub stack: Option<&'static Stack>,
+}
+
+/// What was moved by `move_items`.
+#[derive(Debug, Default)]
+pub struct Moved {
+    /// The name and quantity of each item moved, for messages.
+    pub names: Vec<(String, u32)>,
+    /// Items now in the destination that weren't before, whether moved
+    /// whole or split off a stack. Those merged into a stack aren't listed.
+    pub arrived: Vec<Entity>,
+}
+
+/// A stack in the destination that others can merge into.
+struct Pile {
+    entity: Entity,
+    name: String,
+    stack: Stack,
+}
+
+/// Moves `count` of the found items into `destination`, or all of them
+/// without a count. Stacks are split when only part of one is moved, and
+/// merged into stacks of the same name that are already there, the
+/// destination's `present` children.
+pub fn move_items(
+    bevy: &mut Commands,
+    found: &[Entity],
+    count: Option<u32>,
+    destination: Entity,
+    present: &[Entity],
+    items: &Query<StackQuery>,
+) -> Moved {
+    let mut moved = Moved::default();
+    let mut remaining = count.unwrap_or(u32::MAX);
+
+    let mut piles = present
+        .iter()
+        .filter_map(|entity| items.get(*entity).ok())
+        .filter_map(|item| {
+            item.stack.map(|stack| Pile {
+                entity: item.entity,
+                name: item.depiction.name.clone(),
+                stack: *stack,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut changed = Vec::new();
+
+    for item in found.iter().filter_map(|entity| items.get(*entity).ok()) {
+        if remaining == 0 {
+            break;
+        }
+
+        let Some(stack) = item.stack else {
+            bevy.entity(item.entity).set_parent(destination);
+
+            moved.names.push((item.depiction.name.clone(), 1));
+            moved.arrived.push(item.entity);
+            remaining -= 1;
+
+            continue;
+        };
+
+        let moving = stack.quantity.min(remaining);
+        let mut left = moving;
+
+        remaining -= moving;
+        moved.names.push((item.depiction.name.clone(), moving));
+
+        for pile in piles
+            .iter_mut()
+            .filter(|pile| pile.entity != item.entity && pile.name == item.depiction.name)
+        {
+            let merging = pile.stack.room().min(left);
+
+            if merging > 0 {
+                pile.stack.quantity += merging;
+                left -= merging;
+
+                changed.push(pile.entity);
+            }
+        }
+
+        let stays = stack.quantity - moving;
+
+        let arrived = match (stays, left) {
+            (0, 0) => {
+                bevy.entity(item.entity).despawn_recursive();
+
+                None
+            }
+            (0, left) => {
+                bevy.entity(item.entity)
+                    .insert(Stack {
+                        quantity: left,
+                        ..*stack
+                    })
+                    .set_parent(destination);
+
+                Some(item.entity)
+            }
+            (stays, 0) => {
+                bevy.entity(item.entity).insert(Stack {
+                    quantity: stays,
+                    ..*stack
+                });
+
+                None
+            }
+            (stays, left) => {
+                bevy.entity(item.entity).insert(Stack {
+                    quantity: stays,
+                    ..*stack
+                });
+
+                let split = bevy.spawn_empty().id();
+
+                bevy.add(split_stack(item.entity, split, left));
+                bevy.entity(split).set_parent(destination);
+
+                Some(split)
+            }
+        };
+
+        if let Some(entity) = arrived {
+            moved.arrived.push(entity);
+
+            piles.push(Pile {
+                entity,
+                name: item.depiction.name.clone(),
+                stack: Stack {
+                    quantity: left,
+                    ..*stack
+                },
+            });
+        }
+    }
+
+    for pile in piles.iter().filter(|pile| changed.contains(&pile.entity)) {
+        bevy.entity(pile.entity).insert(pile.stack);
+    }
+
+    moved
+}
+
+/// Copies a stack's item into `into`, an entity reserved for it, holding
+/// `quantity` of them. Stacks are plain items, so copying what a plain item
+/// is made of is enough; its id is left to be assigned anew.
+fn split_stack(from: Entity, into: Entity, quantity: u32) -> impl FnOnce(&mut World) {
+    move |world: &mut World| {
+        let Some(source) = world.get_entity(from) else {
+            return;
+        };
+
+        let item = source.get::<Item>().cloned();
+        let depiction = source.get::<Depiction>().cloned();
+        let interactions = source.get::<Interactions>().cloned();
+        let name = source.get::<Name>().cloned();
+        let state = source.get::<ItemState>().cloned();
+        let stack = source.get::<Stack>().copied();
+
+        let Some(mut split) = world.get_entity_mut(into) else {
+            return;
+        };
+
+        if let Some(item) = item {
+            split.insert(item);
+        }
+
+        if let Some(depiction) = depiction {
+            split.insert(depiction);
+        }
+
+        if let Some(interactions) = interactions {
+            split.insert(interactions);
+        }
+
+        if let Some(name) = name {
+            split.insert(name);
+        }
+
+        if let Some(state) = state {
+            split.insert(state);
+        }
+
+        if let Some(stack) = stack {
+            split.insert(Stack { quantity, ..stack });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_outermost_first() {
+        assert_eq!(
+            split_path("coin in pouch in backpack"),
+            vec!["backpack", "pouch", "coin"]
+        );
+
+        assert_eq!(split_path("plate on table"), vec!["table", "plate"]);
+        assert_eq!(split_path("rock"), vec!["rock"]);
+    }
+
+    #[test]
+    fn splits_counts() {
+        assert_eq!(split_count("5 arrows"), (Some(5), "arrows"));
+        assert_eq!(split_count("2.coin"), (Some(2), "coin"));
+        assert_eq!(split_count("coin"), (None, "coin"));
+        assert_eq!(split_count("#12"), (None, "#12"));
+    }
+}
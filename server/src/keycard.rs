@@ -3,9 +3,10 @@ use bevy::prelude::*;
 pub const SHUTDOWN: u32 = 1 << 2;
 pub const ANNOUNCE: u32 = 1 << 3;
 pub const TELEPORT: u32 = 1 << 4;
+pub const RELOAD: u32 = 1 << 5;
 
 const PLAYER: u32 = 0;
-const ADMIN: u32 = PLAYER | SHUTDOWN | ANNOUNCE | TELEPORT;
+const ADMIN: u32 = PLAYER | SHUTDOWN | ANNOUNCE | TELEPORT | RELOAD;
 
 #[derive(Component)]
 pub struct Keycard {
@@ -39,6 +40,7 @@ mod tests {
         assert!(keycard.can(SHUTDOWN));
         assert!(keycard.can(ANNOUNCE));
         assert!(keycard.can(TELEPORT));
+        assert!(keycard.can(RELOAD));
     }
 
     #[test]
@@ -48,5 +50,6 @@ mod tests {
         assert!(!keycard.can(SHUTDOWN));
         assert!(!keycard.can(ANNOUNCE));
         assert!(!keycard.can(TELEPORT));
+        assert!(!keycard.can(RELOAD));
     }
 }
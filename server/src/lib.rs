@@ -0,0 +1,20 @@
+pub mod auth;
+pub mod combat;
+pub mod data;
+pub mod db;
+pub mod input;
+pub mod interact;
+pub mod items;
+pub mod keycard;
+pub mod lua;
+pub mod menu;
+pub mod net;
+pub mod npc;
+pub mod party;
+pub mod player;
+pub mod social;
+pub mod spatial;
+mod test;
+pub mod values;
+pub mod visual;
+pub mod world;
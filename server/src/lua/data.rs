@@ -6,6 +6,7 @@ use crate::{
         Approach, Attributes, Defense, Distance, Offense, Resistance, Stats, Status,
     },
     data::resources::Stat,
+    items::components::Weapon,
 };
 
 use super::events::Action;
@@ -46,17 +47,21 @@ impl<'a> FromLua<'a> for Action {
 impl UserData for Stats {
     fn add_fields<'lua, F: UserDataFields<'lua, Self>>(fields: &mut F) {
         fields.add_field_method_get("level", |_, stats| Ok(stats.level));
-        fields.add_field_method_get("attributes", |_, stats| Ok(stats.attributes.clone()));
+        fields.add_field_method_get("attributes", |_, stats| Ok(stats.effective_attributes()));
         fields.add_field_method_get("status", |_, stats| Ok(stats.status.clone()));
-        fields.add_field_method_get("defense", |_, stats| Ok(stats.defense.clone()));
-        fields.add_field_method_get("offense", |_, stats| Ok(stats.offense.clone()));
+        fields.add_field_method_get("defense", |_, stats| Ok(stats.effective_defense()));
+        fields.add_field_method_get("offense", |_, stats| Ok(stats.effective_offense()));
         fields.add_field_method_get("resistance", |_, stats| Ok(stats.resistance.clone()));
     }
 
     fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
-        methods.add_method("auto_attack_damage", |_, stats, _: ()| {
-            Ok(stats.auto_attack_damage())
-        })
+        methods.add_method(
+            "auto_attack_damage",
+            |lua, stats, weapon: Option<Weapon>| match weapon {
+                Some(weapon) => Ok(stats.attack_damage(roll_damage(lua, &weapon)?)),
+                None => Ok(stats.auto_attack_damage()),
+            },
+        )
     }
 }
 
@@ -115,6 +120,41 @@ impl UserData for Resistance {
     }
 }
 
+impl UserData for Weapon {
+    fn add_fields<'lua, F: UserDataFields<'lua, Self>>(fields: &mut F) {
+        fields.add_field_method_get("min_damage", |_, weapon| Ok(weapon.min_damage));
+        fields.add_field_method_get("max_damage", |_, weapon| Ok(weapon.max_damage));
+        fields.add_field_method_get("kind", |_, weapon| Ok(weapon.kind.clone()));
+        fields.add_field_method_get("speed", |_, weapon| Ok(weapon.speed));
+    }
+
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("roll", |lua, weapon, _: ()| roll_damage(lua, weapon))
+    }
+}
+
+impl<'a> FromLua<'a> for Weapon {
+    fn from_lua(value: LuaValue<'a>, _: &'a Lua) -> LuaResult<Self> {
+        match value {
+            Value::UserData(data) => data.borrow::<Self>().map(|weapon| weapon.clone()),
+            _ => Err(mlua::Error::FromLuaConversionError {
+                from: value.type_name(),
+                to: "Weapon",
+                message: Some("expected Weapon".into()),
+            }),
+        }
+    }
+}
+
+/// Rolls within the weapon's damage range using Lua's seeded `math.random`,
+/// so scripted fights stay repeatable.
+fn roll_damage(lua: &Lua, weapon: &Weapon) -> LuaResult<u32> {
+    let math: LuaTable = lua.globals().get("math")?;
+
+    math.get::<_, LuaFunction>("random")?
+        .call((weapon.min_damage, weapon.max_damage.max(weapon.min_damage)))
+}
+
 impl UserData for Stat {}
 
 impl<'a> FromLua<'a> for Stat {
@@ -4,8 +4,8 @@ use bevy::prelude::*;
 use uuid::Uuid;
 
 use crate::combat::events::{
-    AddStatModifier, ApplyCondition, ApplyDamage, CombatLog, RemoveStatModifier, SetApproach,
-    SetDistance,
+    AddStatModifier, ApplyCondition, ApplyDamage, ApplyHeal, CombatLog, Dispel, RemoveStatModifier,
+    SetApproach, SetDistance, Taunt,
 };
 
 use super::context::ExecutionContext;
@@ -17,7 +17,7 @@ pub struct ExecutionEvent {
     pub phase: ExecutionPhase,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 #[allow(clippy::enum_variant_names)]
 pub enum ExecutionPhase {
     OnInit,
@@ -26,7 +26,14 @@ pub enum ExecutionPhase {
     OnDodge,
     OnBlock,
     OnHit,
+    OnTick,
     OnEnd,
+    /// Run for a defender who has just blocked an attack.
+    OnBlockReaction,
+    /// Run for a defender who has just dodged an attack.
+    OnDodgeReaction,
+    /// Run for a defender who has just taken damage.
+    OnHitReaction,
 }
 
 impl Display for ExecutionPhase {
@@ -38,7 +45,11 @@ impl Display for ExecutionPhase {
             Self::OnDodge => write!(f, "on_dodge"),
             Self::OnBlock => write!(f, "on_block"),
             Self::OnHit => write!(f, "on_hit"),
+            Self::OnTick => write!(f, "on_tick"),
             Self::OnEnd => write!(f, "on_end"),
+            Self::OnBlockReaction => write!(f, "on_block_reaction"),
+            Self::OnDodgeReaction => write!(f, "on_dodge_reaction"),
+            Self::OnHitReaction => write!(f, "on_hit_reaction"),
         }
     }
 }
@@ -57,9 +68,12 @@ pub struct ProcessEvent {
 #[derive(Clone, Debug)]
 pub enum Action {
     ApplyDamage(ApplyDamage),
+    ApplyHeal(ApplyHeal),
+    Taunt(Taunt),
     SetDistance(SetDistance),
     SetApproach(SetApproach),
     ApplyCondition(ApplyCondition),
+    Dispel(Dispel),
     AddStatModifier(AddStatModifier),
     RemoveStatModifier(RemoveStatModifier),
     CombatLog(CombatLog),
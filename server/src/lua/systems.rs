@@ -3,22 +3,25 @@ use bevy::{asset::FileAssetIo, prelude::*};
 use bevy_mod_sysfail::sysfail;
 use bevy_nest::prelude::*;
 use mlua::{prelude::*, Function, Table, Value};
+use rand::Rng;
 use strum::IntoEnumIterator;
 use uuid::Uuid;
 use walkdir::WalkDir;
 
 use crate::{
     combat::{
-        components::{Approach, CombatState, Distance, Stats},
+        components::{Approach, CombatState, Conditions, Distance, Stats},
         events::{
-            AddStatModifier, ApplyCondition, ApplyDamage, Blocked, CombatEvent, CombatEventKind,
-            CombatEventTrigger, CombatLog, CombatLogKind, ConditionApplied, ConditionRemoved,
-            Damaged, Dodged, Missed, RemoveStatModifier, SetApproach, SetDistance, Used,
-            WithCallback,
+            AddStatModifier, ApplyCondition, ApplyDamage, ApplyHeal, Blocked, CombatEvent,
+            CombatEventKind, CombatEventTrigger, CombatLog, CombatLogKind, ConditionApplied,
+            ConditionRemoved, Damaged, Dispel, Dodged, Missed, RemoveStatModifier, SetApproach,
+            SetDistance, Taunt, Used, WithCallback,
         },
     },
-    data::resources::Stat,
+    data::resources::{ModifierKind, Stat},
+    items::components::{Equipment, Weapon},
     player::components::Client,
+    world::resources::GameRng,
 };
 
 use super::{
@@ -52,6 +55,15 @@ pub fn load_scripts(mut scripts: ResMut<Scripts>) {
     }
 }
 
+/// Reseeds Lua's `math.random` from the game's RNG, so scripts roll the same
+/// way for the same seed.
+fn seed_math_random(lua: &Lua, rng: &mut GameRng) -> mlua::Result<()> {
+    let math: Table = lua.globals().get("math")?;
+
+    math.get::<_, Function>("randomseed")?
+        .call::<_, ()>(rng.gen::<i64>())
+}
+
 #[sysfail(log)]
 pub fn execute_scripts(
     mut executions: EventReader<ExecutionEvent>,
@@ -59,7 +71,10 @@ pub fn execute_scripts(
     scripts: Res<Scripts>,
     stats: Query<&Stats>,
     combat_states: Query<&CombatState>,
+    conditions: Query<&Conditions>,
+    gear: (Query<&Equipment>, Query<&Weapon>),
     lua: NonSend<Lua>,
+    mut rng: ResMut<GameRng>,
 ) -> Result<(), anyhow::Error> {
     for event in executions.iter() {
         for key in &event.scripts {
@@ -94,11 +109,26 @@ pub fn execute_scripts(
                 )?,
             )?;
 
+            action.set(
+                "apply_heal",
+                apply_heal_func(&lua, event.context.sandbox_id.to_string())?,
+            )?;
+
+            action.set(
+                "taunt",
+                taunt_func(&lua, event.context.sandbox_id.to_string())?,
+            )?;
+
             action.set(
                 "apply_condition",
                 apply_condition_func(&lua, event.context.sandbox_id.to_string())?,
             )?;
 
+            action.set(
+                "dispel",
+                dispel_func(&lua, event.context.sandbox_id.to_string())?,
+            )?;
+
             action.set(
                 "set_distance",
                 set_distance_func(&lua, event.context.sandbox_id.to_string())?,
@@ -133,14 +163,22 @@ pub fn execute_scripts(
 
             var.set(
                 "source",
-                combat_entity_var(&lua, event.context.source, &stats, &combat_states)?,
+                combat_entity_var(&lua, event.context.source, &stats, &combat_states, &gear)?,
             )?;
 
             var.set(
                 "target",
-                combat_entity_var(&lua, event.context.target, &stats, &combat_states)?,
+                combat_entity_var(&lua, event.context.target, &stats, &combat_states, &gear)?,
             )?;
 
+            if let ExecutionKind::Condition(condition) = &event.context.kind {
+                let stacks = conditions
+                    .get(event.context.target)
+                    .map_or(0, |conditions| conditions.stacks(&condition.id));
+
+                var.set("stacks", stacks)?;
+            }
+
             var.set("stat", stat_var(&lua)?)?;
             var.set("distance", distance_var(&lua)?)?;
             var.set("approach", approach_var(&lua)?)?;
@@ -150,6 +188,8 @@ pub fn execute_scripts(
                 .get(key)
                 .with_context(|| format!("Failed to find script: {}", key))?;
 
+            seed_math_random(&lua, &mut rng)?;
+
             let result: Table = lua.load(script.as_str()).set_environment(sandbox).eval()?;
 
             if let Some(phase) = result.get::<_, Option<LuaFunction>>(event.phase.to_string())? {
@@ -199,9 +239,11 @@ fn apply_damage_func(
                 target: target.0,
                 kind,
                 damage,
-                with_callback: after.map(|uuid| WithCallback {
-                    context: context.clone(),
-                    callback_id: uuid,
+                with_callback: after.map(|uuid| {
+                    Box::new(WithCallback {
+                        context: context.clone(),
+                        callback_id: uuid,
+                    })
                 }),
             }),
         )?;
@@ -212,6 +254,47 @@ fn apply_damage_func(
     Ok(func)
 }
 
+fn apply_heal_func(lua: &Lua, sandbox_id: String) -> mlua::Result<Function> {
+    let func = lua.create_function(move |ctx, args: Table| {
+        let sandboxes: Table = ctx.globals().get::<_, Table>("sandboxes")?;
+        let sandbox: Table = sandboxes.get::<_, Table>(sandbox_id.clone())?;
+
+        let target = args.get::<_, LuaEntity>("target")?;
+        let amount = args.get::<_, f32>("amount")?;
+
+        let events: Table = sandbox.get("events")?;
+
+        events.set(
+            events.len()? + 1,
+            Action::ApplyHeal(ApplyHeal {
+                target: target.0,
+                amount,
+            }),
+        )?;
+
+        Ok(())
+    })?;
+
+    Ok(func)
+}
+
+fn taunt_func(lua: &Lua, sandbox_id: String) -> mlua::Result<Function> {
+    let func = lua.create_function(move |ctx, args: Table| {
+        let sandboxes: Table = ctx.globals().get::<_, Table>("sandboxes")?;
+        let sandbox: Table = sandboxes.get::<_, Table>(sandbox_id.clone())?;
+
+        let target = args.get::<_, LuaEntity>("target")?;
+
+        let events: Table = sandbox.get("events")?;
+
+        events.set(events.len()? + 1, Action::Taunt(Taunt { target: target.0 }))?;
+
+        Ok(())
+    })?;
+
+    Ok(func)
+}
+
 fn apply_condition_func(lua: &Lua, sandbox_id: String) -> mlua::Result<Function> {
     let func = lua.create_function(move |ctx, args: Table| {
         let sandboxes: Table = ctx.globals().get::<_, Table>("sandboxes")?;
@@ -238,6 +321,32 @@ fn apply_condition_func(lua: &Lua, sandbox_id: String) -> mlua::Result<Function>
     Ok(func)
 }
 
+fn dispel_func(lua: &Lua, sandbox_id: String) -> mlua::Result<Function<'_>> {
+    let func = lua.create_function(move |ctx, args: Table| {
+        let sandboxes: Table = ctx.globals().get::<_, Table>("sandboxes")?;
+        let sandbox: Table = sandboxes.get::<_, Table>(sandbox_id.clone())?;
+
+        let target = args.get::<_, LuaEntity>("target")?;
+        let id = args.get::<_, Option<String>>("id")?;
+        let tag = args.get::<_, Option<String>>("tag")?;
+
+        let events: Table = sandbox.get("events")?;
+
+        events.set(
+            events.len()? + 1,
+            Action::Dispel(Dispel {
+                target: target.0,
+                id,
+                tag,
+            }),
+        )?;
+
+        Ok(())
+    })?;
+
+    Ok(func)
+}
+
 fn set_distance_func(lua: &Lua, sandbox_id: String) -> mlua::Result<Function> {
     let func = lua.create_function(move |ctx, args: Table| {
         let sandboxes: Table = ctx.globals().get::<_, Table>("sandboxes")?;
@@ -294,6 +403,7 @@ fn add_stat_modifier_func(lua: &Lua, sandbox_id: String) -> mlua::Result<Functio
         let target = args.get::<_, LuaEntity>("target")?;
         let stat = args.get::<_, Stat>("stat")?;
         let amount = args.get::<_, f32>("amount")?;
+        let percent = args.get::<_, Option<bool>>("percent")?.unwrap_or(false);
 
         let events: Table = sandbox.get("events")?;
         let uuid = Uuid::new_v4();
@@ -305,6 +415,11 @@ fn add_stat_modifier_func(lua: &Lua, sandbox_id: String) -> mlua::Result<Functio
                 id: uuid.to_string(),
                 stat,
                 amount,
+                kind: if percent {
+                    ModifierKind::Percent
+                } else {
+                    ModifierKind::Flat
+                },
             }),
         )?;
 
@@ -512,13 +627,28 @@ fn combat_entity_var<'a>(
     entity: Entity,
     stats: &Query<&Stats>,
     combat_states: &Query<&CombatState>,
+    (equipment, weapons): &(Query<&Equipment>, Query<&Weapon>),
 ) -> Result<Table<'a>, anyhow::Error> {
     let table = lua.create_table()?;
 
     table.set("entity", LuaEntity(entity))?;
     table.set("stats", stats.get(entity)?.clone())?;
-    table.set("distance", combat_states.get(entity)?.distance)?;
-    table.set("approach", combat_states.get(entity)?.approach)?;
+
+    // Unarmed fighters and hostiles have no weapon, leaving it nil.
+    table.set(
+        "weapon",
+        equipment
+            .get(entity)
+            .ok()
+            .and_then(|equipment| equipment.weapon(weapons))
+            .cloned(),
+    )?;
+
+    // Conditions can outlast a fight, so there may be no combat state.
+    if let Ok(combat_state) = combat_states.get(entity) {
+        table.set("distance", combat_state.distance)?;
+        table.set("approach", combat_state.approach)?;
+    }
 
     Ok(table)
 }
@@ -587,9 +717,12 @@ pub fn handle_combat_event_action(
             source: action.context.source,
             kind: match &action.action {
                 Action::ApplyDamage(args) => CombatEventKind::ApplyDamage(args.clone()),
+                Action::ApplyHeal(args) => CombatEventKind::ApplyHeal(args.clone()),
+                Action::Taunt(args) => CombatEventKind::Taunt(args.clone()),
                 Action::SetDistance(args) => CombatEventKind::SetDistance(args.clone()),
                 Action::SetApproach(args) => CombatEventKind::SetApproach(args.clone()),
                 Action::ApplyCondition(args) => CombatEventKind::ApplyCondition(args.clone()),
+                Action::Dispel(args) => CombatEventKind::Dispel(args.clone()),
                 Action::AddStatModifier(args) => CombatEventKind::AddStatModifier(args.clone()),
                 Action::RemoveStatModifier(args) => {
                     CombatEventKind::RemoveStatModifier(args.clone())
@@ -628,6 +761,7 @@ pub fn on_apply_damage_response(
     lua: NonSend<Lua>,
     mut responses: EventReader<ApplyDamageResponse>,
     mut process: EventWriter<ProcessEvent>,
+    mut rng: ResMut<GameRng>,
 ) -> Result<(), anyhow::Error> {
     for response in responses.iter() {
         let globals = lua.globals();
@@ -636,6 +770,8 @@ pub fn on_apply_damage_response(
         let callbacks: Table = sandbox.get("callbacks")?;
         let callback: Function = callbacks.get(response.callback_id.to_string())?;
 
+        seed_math_random(&lua, &mut rng)?;
+
         callback.call::<_, ()>((response.damage, response.kind.clone(), response.crit))?;
 
         process.send(ProcessEvent {
@@ -645,3 +781,22 @@ pub fn on_apply_damage_response(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn math_random_follows_seed() {
+        let roll = || {
+            let lua = Lua::new();
+            seed_math_random(&lua, &mut GameRng::seeded(7)).unwrap();
+
+            lua.load("return math.random(1, 1000000)")
+                .eval::<i64>()
+                .unwrap()
+        };
+
+        assert_eq!(roll(), roll());
+    }
+}
@@ -1,23 +1,3 @@
-mod auth;
-mod combat;
-mod data;
-mod db;
-mod input;
-mod interact;
-mod items;
-mod keycard;
-mod lua;
-mod menu;
-mod net;
-mod npc;
-mod player;
-mod social;
-mod spatial;
-mod test;
-mod values;
-mod visual;
-mod world;
-
 use std::{env, time::Duration};
 
 use bevy::{
@@ -32,13 +12,13 @@ use bevy_proto::prelude::*;
 use dotenvy::dotenv;
 use sqlx::{migrate, postgres::PgPoolOptions};
 
-use crate::{
+use server::{
     auth::plugin::AuthPlugin, combat::plugin::CombatPlugin, data::plugin::DataPlugin,
     db::pool::DatabasePool, input::plugin::InputPlugin, interact::plugin::InteractPlugin,
     items::plugin::ItemPlugin, lua::plugin::LuaPlugin, menu::plugin::MenuPlugin,
-    net::plugin::NetPlugin, npc::plugin::NpcPlugin, player::plugin::PlayerPlugin,
-    social::plugin::SocialPlugin, spatial::plugin::SpatialPlugin, visual::plugin::VisualPlugin,
-    world::plugin::WorldPlugin,
+    net::plugin::NetPlugin, npc::plugin::NpcPlugin, party::plugin::PartyPlugin,
+    player::plugin::PlayerPlugin, social::plugin::SocialPlugin, spatial::plugin::SpatialPlugin,
+    visual::plugin::VisualPlugin, world::plugin::WorldPlugin, world::resources::GameRng,
 };
 
 fn load_prototypes(mut prototypes: PrototypesMut) {
@@ -63,6 +43,29 @@ fn load_prototypes(mut prototypes: PrototypesMut) {
             error!("Failed to load prototypes: {}", err);
         }
     }
+
+    match prototypes.load_folder("prototypes/items/") {
+        Ok(loaded) => {
+            loaded.iter().for_each(|proto| {
+                info!("Loaded items: {:?}", proto);
+            });
+        }
+        Err(err) => {
+            error!("Failed to load prototypes: {}", err);
+        }
+    }
+}
+
+/// Replays a recorded run from `REPLAY_FILE` if given, otherwise seeds from
+/// `RNG_SEED` or picks a fresh seed.
+fn game_rng() -> GameRng {
+    if let Ok(path) = env::var("REPLAY_FILE") {
+        return GameRng::from_replay(path).expect("Failed to load replay file");
+    }
+
+    env::var("RNG_SEED")
+        .map(|seed| GameRng::seeded(seed.parse().expect("RNG_SEED must be a number")))
+        .unwrap_or_default()
 }
 
 fn setup_network(server: Res<Server>) {
@@ -83,6 +86,7 @@ async fn main() -> Result<(), sqlx::Error> {
     App::new()
         // Resources
         .insert_resource(DatabasePool(pool))
+        .insert_resource(game_rng())
         // Bevy plugins
         .add_plugins((
             AssetPlugin::default(),
@@ -109,6 +113,9 @@ async fn main() -> Result<(), sqlx::Error> {
             MenuPlugin,
             NetPlugin,
             NpcPlugin,
+        ))
+        .add_plugins((
+            PartyPlugin,
             PlayerPlugin,
             SocialPlugin,
             SpatialPlugin,
@@ -9,11 +9,14 @@ use sqlx::{Pool, Postgres};
 
 use crate::{
     auth::components::Authenticating,
-    db::{pool::DatabasePool, utils::store_world_state},
-    items::components::{Inventory, Item},
+    combat::components::{Conditions, Cooldowns, LearnedSkills, Modifiers, Stats},
+    db::{models::WorldSaveModel, pool::DatabasePool, utils::store_world_state},
+    items::components::{Equipment, Inventory},
     player::components::{Character, Client, Online},
     spatial::components::Tile,
-    world::resources::{WorldState, WorldStateCharacter},
+    world::resources::{
+        SavedItems, WorldState, WorldStateCharacter, WorldStateCombat, WorldStateItem,
+    },
 };
 
 use super::telnet::NAWS;
@@ -26,11 +29,22 @@ pub fn on_network_event(
     mut bevy: Commands,
     mut events: EventReader<NetworkEvent>,
     mut outbox: EventWriter<Outbox>,
-    players: Query<(Entity, &Client, &Character, &Parent, &Children), With<Online>>,
+    players: Query<
+        (
+            Entity,
+            &Client,
+            &Character,
+            &Parent,
+            &Children,
+            (&Stats, &Conditions, &Modifiers, &Cooldowns),
+            (&LearnedSkills, &Equipment),
+        ),
+        With<Online>,
+    >,
     database: Res<DatabasePool>,
-    world_state: Res<WorldState>,
+    mut world_state: ResMut<WorldState>,
     inventories: Query<Option<&Children>, With<Inventory>>,
-    items: Query<(Entity, &Name), With<Item>>,
+    items: SavedItems,
     tiles: Query<&Name, With<Tile>>,
 ) -> Result<(), anyhow::Error> {
     for event in events.iter() {
@@ -47,8 +61,15 @@ pub fn on_network_event(
         }
 
         if let NetworkEvent::Disconnected(id) = event {
-            if let Some((entity, _, character, parent, children)) =
-                players.iter().find(|(_, c, _, _, _)| c.id == *id)
+            if let Some((
+                entity,
+                _,
+                character,
+                parent,
+                children,
+                (stats, conditions, modifiers, cooldowns),
+                (learned, equipment),
+            )) = players.iter().find(|(_, c, _, _, _, _, _)| c.id == *id)
             {
                 let tile = tiles
                     .get(parent.get())
@@ -62,25 +83,29 @@ pub fn on_network_event(
                     .context("Inventory not found")?
                     .iter()
                     .flat_map(|children| children.iter())
-                    .filter_map(|child| items.get(*child).ok())
-                    .map(|(_, name)| name.to_string())
+                    .filter_map(|child| WorldStateItem::new(*child, &items))
                     .collect::<Vec<_>>();
 
                 let state = WorldStateCharacter {
                     id: character.id,
                     tile,
                     inventory,
+                    combat: Some(WorldStateCombat::new(
+                        stats, conditions, modifiers, cooldowns,
+                    )),
+                    skills: learned.0.iter().cloned().collect(),
+                    equipment: equipment
+                        .0
+                        .iter()
+                        .filter_map(|(slot, item)| {
+                            WorldStateItem::new(*item, &items).map(|item| (*slot, item))
+                        })
+                        .collect(),
                 };
 
-                let mut characters = world_state.characters.clone();
-
-                if let Some(index) = characters.iter().position(|c| c.id == character.id) {
-                    characters[index] = state;
-                } else {
-                    characters.push(state);
-                }
-
-                let state = WorldState { characters };
+                // Keep the in-memory state current so logging back in before
+                // the next save restores what was just stored.
+                world_state.merge_character(state.clone());
 
                 bevy.spawn(SaveCharacterTask(spawn_save_character_task(
                     database.0.clone(),
@@ -95,13 +120,26 @@ pub fn on_network_event(
     Ok(())
 }
 
+/// Merges the character into the latest save rather than writing out the
+/// rest of the world as it was last snapshotted, which would lose whatever's
+/// been dropped since and save whatever's been picked up twice.
 fn spawn_save_character_task(
     pool: Pool<Postgres>,
-    state: WorldState,
+    character: WorldStateCharacter,
 ) -> Task<Result<WorldState, sqlx::Error>> {
     AsyncComputeTaskPool::get().spawn(async move {
         let mut transaction = pool.begin().await?;
 
+        let latest = sqlx::query_as::<_, WorldSaveModel>(
+            "SELECT * FROM world_saves ORDER BY id DESC LIMIT 1",
+        )
+        .fetch_optional(&mut *transaction)
+        .await?;
+
+        let mut state = latest.map(|save| save.state.0).unwrap_or_default();
+
+        state.merge_character(character);
+
         store_world_state(&state, &mut transaction).await?;
 
         transaction.commit().await?;
@@ -109,3 +147,94 @@ fn spawn_save_character_task(
         Ok(state)
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        interact::{commands::take::take, components::Interaction},
+        items::components::ItemId,
+        test::{
+            app_builder::AppBuilder,
+            item_builder::ItemBuilder,
+            player_builder::PlayerBuilder,
+            tile_builder::{TileBuilder, ZoneBuilder},
+            utils::{get_task, send_message, wait_for_task},
+        },
+        world::resources::WorldStateDroppedItem,
+    };
+
+    #[sqlx::test]
+    async fn take_then_disconnect(pool: PgPool) -> sqlx::Result<()> {
+        let mut app = AppBuilder::new().database(&pool).build();
+        app.add_systems(Update, (take, on_network_event));
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+        app.world.entity_mut(tile).insert(Name::new("Tile"));
+
+        let id = Uuid::new_v4();
+
+        let stick = ItemBuilder::new()
+            .name("stick")
+            .interactions(vec![Interaction::Take])
+            .tile(tile)
+            .build(&mut app);
+
+        app.world
+            .entity_mut(stick)
+            .insert((Name::new("stick"), ItemId(id)));
+
+        let saved = WorldState {
+            characters: vec![],
+            corpses: vec![],
+            items: vec![WorldStateDroppedItem {
+                tile: "Tile".into(),
+                surface: None,
+                item: WorldStateItem {
+                    id,
+                    prototype: "stick".into(),
+                    state: Default::default(),
+                    contents: vec![],
+                },
+                cleanup: None,
+            }],
+        };
+
+        let mut transaction = pool.begin().await?;
+        store_world_state(&saved, &mut transaction).await?;
+        transaction.commit().await?;
+
+        *app.world.resource_mut::<WorldState>() = saved;
+
+        let (_, client_id, _) = PlayerBuilder::new()
+            .id(1)
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        send_message(&mut app, client_id, "take stick");
+        app.update();
+
+        app.world
+            .resource_mut::<Events<NetworkEvent>>()
+            .send(NetworkEvent::Disconnected(client_id));
+        app.update();
+
+        wait_for_task(&get_task::<SaveCharacterTask>(&mut app).unwrap().0);
+
+        let latest = sqlx::query_as::<_, WorldSaveModel>(
+            "SELECT * FROM world_saves ORDER BY id DESC LIMIT 1",
+        )
+        .fetch_one(&pool)
+        .await?;
+
+        assert_eq!(latest.state.characters[0].inventory[0].id, id);
+        assert!(latest.state.items.is_empty());
+
+        Ok(())
+    }
+}
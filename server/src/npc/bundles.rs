@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use bevy::prelude::*;
 use bevy_proto::prelude::*;
 
@@ -6,6 +8,7 @@ use crate::{
         bundles::CombatBundle,
         components::{Stats, Status},
     },
+    data::resources::Balance,
     interact::components::Interactions,
     visual::components::Depiction,
 };
@@ -35,17 +38,23 @@ impl Schematic for HostileBundle {
     type Input = Self;
 
     fn apply(input: &Self::Input, context: &mut SchematicContext) {
+        // Vitals come from the loaded balance, not the one the input was built with.
+        let stats = Stats {
+            balance: Arc::new(context.world().resource::<Balance>().clone()),
+            ..input.combat.stats.clone()
+        };
+
         if let Some(mut entity) = context.entity_mut() {
             entity.insert((
                 input.hostile.clone(),
                 CombatBundle {
                     stats: Stats {
                         status: Status {
-                            health: input.combat.stats.max_health(),
-                            vigor: input.combat.stats.max_vigor(),
+                            health: stats.max_health(),
+                            vigor: stats.max_vigor(),
                             ..Default::default()
                         },
-                        ..input.combat.stats.clone()
+                        ..stats
                     },
                     cooldowns: input.combat.cooldowns.clone(),
                     conditions: input.combat.conditions.clone(),
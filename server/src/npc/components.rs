@@ -1,4 +1,4 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use bevy_proto::prelude::*;
 
 #[derive(Component, Reflect)]
@@ -8,6 +8,37 @@ pub struct Npc;
 pub struct Hostile {
     pub auto_attack: String,
     pub skills: Vec<String>,
+    #[reflect(default)]
+    pub behavior: Behavior,
+    #[reflect(default)]
+    pub faction: Option<String>,
+    #[reflect(default)]
+    pub aggression: Option<Aggression>,
+    /// The loot table rolled into its corpse when it dies.
+    #[reflect(default)]
+    pub loot: Option<String>,
+}
+
+/// How a hostile carries itself once it's in a fight.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Behavior {
+    /// Closes in and keeps swinging, raising its guard when badly hurt.
+    #[default]
+    Aggressive,
+    /// Keeps its distance, dodges now and then and runs when it's losing.
+    Skittish,
+    /// Fights from afar and backs off whenever its target gets close.
+    Kiter,
+}
+
+/// Makes a hostile attack players on its own. Anyone within `radius` tiles
+/// is hunted down, then attacked once they've lingered for `grace` seconds,
+/// or the balance's grace period when it isn't set.
+#[derive(Reflect, Clone, Debug)]
+pub struct Aggression {
+    pub radius: u32,
+    #[reflect(default)]
+    pub grace: Option<f32>,
 }
 
 #[derive(Component, Reflect)]
@@ -25,3 +56,23 @@ pub struct HostileSpawner {
 
 #[derive(Component)]
 pub struct HostileSpawnTimer(pub Timer);
+
+#[derive(Component)]
+pub struct HostileAiTimer(pub Timer);
+
+/// What an aggressive hostile has noticed: the players it's giving a moment
+/// before attacking, and when it next moves towards someone further away.
+#[derive(Component)]
+pub struct Awareness {
+    pub noticed: HashMap<Entity, Timer>,
+    pub step: Timer,
+}
+
+impl Awareness {
+    pub fn new(step: f32) -> Self {
+        Self {
+            noticed: HashMap::new(),
+            step: Timer::from_seconds(step, TimerMode::Repeating),
+        }
+    }
+}
@@ -11,11 +11,23 @@ impl Plugin for NpcPlugin {
             .register_type::<Npc>()
             .register_type::<Friendly>()
             .register_type::<Hostile>()
+            .register_type::<Behavior>()
+            .register_type::<Aggression>()
+            .register_type::<Option<Aggression>>()
+            .register_type::<Option<String>>()
             .register_type::<NpcBundle>()
             .register_type::<FriendlyBundle>()
             .register_type::<HostileBundle>()
             .register_type::<HostileSpawner>();
 
-        app.add_systems(Update, handle_hostile_spawner);
+        app.add_systems(
+            Update,
+            (
+                handle_hostile_spawner,
+                handle_hostile_ai,
+                handle_hostile_aggression,
+                handle_hostile_assist,
+            ),
+        );
     }
 }
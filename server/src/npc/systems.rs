@@ -1,11 +1,36 @@
-use bevy::prelude::*;
+use anyhow::Context;
+use bevy::{ecs::query::WorldQuery, prelude::*};
 use bevy_mod_sysfail::sysfail;
+use bevy_nest::prelude::*;
 use bevy_proto::prelude::*;
-use rand::{thread_rng, Rng};
+use rand::{seq::SliceRandom, Rng};
 
-use crate::spatial::components::Tile;
+use crate::{
+    combat::{
+        components::{
+            Approach, BlockCooldown, CombatState, Conditions, Cooldowns, Distance, DodgeCooldown,
+            FleeTimer, ManualBlock, ManualDodge, Repositioning, Stats,
+        },
+        events::{CombatEvent, CombatEventKind, CombatEventTrigger, SetApproach},
+        utils::engage,
+    },
+    data::{
+        self,
+        resources::{Balance, ControlFlag, Skill, Skills},
+    },
+    player::components::{Client, Online},
+    spatial::{
+        components::{Door, Position, Tile, Zone},
+        utils::{adjacent_tile, direction_for_offset, offset_for_direction, DIRECTIONS},
+    },
+    values::HOSTILE_AI_TICK,
+    visual::components::Depiction,
+    world::resources::GameRng,
+};
 
-use super::components::{HostileSpawnTimer, HostileSpawner};
+use super::components::{
+    Awareness, Behavior, Hostile, HostileAiTimer, HostileSpawnTimer, HostileSpawner, Npc,
+};
 
 #[sysfail(log)]
 pub fn handle_hostile_spawner(
@@ -20,6 +45,7 @@ pub fn handle_hostile_spawner(
     prototypes: Prototypes,
     tiles: Query<Entity, With<Tile>>,
     time: Res<Time>,
+    mut rng: ResMut<GameRng>,
 ) -> Result<(), anyhow::Error> {
     for (entity, tile, mut spawner, timer) in spawners.iter_mut() {
         if !prototypes.is_ready(&spawner.hostiles.0) {
@@ -29,14 +55,14 @@ pub fn handle_hostile_spawner(
         let tile = tiles.get(tile.get())?;
 
         if !spawner.initial_spawn {
-            spawn_hostiles(&mut bevy, &mut proto, &mut spawner, entity, tile);
+            spawn_hostiles(&mut bevy, &mut proto, &mut rng, &mut spawner, entity, tile);
 
             spawner.initial_spawn = true;
         }
 
         if let Some(mut timer) = timer {
             if timer.0.tick(time.delta()).just_finished() {
-                spawn_hostiles(&mut bevy, &mut proto, &mut spawner, entity, tile);
+                spawn_hostiles(&mut bevy, &mut proto, &mut rng, &mut spawner, entity, tile);
             }
         } else if spawner.spawned.is_empty() {
             bevy.entity(entity)
@@ -50,6 +76,7 @@ pub fn handle_hostile_spawner(
     fn spawn_hostiles(
         bevy: &mut Commands,
         proto: &mut ProtoCommands,
+        rng: &mut GameRng,
         spawner: &mut HostileSpawner,
         entity: Entity,
         tile: Entity,
@@ -58,7 +85,6 @@ pub fn handle_hostile_spawner(
             return;
         }
 
-        let mut rng = thread_rng();
         let range = spawner.hostiles.1..=spawner.hostiles.2;
         let amount = rng.gen_range(range);
 
@@ -74,3 +100,914 @@ pub fn handle_hostile_spawner(
 
     Ok(())
 }
+
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+pub struct HostileAiQuery {
+    entity: Entity,
+    hostile: &'static Hostile,
+    depiction: &'static Depiction,
+    stats: &'static Stats,
+    cooldowns: &'static Cooldowns,
+    combat_state: &'static CombatState,
+    tile: &'static Parent,
+    timer: Option<&'static mut HostileAiTimer>,
+    dodge_cooldown: Option<&'static DodgeCooldown>,
+    block_cooldown: Option<&'static BlockCooldown>,
+    flee_timer: Option<&'static FleeTimer>,
+    repositioning: Option<&'static Repositioning>,
+    conditions: Option<&'static Conditions>,
+}
+
+#[derive(Debug, PartialEq)]
+enum AiAction {
+    UseSkill(String),
+    Turn(Entity),
+    SetDistance(Distance),
+    Dodge,
+    Block,
+    Flee,
+    Wait,
+}
+
+/// Gives hostiles in combat a turn every so often to use a skill, change
+/// distance, guard themselves or run, all by way of the same combat events
+/// players trigger. Anyone who gets behind them is dealt with first. Auto
+/// attacks carry on regardless.
+#[sysfail(log)]
+pub fn handle_hostile_ai(
+    mut bevy: Commands,
+    mut hostiles: Query<HostileAiQuery>,
+    mut combat_events: EventWriter<CombatEvent>,
+    mut outbox: EventWriter<Outbox>,
+    clients: Query<&Client>,
+    attackers: Query<&CombatState, Without<Hostile>>,
+    skills: Res<Skills>,
+    conditions: Res<data::resources::Conditions>,
+    tiles: Query<(Entity, &Position, &Parent, Option<&Children>), With<Tile>>,
+    zones: Query<&Children, With<Zone>>,
+    doors: Query<&Door>,
+    time: Res<Time>,
+    balance: Res<Balance>,
+    mut rng: ResMut<GameRng>,
+) -> Result<(), anyhow::Error> {
+    for hostile in hostiles.iter_mut() {
+        let Some(mut timer) = hostile.timer else {
+            bevy.entity(hostile.entity)
+                .insert(HostileAiTimer(Timer::from_seconds(
+                    HOSTILE_AI_TICK,
+                    TimerMode::Repeating,
+                )));
+
+            continue;
+        };
+
+        if !timer.0.tick(time.delta()).just_finished() || hostile.stats.status.health == 0 {
+            continue;
+        }
+
+        let target = clients.get(hostile.combat_state.target).ok();
+        let name = &hostile.depiction.short_name;
+
+        let flanker = hostile
+            .combat_state
+            .threat
+            .0
+            .keys()
+            .copied()
+            .find(|opponent| {
+                attackers.get(*opponent).is_ok_and(|combat_state| {
+                    combat_state.target == hostile.entity && combat_state.approach == Approach::Rear
+                })
+            });
+
+        let action = match flanker {
+            Some(flanker) => AiAction::Turn(flanker),
+            None => decide(
+                hostile.hostile,
+                hostile.stats,
+                hostile.cooldowns,
+                hostile.combat_state,
+                &skills,
+                // Not every turn, or it'd spend the whole fight dodging.
+                hostile.dodge_cooldown.is_none() && rng.gen::<f32>() < balance.hostile_dodge_chance,
+                hostile.block_cooldown.is_none(),
+                hostile.flee_timer.is_none(),
+                &balance,
+            ),
+        };
+
+        let flags = hostile
+            .conditions
+            .map(|applied| applied.flags(&conditions))
+            .unwrap_or_default();
+
+        if is_restricted(&action, &flags) {
+            continue;
+        }
+
+        match action {
+            AiAction::UseSkill(id) => {
+                let skill = skills.0.get(&id).cloned().context("Skill not found")?;
+
+                combat_events.send(CombatEvent {
+                    source: hostile.entity,
+                    trigger: CombatEventTrigger::Skill(skill),
+                    kind: CombatEventKind::Attack,
+                });
+            }
+            AiAction::Turn(flanker) => {
+                combat_events.send(CombatEvent {
+                    source: hostile.entity,
+                    trigger: CombatEventTrigger::Movement,
+                    kind: CombatEventKind::SetApproach(SetApproach {
+                        target: flanker,
+                        approach: Approach::Front,
+                    }),
+                });
+
+                if let Ok(client) = clients.get(flanker) {
+                    outbox.send_text(client.id, format!("The {name} turns to face you."));
+                }
+            }
+            AiAction::SetDistance(distance) => {
+                // Already on its way somewhere.
+                if hostile.repositioning.is_some() {
+                    continue;
+                }
+
+                combat_events.send(CombatEvent {
+                    source: hostile.entity,
+                    trigger: CombatEventTrigger::Movement,
+                    kind: CombatEventKind::AttemptReposition(distance),
+                });
+            }
+            AiAction::Dodge => {
+                bevy.entity(hostile.entity).insert((
+                    ManualDodge(Timer::from_seconds(
+                        balance.manual_dodge_timer,
+                        TimerMode::Once,
+                    )),
+                    DodgeCooldown(Timer::from_seconds(
+                        hostile.stats.dodge_cooldown(),
+                        TimerMode::Once,
+                    )),
+                ));
+
+                if let Some(client) = target {
+                    outbox.send_text(client.id, format!("The {name} prepares to dodge."));
+                }
+            }
+            AiAction::Block => {
+                bevy.entity(hostile.entity).insert((
+                    ManualBlock(Timer::from_seconds(
+                        balance.manual_block_timer,
+                        TimerMode::Once,
+                    )),
+                    BlockCooldown(Timer::from_seconds(
+                        hostile.stats.block_cooldown(),
+                        TimerMode::Once,
+                    )),
+                ));
+
+                if let Some(client) = target {
+                    outbox.send_text(client.id, format!("The {name} prepares to block."));
+                }
+            }
+            AiAction::Flee => {
+                let exits = DIRECTIONS
+                    .iter()
+                    .filter(|direction| {
+                        offset_for_direction(direction)
+                            .and_then(|offset| {
+                                adjacent_tile(&tiles, &zones, &doors, hostile.tile.get(), offset)
+                            })
+                            .is_some()
+                    })
+                    .collect::<Vec<_>>();
+
+                // Cornered, it has no choice but to keep fighting.
+                let Some(direction) = exits.choose(&mut *rng) else {
+                    continue;
+                };
+
+                combat_events.send(CombatEvent {
+                    source: hostile.entity,
+                    trigger: CombatEventTrigger::Movement,
+                    kind: CombatEventKind::AttemptFlee(direction.to_string()),
+                });
+            }
+            AiAction::Wait => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Hostiles are held to the same crowd control as players, so a stunned
+/// hostile loses its turn and a rooted one can't reposition.
+fn is_restricted(action: &AiAction, flags: &[ControlFlag]) -> bool {
+    flags.iter().any(|flag| match flag {
+        ControlFlag::Stunned => *action != AiAction::Wait,
+        ControlFlag::Rooted => matches!(action, AiAction::SetDistance(_) | AiAction::Flee),
+        ControlFlag::Silenced => matches!(action, AiAction::UseSkill(_)),
+        ControlFlag::Disarmed => false,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn decide(
+    hostile: &Hostile,
+    stats: &Stats,
+    cooldowns: &Cooldowns,
+    combat_state: &CombatState,
+    skills: &Skills,
+    can_dodge: bool,
+    can_block: bool,
+    can_flee: bool,
+    balance: &Balance,
+) -> AiAction {
+    let health = stats.status.health as f32 / stats.max_health().max(1) as f32;
+    let near = combat_state.distance == Distance::Near;
+
+    let skill = best_skill(hostile, stats, cooldowns, skills, combat_state)
+        .map(|skill| AiAction::UseSkill(skill.id.clone()))
+        .unwrap_or(AiAction::Wait);
+
+    match hostile.behavior {
+        Behavior::Aggressive => {
+            if !near {
+                AiAction::SetDistance(Distance::Near)
+            } else if health < balance.aggressive_block_threshold && can_block {
+                AiAction::Block
+            } else {
+                skill
+            }
+        }
+        Behavior::Skittish => {
+            if health <= balance.skittish_flee_threshold && can_flee {
+                AiAction::Flee
+            } else if can_dodge {
+                AiAction::Dodge
+            } else if near {
+                AiAction::SetDistance(Distance::Far)
+            } else {
+                skill
+            }
+        }
+        Behavior::Kiter => {
+            let ranged = hostile.skills.iter().any(|id| {
+                skills
+                    .0
+                    .get(id)
+                    .is_some_and(|skill| skill.distance == Distance::Far)
+            });
+
+            if near && ranged {
+                AiAction::SetDistance(Distance::Far)
+            } else if near && can_dodge {
+                AiAction::Dodge
+            } else {
+                skill
+            }
+        }
+    }
+}
+
+/// The most expensive skill the hostile can use right now, if any. Reactions
+/// look after themselves.
+fn best_skill<'a>(
+    hostile: &Hostile,
+    stats: &Stats,
+    cooldowns: &Cooldowns,
+    skills: &'a Skills,
+    combat_state: &CombatState,
+) -> Option<&'a Skill> {
+    hostile
+        .skills
+        .iter()
+        .filter_map(|id| skills.0.get(id))
+        .filter(|skill| skill.reaction.is_none())
+        .filter(|skill| skill.reaches(combat_state.distance))
+        .filter(|skill| skill.approach.allows(combat_state.approach))
+        .filter(|skill| skill.cost <= stats.status.vigor)
+        .filter(|skill| !cooldowns.0.contains_key(&skill.id))
+        .max_by_key(|skill| skill.cost)
+}
+
+/// Aggressive hostiles keep an eye out for players. Anyone within their radius
+/// is tracked down one tile at a time, and anyone sharing their tile is
+/// attacked once the grace period runs out.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_hostile_aggression(
+    mut bevy: Commands,
+    mut outbox: EventWriter<Outbox>,
+    mut hostiles: Query<
+        (
+            Entity,
+            &Hostile,
+            &Depiction,
+            &Stats,
+            &Parent,
+            Option<&mut Awareness>,
+        ),
+        (With<Npc>, Without<CombatState>),
+    >,
+    mut players: Query<
+        (Entity, &Client, &Stats, &Parent, Option<&mut CombatState>),
+        (With<Online>, Without<Npc>),
+    >,
+    tiles: Query<(Entity, &Position, &Parent, Option<&Children>), With<Tile>>,
+    zones: Query<&Children, With<Zone>>,
+    doors: Query<&Door>,
+    time: Res<Time>,
+    balance: Res<Balance>,
+) {
+    for (entity, hostile, depiction, stats, tile, awareness) in hostiles.iter_mut() {
+        let Some(aggression) = &hostile.aggression else {
+            continue;
+        };
+
+        if stats.status.health == 0 {
+            continue;
+        }
+
+        let Some(mut awareness) = awareness else {
+            bevy.entity(entity)
+                .insert(Awareness::new(balance.aggression_step));
+
+            continue;
+        };
+
+        let Ok((_, position, zone, _)) = tiles.get(tile.get()) else {
+            continue;
+        };
+
+        let mut nearby = players
+            .iter()
+            .filter(|(_, _, player_stats, _, _)| {
+                player_stats.status.health > 0
+                    && player_stats.level <= stats.level + balance.aggression_level_difference
+            })
+            .filter_map(|(player, _, _, player_tile, _)| {
+                let (_, player_position, player_zone, _) = tiles.get(player_tile.get()).ok()?;
+
+                (player_zone.get() == zone.get())
+                    .then(|| (player, player_position.0 - position.0))
+                    .filter(|(_, offset)| offset.abs().max_element() as u32 <= aggression.radius)
+            })
+            .collect::<Vec<_>>();
+
+        nearby.sort_by_key(|(player, offset)| (offset.abs().max_element(), *player));
+
+        awareness
+            .noticed
+            .retain(|player, _| nearby.iter().any(|(p, o)| p == player && *o == IVec3::ZERO));
+
+        let mut attacked = None;
+
+        for (player, _) in nearby.iter().filter(|(_, offset)| *offset == IVec3::ZERO) {
+            let timer = awareness.noticed.entry(*player).or_insert_with(|| {
+                if let Ok((_, client, _, _, _)) = players.get(*player) {
+                    outbox.send_text(
+                        client.id,
+                        format!("The {} notices you.", depiction.short_name),
+                    );
+                }
+
+                Timer::from_seconds(
+                    aggression.grace.unwrap_or(balance.aggression_grace_period),
+                    TimerMode::Once,
+                )
+            });
+
+            if timer.tick(time.delta()).finished() {
+                attacked = Some(*player);
+
+                break;
+            }
+        }
+
+        if let Some(player) = attacked {
+            let Ok((_, client, _, _, combat_state)) = players.get_mut(player) else {
+                continue;
+            };
+
+            engage(&mut bevy, player, combat_state, entity, Distance::Near);
+
+            bevy.entity(entity)
+                .insert(CombatState::new(player, Distance::Near))
+                .remove::<Awareness>();
+
+            outbox.send_text(
+                client.id,
+                format!("The {} attacks you!", depiction.short_name),
+            );
+
+            continue;
+        }
+
+        let Some((_, offset)) = nearby.first().filter(|(_, offset)| *offset != IVec3::ZERO) else {
+            continue;
+        };
+
+        if !awareness.step.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let step = offset.signum();
+
+        let Some((destination, direction)) =
+            [step, step * IVec3::X, step * IVec3::Y, step * IVec3::Z]
+                .into_iter()
+                .filter(|step| *step != IVec3::ZERO)
+                .find_map(|step| {
+                    adjacent_tile(&tiles, &zones, &doors, tile.get(), step)
+                        .zip(direction_for_offset(step))
+                })
+        else {
+            continue;
+        };
+
+        announce(&mut outbox, &tiles, &players, tile.get(), || {
+            format!("The {} heads {direction}.", depiction.short_name)
+        });
+
+        announce(&mut outbox, &tiles, &players, destination, || {
+            format!("The {} arrives.", depiction.short_name)
+        });
+
+        bevy.entity(entity).set_parent(destination);
+    }
+
+    fn announce(
+        outbox: &mut EventWriter<Outbox>,
+        tiles: &Query<(Entity, &Position, &Parent, Option<&Children>), With<Tile>>,
+        players: &Query<
+            (Entity, &Client, &Stats, &Parent, Option<&mut CombatState>),
+            (With<Online>, Without<Npc>),
+        >,
+        tile: Entity,
+        message: impl Fn() -> String,
+    ) {
+        let Ok((_, _, _, Some(children))) = tiles.get(tile) else {
+            return;
+        };
+
+        for (_, client, _, _, _) in children.iter().filter_map(|child| players.get(*child).ok()) {
+            outbox.send_text(client.id, message());
+        }
+    }
+}
+
+/// Hostiles join a fight already under way on their tile when one of their
+/// own faction is in it.
+pub fn handle_hostile_assist(
+    mut bevy: Commands,
+    mut outbox: EventWriter<Outbox>,
+    fighting: Query<(&Hostile, &Parent, &CombatState), With<Npc>>,
+    idle: Query<(Entity, &Hostile, &Depiction, &Stats, &Parent), (With<Npc>, Without<CombatState>)>,
+    mut players: Query<(&Client, Option<&mut CombatState>), Without<Npc>>,
+) {
+    for (entity, hostile, depiction, stats, tile) in idle.iter() {
+        let Some(faction) = &hostile.faction else {
+            continue;
+        };
+
+        if stats.status.health == 0 {
+            continue;
+        }
+
+        let Some((_, _, ally)) = fighting.iter().find(|(other, other_tile, _)| {
+            other_tile.get() == tile.get() && other.faction.as_ref() == Some(faction)
+        }) else {
+            continue;
+        };
+
+        let Ok((client, combat_state)) = players.get_mut(ally.target) else {
+            continue;
+        };
+
+        engage(&mut bevy, ally.target, combat_state, entity, ally.distance);
+
+        bevy.entity(entity)
+            .insert(CombatState::new(ally.target, ally.distance));
+
+        outbox.send_text(
+            client.id,
+            format!("The {} joins the fight!", depiction.short_name),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::data::resources::SkillApproach;
+    use crate::test::{
+        app_builder::AppBuilder,
+        npc_builder::NpcBuilder,
+        player_builder::PlayerBuilder,
+        tile_builder::{TileBuilder, ZoneBuilder},
+    };
+
+    fn skill(id: &str, cost: u32, distance: Distance) -> Skill {
+        Skill {
+            id: id.into(),
+            commands: vec![],
+            name: id.into(),
+            description: "".into(),
+            cost,
+            cooldown: 0,
+            distance,
+            dodge_difficulty: 0.0,
+            block_difficulty: 0.0,
+            approach: SkillApproach::Any,
+            reaction: None,
+            ammo: None,
+            scripts: vec![],
+        }
+    }
+
+    fn hostile(behavior: Behavior) -> Hostile {
+        Hostile {
+            auto_attack: "kick".into(),
+            skills: vec!["bite".into(), "maul".into(), "spit".into()],
+            behavior,
+            faction: None,
+            aggression: None,
+            loot: None,
+        }
+    }
+
+    fn setup() -> (Skills, Stats) {
+        let mut skills = Skills::default();
+
+        for skill in [
+            skill("bite", 0, Distance::Near),
+            skill("maul", 20, Distance::Near),
+            skill("spit", 5, Distance::Far),
+        ] {
+            skills.0.insert(skill.id.clone(), skill);
+        }
+
+        let mut stats = Stats::default();
+        stats.status.health = stats.max_health();
+        stats.status.vigor = 10;
+
+        (skills, stats)
+    }
+
+    #[test]
+    fn picks_best_affordable_skill_in_range() {
+        let (skills, stats) = setup();
+        let hostile = hostile(Behavior::Aggressive);
+        let mut cooldowns = Cooldowns::default();
+        let near = CombatState::new(Entity::PLACEHOLDER, Distance::Near);
+        let far = CombatState::new(Entity::PLACEHOLDER, Distance::Far);
+
+        let best = best_skill(&hostile, &stats, &cooldowns, &skills, &near);
+        assert_eq!(best.map(|s| s.id.as_str()), Some("bite"));
+
+        let best = best_skill(&hostile, &stats, &cooldowns, &skills, &far);
+        assert_eq!(best.map(|s| s.id.as_str()), Some("spit"));
+
+        cooldowns.0.insert(
+            "spit".into(),
+            (
+                Entity::PLACEHOLDER,
+                Timer::from_seconds(1.0, TimerMode::Once),
+            ),
+        );
+
+        let best = best_skill(&hostile, &stats, &cooldowns, &skills, &far);
+        assert!(best.is_none());
+    }
+
+    #[test]
+    fn aggressive_closes_distance() {
+        let (skills, stats) = setup();
+        let combat_state = CombatState::new(Entity::PLACEHOLDER, Distance::Far);
+
+        let action = decide(
+            &hostile(Behavior::Aggressive),
+            &stats,
+            &Cooldowns::default(),
+            &combat_state,
+            &skills,
+            true,
+            true,
+            true,
+            &Balance::default(),
+        );
+
+        assert_eq!(action, AiAction::SetDistance(Distance::Near));
+    }
+
+    #[test]
+    fn skittish_flees_when_hurt() {
+        let (skills, mut stats) = setup();
+        stats.status.health = 1;
+
+        let combat_state = CombatState::new(Entity::PLACEHOLDER, Distance::Near);
+
+        let action = decide(
+            &hostile(Behavior::Skittish),
+            &stats,
+            &Cooldowns::default(),
+            &combat_state,
+            &skills,
+            true,
+            true,
+            true,
+            &Balance::default(),
+        );
+
+        assert_eq!(action, AiAction::Flee);
+    }
+
+    #[test]
+    fn kiter_backs_off() {
+        let (skills, stats) = setup();
+        let combat_state = CombatState::new(Entity::PLACEHOLDER, Distance::Near);
+
+        let action = decide(
+            &hostile(Behavior::Kiter),
+            &stats,
+            &Cooldowns::default(),
+            &combat_state,
+            &skills,
+            true,
+            true,
+            true,
+            &Balance::default(),
+        );
+
+        assert_eq!(action, AiAction::SetDistance(Distance::Far));
+    }
+
+    #[test]
+    fn crowd_control_restricts_actions() {
+        let rooted = [ControlFlag::Rooted];
+
+        assert!(is_restricted(&AiAction::Flee, &rooted));
+        assert!(!is_restricted(&AiAction::Block, &rooted));
+
+        let stunned = [ControlFlag::Stunned];
+
+        assert!(is_restricted(&AiAction::Dodge, &stunned));
+        assert!(!is_restricted(&AiAction::Wait, &stunned));
+    }
+
+    #[test]
+    fn hostile_advances_through_combat_events() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, handle_hostile_ai);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (player, _, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+
+        let hostile = NpcBuilder::new()
+            .combat(true)
+            .behavior(Behavior::Aggressive)
+            .tile(tile)
+            .build(&mut app);
+
+        let mut stats = app.world.get_mut::<Stats>(hostile).unwrap();
+        stats.status.health = stats.max_health();
+
+        let mut timer = Timer::from_seconds(HOSTILE_AI_TICK, TimerMode::Repeating);
+        timer.set_elapsed(Duration::from_secs_f32(HOSTILE_AI_TICK));
+
+        app.world.entity_mut(hostile).insert((
+            HostileAiTimer(timer),
+            CombatState::new(player, Distance::Far),
+        ));
+
+        app.world
+            .entity_mut(player)
+            .insert(CombatState::new(hostile, Distance::Far));
+
+        app.update();
+
+        let events = app.world.resource::<Events<CombatEvent>>();
+        let mut reader = events.get_reader();
+
+        let advanced = reader.iter(events).any(|event| {
+            event.source == hostile
+                && matches!(
+                    &event.kind,
+                    CombatEventKind::AttemptReposition(Distance::Near)
+                )
+        });
+
+        assert!(advanced);
+    }
+
+    #[test]
+    fn hostile_turns_to_face_flanker() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, handle_hostile_ai);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (player, _, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+
+        let hostile = NpcBuilder::new()
+            .combat(true)
+            .behavior(Behavior::Aggressive)
+            .tile(tile)
+            .build(&mut app);
+
+        let mut stats = app.world.get_mut::<Stats>(hostile).unwrap();
+        stats.status.health = stats.max_health();
+
+        let mut timer = Timer::from_seconds(HOSTILE_AI_TICK, TimerMode::Repeating);
+        timer.set_elapsed(Duration::from_secs_f32(HOSTILE_AI_TICK));
+
+        app.world.entity_mut(hostile).insert((
+            HostileAiTimer(timer),
+            CombatState::new(player, Distance::Near),
+        ));
+
+        let mut combat_state = CombatState::new(hostile, Distance::Near);
+        combat_state.approach = Approach::Rear;
+
+        app.world.entity_mut(player).insert(combat_state);
+
+        app.update();
+
+        let events = app.world.resource::<Events<CombatEvent>>();
+        let mut reader = events.get_reader();
+
+        let turned = reader.iter(events).any(|event| {
+            event.source == hostile
+                && matches!(
+                    &event.kind,
+                    CombatEventKind::SetApproach(args)
+                        if args.target == player && args.approach == Approach::Front
+                )
+        });
+
+        assert!(turned);
+    }
+
+    fn revive(app: &mut App, entity: Entity) {
+        let mut stats = app.world.get_mut::<Stats>(entity).unwrap();
+        stats.status.health = stats.max_health();
+    }
+
+    #[test]
+    fn aggressive_hostile_attacks_after_grace() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, handle_hostile_aggression);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (player, _, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+
+        let hostile = NpcBuilder::new()
+            .combat(true)
+            .aggression(0, 0.0)
+            .tile(tile)
+            .build(&mut app);
+
+        for entity in [player, hostile] {
+            revive(&mut app, entity);
+        }
+
+        app.update();
+        app.update();
+
+        assert_eq!(
+            app.world.get::<CombatState>(hostile).unwrap().target,
+            player
+        );
+        assert_eq!(
+            app.world.get::<CombatState>(player).unwrap().target,
+            hostile
+        );
+    }
+
+    #[test]
+    fn aggression_ignores_much_higher_levels() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, handle_hostile_aggression);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (player, _, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+
+        let hostile = NpcBuilder::new()
+            .combat(true)
+            .aggression(0, 0.0)
+            .tile(tile)
+            .build(&mut app);
+
+        app.world.get_mut::<Stats>(player).unwrap().level =
+            Balance::default().aggression_level_difference + 1;
+
+        for entity in [player, hostile] {
+            revive(&mut app, entity);
+        }
+
+        app.update();
+        app.update();
+
+        assert!(app.world.get::<CombatState>(hostile).is_none());
+    }
+
+    #[test]
+    fn aggressive_hostile_hunts_within_radius() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, handle_hostile_aggression);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let start = TileBuilder::new()
+            .position(IVec3::ZERO)
+            .build(&mut app, zone);
+        let destination = TileBuilder::new()
+            .position(IVec3::new(0, 1, 0))
+            .build(&mut app, zone);
+
+        let (player, _, _) = PlayerBuilder::new().tile(destination).build(&mut app);
+
+        let hostile = NpcBuilder::new()
+            .combat(true)
+            .aggression(1, 3.0)
+            .tile(start)
+            .build(&mut app);
+
+        for entity in [player, hostile] {
+            revive(&mut app, entity);
+        }
+
+        let mut awareness = Awareness::new(Balance::default().aggression_step);
+        awareness.step.set_elapsed(awareness.step.duration());
+
+        app.world.entity_mut(hostile).insert(awareness);
+
+        app.update();
+
+        assert_eq!(app.world.get::<Parent>(hostile).unwrap().get(), destination);
+    }
+
+    #[test]
+    fn same_faction_joins_the_fight() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, handle_hostile_assist);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let (player, _, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+
+        let fighting = NpcBuilder::new()
+            .combat(true)
+            .faction("vermin")
+            .tile(tile)
+            .build(&mut app);
+
+        let ally = NpcBuilder::new()
+            .combat(true)
+            .faction("vermin")
+            .tile(tile)
+            .build(&mut app);
+
+        let stranger = NpcBuilder::new()
+            .combat(true)
+            .faction("bandits")
+            .tile(tile)
+            .build(&mut app);
+
+        for entity in [fighting, ally, stranger] {
+            revive(&mut app, entity);
+        }
+
+        app.world
+            .entity_mut(fighting)
+            .insert(CombatState::new(player, Distance::Near));
+
+        app.world
+            .entity_mut(player)
+            .insert(CombatState::new(fighting, Distance::Near));
+
+        app.update();
+
+        assert_eq!(app.world.get::<CombatState>(ally).unwrap().target, player);
+        assert!(app.world.get::<CombatState>(stranger).is_none());
+        assert!(app
+            .world
+            .get::<CombatState>(player)
+            .unwrap()
+            .threat
+            .contains(&ally));
+    }
+}
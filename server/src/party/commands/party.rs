@@ -0,0 +1,516 @@
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use bevy::prelude::*;
+use bevy_mod_sysfail::sysfail;
+use bevy_nest::prelude::*;
+use regex::Regex;
+
+use crate::{
+    input::events::{Command, ParseError, ParsedCommand, PartyAction},
+    party::components::Party,
+    player::components::{Character, Client, Online},
+    values::PARTY_SIZE_CAP,
+};
+
+static REGEX: OnceLock<Regex> = OnceLock::new();
+
+pub fn handle_party(content: &str) -> Result<Command, ParseError> {
+    let regex = REGEX.get_or_init(|| {
+        Regex::new(r"^party( (?P<action>invite|accept|leave|kick)( (?P<target>.*))?)?$").unwrap()
+    });
+
+    match regex.captures(content) {
+        None => Err(ParseError::WrongCommand),
+        Some(captures) => {
+            let target = captures
+                .name("target")
+                .map(|m| m.as_str().trim())
+                .filter(|m| !m.is_empty());
+
+            // Anything that doesn't look like a party command falls through
+            // to the party chat channel.
+            match (captures.name("action").map(|m| m.as_str()), target) {
+                (None, _) => Ok(Command::Party(PartyAction::List)),
+                (Some("invite"), Some(target)) => {
+                    Ok(Command::Party(PartyAction::Invite(target.into())))
+                }
+                (Some("invite"), None) => Err(ParseError::InvalidArguments("Invite whom?".into())),
+                (Some("kick"), Some(target)) => {
+                    Ok(Command::Party(PartyAction::Kick(target.into())))
+                }
+                (Some("kick"), None) => Err(ParseError::InvalidArguments("Kick whom?".into())),
+                (Some("accept"), None) => Ok(Command::Party(PartyAction::Accept)),
+                (Some("leave"), None) => Ok(Command::Party(PartyAction::Leave)),
+                _ => Err(ParseError::WrongCommand),
+            }
+        }
+    }
+}
+
+#[sysfail(log)]
+pub fn party(
+    mut bevy: Commands,
+    mut commands: EventReader<ParsedCommand>,
+    mut outbox: EventWriter<Outbox>,
+    players: Query<(&Client, &Character), With<Online>>,
+    mut parties: Query<(Entity, &mut Party)>,
+) -> Result<(), anyhow::Error> {
+    for command in commands.iter() {
+        if let Command::Party(action) = &command.command {
+            let (client, character) = players
+                .iter()
+                .find(|(c, _)| c.id == command.from)
+                .context("Player not found")?;
+
+            match action {
+                PartyAction::List => {
+                    let Some((_, party)) = parties.iter().find(|(_, p)| p.contains(character.id))
+                    else {
+                        outbox.send_text(client.id, "You're not in a party.");
+
+                        continue;
+                    };
+
+                    let members = party
+                        .members
+                        .iter()
+                        .map(|member| {
+                            let mut name = member.name.clone();
+
+                            if party.is_leader(member.id) {
+                                name.push_str(" (leader)");
+                            }
+
+                            if !players.iter().any(|(_, c)| c.id == member.id) {
+                                name.push_str(" (offline)");
+                            }
+
+                            name
+                        })
+                        .collect::<Vec<_>>();
+
+                    outbox.send_text(client.id, format!("Party: {}", members.join(", ")));
+                }
+                PartyAction::Invite(name) => {
+                    let Some((target_client, target)) = players
+                        .iter()
+                        .find(|(_, c)| c.name.eq_ignore_ascii_case(name))
+                    else {
+                        outbox.send_text(client.id, format!("No one named {name} is online."));
+
+                        continue;
+                    };
+
+                    if target.id == character.id {
+                        outbox.send_text(client.id, "You can't invite yourself.");
+
+                        continue;
+                    }
+
+                    if parties.iter().any(|(_, p)| p.contains(target.id)) {
+                        outbox.send_text(
+                            client.id,
+                            format!("{} is already in a party.", target.name),
+                        );
+
+                        continue;
+                    }
+
+                    match parties.iter_mut().find(|(_, p)| p.contains(character.id)) {
+                        Some((_, mut party)) => {
+                            if !party.is_leader(character.id) {
+                                outbox.send_text(client.id, "Only the party leader can invite.");
+
+                                continue;
+                            }
+
+                            if party.members.len() >= PARTY_SIZE_CAP {
+                                outbox.send_text(client.id, "Your party is full.");
+
+                                continue;
+                            }
+
+                            if party.invites.contains(&target.id) {
+                                outbox.send_text(
+                                    client.id,
+                                    format!("You've already invited {}.", target.name),
+                                );
+
+                                continue;
+                            }
+
+                            party.invites.push(target.id);
+                        }
+                        None => {
+                            let mut party = Party::new(character);
+                            party.invites.push(target.id);
+
+                            bevy.spawn(party);
+                        }
+                    }
+
+                    outbox.send_text(
+                        client.id,
+                        format!("You invite {} to join your party.", target.name),
+                    );
+
+                    outbox.send_text(
+                        target_client.id,
+                        format!(
+                            "{} invites you to join their party. Type 'party accept' to join.",
+                            character.name
+                        ),
+                    );
+                }
+                PartyAction::Accept => {
+                    if parties.iter().any(|(_, p)| p.contains(character.id)) {
+                        outbox.send_text(client.id, "You're already in a party.");
+
+                        continue;
+                    }
+
+                    let Some((_, mut party)) = parties
+                        .iter_mut()
+                        .find(|(_, p)| p.invites.contains(&character.id))
+                    else {
+                        outbox.send_text(client.id, "You haven't been invited to a party.");
+
+                        continue;
+                    };
+
+                    if party.members.len() >= PARTY_SIZE_CAP {
+                        party.invites.retain(|id| *id != character.id);
+                        outbox.send_text(client.id, "That party is full.");
+
+                        continue;
+                    }
+
+                    notify(
+                        &mut outbox,
+                        &players,
+                        &party,
+                        format!("{} joins the party.", character.name),
+                    );
+
+                    party.join(character);
+
+                    outbox.send_text(
+                        client.id,
+                        format!(
+                            "You join {}'s party.",
+                            party.leader_name().unwrap_or("someone")
+                        ),
+                    );
+                }
+                PartyAction::Leave => {
+                    let Some((entity, mut party)) =
+                        parties.iter_mut().find(|(_, p)| p.contains(character.id))
+                    else {
+                        outbox.send_text(client.id, "You're not in a party.");
+
+                        continue;
+                    };
+
+                    outbox.send_text(client.id, "You leave the party.");
+
+                    let new_leader = party.remove(character.id);
+
+                    notify(
+                        &mut outbox,
+                        &players,
+                        &party,
+                        format!("{} leaves the party.", character.name),
+                    );
+
+                    after_departure(&mut bevy, &mut outbox, &players, entity, &party, new_leader);
+                }
+                PartyAction::Kick(name) => {
+                    let Some((entity, mut party)) =
+                        parties.iter_mut().find(|(_, p)| p.contains(character.id))
+                    else {
+                        outbox.send_text(client.id, "You're not in a party.");
+
+                        continue;
+                    };
+
+                    if !party.is_leader(character.id) {
+                        outbox.send_text(client.id, "Only the party leader can kick members.");
+
+                        continue;
+                    }
+
+                    let Some(member) = party
+                        .members
+                        .iter()
+                        .find(|m| m.name.eq_ignore_ascii_case(name))
+                        .cloned()
+                    else {
+                        outbox.send_text(client.id, format!("{name} isn't in your party."));
+
+                        continue;
+                    };
+
+                    if member.id == character.id {
+                        outbox.send_text(client.id, "You can't kick yourself, try leaving.");
+
+                        continue;
+                    }
+
+                    if let Some((kicked, _)) = players.iter().find(|(_, c)| c.id == member.id) {
+                        outbox.send_text(kicked.id, "You have been kicked from the party.");
+                    }
+
+                    let new_leader = party.remove(member.id);
+
+                    notify(
+                        &mut outbox,
+                        &players,
+                        &party,
+                        format!("{} has been kicked from the party.", member.name),
+                    );
+
+                    after_departure(&mut bevy, &mut outbox, &players, entity, &party, new_leader);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn notify(
+    outbox: &mut EventWriter<Outbox>,
+    players: &Query<(&Client, &Character), With<Online>>,
+    party: &Party,
+    message: String,
+) {
+    for (client, _) in players.iter().filter(|(_, c)| party.contains(c.id)) {
+        outbox.send_text(client.id, message.clone());
+    }
+}
+
+fn after_departure(
+    bevy: &mut Commands,
+    outbox: &mut EventWriter<Outbox>,
+    players: &Query<(&Client, &Character), With<Online>>,
+    entity: Entity,
+    party: &Party,
+    new_leader: Option<String>,
+) {
+    if party.should_disband() {
+        notify(
+            outbox,
+            players,
+            party,
+            "The party has been disbanded.".into(),
+        );
+
+        bevy.entity(entity).despawn();
+    } else if let Some(leader) = new_leader {
+        notify(
+            outbox,
+            players,
+            party,
+            format!("{leader} is now the party leader."),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{
+        app_builder::AppBuilder,
+        player_builder::PlayerBuilder,
+        utils::{clear_messages, get_message_content, send_message},
+    };
+
+    fn get_party(app: &mut App) -> Option<&Party> {
+        app.world.query::<&Party>().iter(&app.world).next()
+    }
+
+    #[test]
+    fn parses() {
+        assert_eq!(
+            handle_party("party invite Bau"),
+            Ok(Command::Party(PartyAction::Invite("Bau".into())))
+        );
+
+        assert_eq!(handle_party("party"), Ok(Command::Party(PartyAction::List)));
+
+        assert_eq!(
+            handle_party("party kick"),
+            Err(ParseError::InvalidArguments("Kick whom?".into()))
+        );
+
+        assert_eq!(
+            handle_party("party leave now"),
+            Err(ParseError::WrongCommand)
+        );
+        assert_eq!(handle_party("party time!"), Err(ParseError::WrongCommand));
+    }
+
+    #[test]
+    fn invite_and_accept() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, party);
+
+        let (_, leader_id, _) = PlayerBuilder::new().name("Ashur").build(&mut app);
+        let (_, member_id, _) = PlayerBuilder::new().name("Bau").build(&mut app);
+
+        send_message(&mut app, leader_id, "party invite bau");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, member_id).unwrap(),
+            "Ashur invites you to join their party. Type 'party accept' to join."
+        );
+
+        clear_messages(&mut app);
+
+        send_message(&mut app, member_id, "party accept");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, leader_id).unwrap(),
+            "Bau joins the party."
+        );
+
+        let party = get_party(&mut app).unwrap();
+
+        assert_eq!(party.members.len(), 2);
+        assert!(party.invites.is_empty());
+    }
+
+    #[test]
+    fn accept_without_invite() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, party);
+
+        let (_, client_id, _) = PlayerBuilder::new().build(&mut app);
+
+        send_message(&mut app, client_id, "party accept");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "You haven't been invited to a party."
+        );
+    }
+
+    #[test]
+    fn only_leader_can_kick() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, party);
+
+        let (_, leader_id, _) = PlayerBuilder::new().name("Ashur").build(&mut app);
+        let (_, member_id, _) = PlayerBuilder::new().name("Bau").build(&mut app);
+
+        send_message(&mut app, leader_id, "party invite bau");
+        app.update();
+
+        clear_messages(&mut app);
+
+        send_message(&mut app, member_id, "party accept");
+        app.update();
+
+        clear_messages(&mut app);
+
+        send_message(&mut app, member_id, "party kick ashur");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, member_id).unwrap(),
+            "Only the party leader can kick members."
+        );
+    }
+
+    #[test]
+    fn leaving_a_pair_disbands() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, party);
+
+        let (_, leader_id, _) = PlayerBuilder::new().name("Ashur").build(&mut app);
+        let (_, member_id, _) = PlayerBuilder::new().name("Bau").build(&mut app);
+
+        send_message(&mut app, leader_id, "party invite bau");
+        app.update();
+
+        clear_messages(&mut app);
+
+        send_message(&mut app, member_id, "party accept");
+        app.update();
+
+        clear_messages(&mut app);
+
+        send_message(&mut app, leader_id, "party leave");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, member_id).unwrap(),
+            "Ashur leaves the party."
+        );
+
+        assert!(get_party(&mut app).is_none());
+    }
+
+    #[test]
+    fn leader_leaving_passes_leadership() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, party);
+
+        let (_, leader_id, _) = PlayerBuilder::new().name("Ashur").build(&mut app);
+        let (_, bau_id, _) = PlayerBuilder::new().name("Bau").build(&mut app);
+        let (_, cato_id, _) = PlayerBuilder::new().name("Cato").build(&mut app);
+
+        send_message(&mut app, leader_id, "party invite bau");
+        app.update();
+
+        send_message(&mut app, leader_id, "party invite cato");
+        app.update();
+
+        send_message(&mut app, bau_id, "party accept");
+        send_message(&mut app, cato_id, "party accept");
+        app.update();
+
+        clear_messages(&mut app);
+
+        send_message(&mut app, leader_id, "party leave");
+        app.update();
+
+        let party = get_party(&mut app).unwrap();
+
+        assert_eq!(party.leader_name(), Some("Bau"));
+        assert_eq!(party.members.len(), 2);
+    }
+
+    #[test]
+    fn survives_disconnect() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, party);
+
+        let (_, leader_id, _) = PlayerBuilder::new().name("Ashur").build(&mut app);
+        let (member, member_id, _) = PlayerBuilder::new().name("Bau").build(&mut app);
+
+        send_message(&mut app, leader_id, "party invite bau");
+        app.update();
+
+        clear_messages(&mut app);
+
+        send_message(&mut app, member_id, "party accept");
+        app.update();
+
+        app.world.entity_mut(member).despawn();
+
+        clear_messages(&mut app);
+
+        send_message(&mut app, leader_id, "party");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, leader_id).unwrap(),
+            "Party: Ashur (leader), Bau (offline)"
+        );
+    }
+}
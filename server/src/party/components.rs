@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+
+use crate::player::components::Character;
+
+/// A group of characters adventuring together. Lives on its own entity and
+/// tracks members by character ID so it outlives any one member's connection.
+#[derive(Component, Debug)]
+pub struct Party {
+    pub leader: i64,
+    pub members: Vec<PartyMember>,
+    pub invites: Vec<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PartyMember {
+    pub id: i64,
+    pub name: String,
+}
+
+impl Party {
+    pub fn new(leader: &Character) -> Self {
+        Self {
+            leader: leader.id,
+            members: vec![PartyMember {
+                id: leader.id,
+                name: leader.name.clone(),
+            }],
+            invites: vec![],
+        }
+    }
+
+    pub fn contains(&self, id: i64) -> bool {
+        self.members.iter().any(|member| member.id == id)
+    }
+
+    pub fn is_leader(&self, id: i64) -> bool {
+        self.leader == id
+    }
+
+    pub fn leader_name(&self) -> Option<&str> {
+        self.members
+            .iter()
+            .find(|member| member.id == self.leader)
+            .map(|member| member.name.as_str())
+    }
+
+    pub fn join(&mut self, character: &Character) {
+        self.invites.retain(|id| *id != character.id);
+        self.members.push(PartyMember {
+            id: character.id,
+            name: character.name.clone(),
+        });
+    }
+
+    /// Removes a member, handing leadership to the next in line if needed.
+    /// Returns the new leader's name when leadership changed hands.
+    pub fn remove(&mut self, id: i64) -> Option<String> {
+        self.members.retain(|member| member.id != id);
+
+        if self.leader == id {
+            if let Some(next) = self.members.first() {
+                self.leader = next.id;
+
+                return Some(next.name.clone());
+            }
+        }
+
+        None
+    }
+
+    /// A party of one isn't much of a party.
+    pub fn should_disband(&self) -> bool {
+        self.members.len() < 2
+    }
+}
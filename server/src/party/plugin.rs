@@ -0,0 +1,11 @@
+use bevy::prelude::*;
+
+use super::{commands::party::*, systems::*};
+
+pub struct PartyPlugin;
+
+impl Plugin for PartyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (party, assist_party_leader));
+    }
+}
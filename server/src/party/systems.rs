@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+use bevy_nest::prelude::*;
+
+use crate::{
+    combat::{
+        components::{CombatState, Stats},
+        utils::engage,
+    },
+    npc::components::{Hostile, Npc},
+    player::components::{Character, Client, Online},
+    visual::components::Depiction,
+};
+
+use super::components::Party;
+
+/// When a party leader enters combat with a hostile, any party members standing
+/// on the same tile who aren't already fighting join in.
+pub fn assist_party_leader(
+    mut bevy: Commands,
+    mut outbox: EventWriter<Outbox>,
+    leaders: Query<(&Character, &CombatState, &Parent), (Added<CombatState>, Without<Npc>)>,
+    members: Query<
+        (
+            Entity,
+            &Client,
+            &Character,
+            &Parent,
+            &Stats,
+            Option<&CombatState>,
+        ),
+        (With<Online>, Without<Npc>),
+    >,
+    mut hostiles: Query<(&Depiction, Option<&mut CombatState>), (With<Hostile>, With<Npc>)>,
+    parties: Query<&Party>,
+) {
+    for (leader, combat_state, leader_tile) in leaders.iter() {
+        let Some(party) = parties.iter().find(|p| p.is_leader(leader.id)) else {
+            continue;
+        };
+
+        let Ok((depiction, mut hostile_combat_state)) = hostiles.get_mut(combat_state.target)
+        else {
+            continue;
+        };
+
+        let assisting = members
+            .iter()
+            .filter(|(_, _, character, tile, stats, in_combat)| {
+                character.id != leader.id
+                    && party.contains(character.id)
+                    && tile.get() == leader_tile.get()
+                    && stats.status.health > 0
+                    && in_combat.is_none()
+            });
+
+        for (member, client, _, _, _, _) in assisting {
+            bevy.entity(member)
+                .insert(CombatState::new(combat_state.target, combat_state.distance));
+
+            engage(
+                &mut bevy,
+                combat_state.target,
+                hostile_combat_state.as_mut().map(|c| c.reborrow()),
+                member,
+                combat_state.distance,
+            );
+
+            outbox.send_text(
+                client.id,
+                format!(
+                    "You join {} in attacking the {}.",
+                    leader.name, depiction.short_name
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        combat::components::Distance,
+        party::components::Party,
+        test::{
+            app_builder::AppBuilder,
+            npc_builder::NpcBuilder,
+            player_builder::PlayerBuilder,
+            tile_builder::{TileBuilder, ZoneBuilder},
+        },
+    };
+
+    #[test]
+    fn members_on_tile_assist() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, assist_party_leader);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+        let elsewhere = TileBuilder::new().build(&mut app, zone);
+
+        let (leader, _, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+        let (member, _, _) = PlayerBuilder::new().tile(tile).build(&mut app);
+        let (absent, _, _) = PlayerBuilder::new().tile(elsewhere).build(&mut app);
+
+        for entity in [member, absent] {
+            let mut stats = app.world.get_mut::<Stats>(entity).unwrap();
+            stats.status.health = stats.max_health();
+        }
+
+        let mut party = Party::new(app.world.get::<Character>(leader).unwrap());
+
+        for entity in [member, absent] {
+            party.join(app.world.get::<Character>(entity).unwrap());
+        }
+
+        app.world.spawn(party);
+
+        let goat = NpcBuilder::new()
+            .name("Goat")
+            .combat(true)
+            .tile(tile)
+            .build(&mut app);
+
+        app.world
+            .entity_mut(goat)
+            .insert(CombatState::new(leader, Distance::Near));
+
+        app.world
+            .entity_mut(leader)
+            .insert(CombatState::new(goat, Distance::Near));
+
+        app.update();
+
+        assert_eq!(app.world.get::<CombatState>(member).unwrap().target, goat);
+        assert!(app.world.get::<CombatState>(absent).is_none());
+        assert!(app
+            .world
+            .get::<CombatState>(goat)
+            .unwrap()
+            .threat
+            .contains(&member));
+    }
+}
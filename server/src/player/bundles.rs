@@ -1,6 +1,13 @@
 use bevy::prelude::*;
 
-use crate::{combat::bundles::CombatBundle, keycard::Keycard};
+use crate::{
+    combat::{
+        bundles::CombatBundle,
+        components::{CombatMeter, LearnedSkills},
+    },
+    items::components::Equipment,
+    keycard::Keycard,
+};
 
 use super::components::Character;
 
@@ -9,4 +16,7 @@ pub struct PlayerBundle {
     pub keycard: Keycard,
     pub character: Character,
     pub combat: CombatBundle,
+    pub meter: CombatMeter,
+    pub skills: LearnedSkills,
+    pub equipment: Equipment,
 }
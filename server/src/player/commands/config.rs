@@ -65,8 +65,10 @@ pub fn config(
                     table.column(1).set_header("options");
                     table.column(2).set_header("value");
 
-                    let options: Vec<Vec<&dyn Display>> =
-                        vec![vec![&"brief", &"<true|false>", &character.config.brief]];
+                    let options: Vec<Vec<&dyn Display>> = vec![
+                        vec![&"brief", &"<true|false>", &character.config.brief],
+                        vec![&"meters", &"<true|false>", &character.config.meters],
+                    ];
 
                     outbox.send_text(client.id, table.format(options));
                 }
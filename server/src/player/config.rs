@@ -4,6 +4,8 @@ use serde_json::Value;
 #[derive(Copy, Clone, Default, Serialize, Deserialize)]
 pub struct CharacterConfig {
     pub brief: bool,
+    #[serde(default)]
+    pub meters: bool,
 }
 
 impl CharacterConfig {
@@ -13,6 +15,10 @@ impl CharacterConfig {
                 self.brief.into(),
                 "If enabled, you will only see room names when moving.",
             )),
+            "meters" => Ok((
+                self.meters.into(),
+                "If enabled, combat statistics are sent over GMCP for your client to display.",
+            )),
             _ => Err("Invalid option."),
         }
     }
@@ -26,6 +32,13 @@ impl CharacterConfig {
 
                 Ok(())
             }
+            "meters" => {
+                self.meters = value
+                    .parse()
+                    .map_err(|_| "Value must be `true` or `false`.")?;
+
+                Ok(())
+            }
             _ => Err("Invalid option."),
         }
     }
@@ -4,7 +4,8 @@ use bevy_mod_sysfail::sysfail;
 use bevy_nest::prelude::*;
 
 use crate::{
-    combat::components::{BlockCooldown, CombatState, DodgeCooldown, Stats},
+    combat::components::{BlockCooldown, CombatState, Conditions, DodgeCooldown, Stats},
+    data,
     net::telnet::NAWS,
     npc::components::Npc,
     paint,
@@ -12,7 +13,7 @@ use crate::{
 };
 
 use super::{
-    components::{Client, Online},
+    components::{Character, Client, Online},
     events::Prompt,
     resources::PromptTimer,
 };
@@ -57,15 +58,17 @@ pub fn send_prompt(
             Option<&CombatState>,
             Option<&DodgeCooldown>,
             Option<&BlockCooldown>,
+            Option<&Conditions>,
         ),
         (With<Online>, Without<Npc>),
     >,
-    npcs: Query<(&Stats, &Depiction), With<Npc>>,
+    opponents: Query<(&Stats, Option<&Depiction>, Option<&Character>)>,
+    conditions: Res<data::resources::Conditions>,
 ) -> Result<(), anyhow::Error> {
     for prompt in events.iter() {
-        let (client, stats, combat_state, dodge_cooldown, block_cooldown) = players
+        let (client, stats, combat_state, dodge_cooldown, block_cooldown, applied) = players
             .iter()
-            .find(|(c, _, _, _, _)| c.id == prompt.client_id)
+            .find(|(c, _, _, _, _, _)| c.id == prompt.client_id)
             .context("Player not found")?;
 
         let mut parts: Vec<String> = vec![];
@@ -80,17 +83,50 @@ pub fn send_prompt(
             if block_cooldown.is_none() { " b" } else { "" },
         ));
 
+        let flags = applied
+            .map(|applied| applied.flags(&conditions))
+            .unwrap_or_default();
+
+        if !flags.is_empty() {
+            let flags = flags
+                .iter()
+                .map(|flag| flag.to_string())
+                .collect::<Vec<_>>();
+
+            parts.push(paint!("<fg.yellow>({})</>", flags.join(", ")));
+        }
+
         if let Some(combat) = combat_state {
-            let (stats, depiction) = npcs.get(combat.target)?;
+            let (stats, depiction, character) = opponents.get(combat.target)?;
 
             parts.push(paint!(
                 "{} ({}, {}) [{}/<fg.red>{}</>]",
-                depiction.name,
+                opponent_name(depiction, character),
                 combat.distance,
                 combat.approach,
                 stats.status.health,
                 stats.max_health(),
             ));
+
+            let mut others = combat
+                .threat
+                .0
+                .keys()
+                .filter(|entity| **entity != combat.target)
+                .filter_map(|entity| opponents.get(*entity).ok())
+                .map(|(stats, depiction, character)| (stats, opponent_name(depiction, character)))
+                .collect::<Vec<_>>();
+
+            others.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+            for (stats, name) in others {
+                parts.push(paint!(
+                    "{} [{}/<fg.red>{}</>]",
+                    name,
+                    stats.status.health,
+                    stats.max_health(),
+                ));
+            }
         }
 
         parts.push("->".into());
@@ -101,6 +137,14 @@ pub fn send_prompt(
     Ok(())
 }
 
+/// Players fight under their own names, everything else under its depiction.
+fn opponent_name(depiction: Option<&Depiction>, character: Option<&Character>) -> String {
+    character
+        .map(|character| character.name.clone())
+        .or_else(|| depiction.map(|depiction| depiction.name.clone()))
+        .unwrap_or_else(|| "Unknown".into())
+}
+
 pub fn send_prompt_on_timer(
     mut prompts: EventWriter<Prompt>,
     mut timer: ResMut<PromptTimer>,
@@ -9,14 +9,16 @@ use regex::Regex;
 use crate::{
     input::events::{ChatChannel, Command, ParseError, ParsedCommand},
     paint,
+    party::components::Party,
     player::components::{Character, Client, Online},
 };
 
 static REGEX: OnceLock<Regex> = OnceLock::new();
 
 pub fn handle_chat(content: &str) -> Result<Command, ParseError> {
-    let regex =
-        REGEX.get_or_init(|| Regex::new(r"^(?P<channel>chat|novice)( (?P<message>.*))?$").unwrap());
+    let regex = REGEX.get_or_init(|| {
+        Regex::new(r"^(?P<channel>chat|novice|party)( (?P<message>.*))?$").unwrap()
+    });
 
     match regex.captures(content) {
         None => Err(ParseError::WrongCommand),
@@ -29,6 +31,7 @@ pub fn handle_chat(content: &str) -> Result<Command, ParseError> {
             let channel = match channel {
                 "chat" | "c" => ChatChannel::Chat,
                 "novice" | "n" => ChatChannel::Novice,
+                "party" | "p" => ChatChannel::Party,
                 _ => ChatChannel::Chat,
             };
 
@@ -48,15 +51,29 @@ pub fn chat(
     mut commands: EventReader<ParsedCommand>,
     mut outbox: EventWriter<Outbox>,
     players: Query<(&Client, &Character), With<Online>>,
+    parties: Query<&Party>,
 ) -> Result<(), anyhow::Error> {
     for command in commands.iter() {
         if let Command::Chat((channel, message)) = &command.command {
-            let (_, character) = players
+            let (client, character) = players
                 .iter()
                 .find(|(c, _)| c.id == command.from)
                 .context("Player not found")?;
 
-            for (client, other_character) in players.iter() {
+            let party = parties.iter().find(|p| p.contains(character.id));
+
+            if *channel == ChatChannel::Party && party.is_none() {
+                outbox.send_text(client.id, "You're not in a party.");
+
+                continue;
+            }
+
+            let listeners = players.iter().filter(|(_, other_character)| match channel {
+                ChatChannel::Party => party.is_some_and(|p| p.contains(other_character.id)),
+                _ => true,
+            });
+
+            for (client, other_character) in listeners {
                 let mentioned = message
                     .to_lowercase()
                     .contains(&other_character.name.to_lowercase());
@@ -172,4 +189,43 @@ mod tests {
 
         assert_eq!(content, "Say what?");
     }
+
+    #[test]
+    fn party_channel_stays_in_party() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, chat);
+
+        let (leader, leader_id, _) = PlayerBuilder::new().name("Ashur").build(&mut app);
+        let (member, member_id, _) = PlayerBuilder::new().name("Bau").build(&mut app);
+        let (_, outsider_id, _) = PlayerBuilder::new().name("Cato").build(&mut app);
+
+        let mut party = Party::new(app.world.get::<Character>(leader).unwrap());
+        party.join(app.world.get::<Character>(member).unwrap());
+        app.world.spawn(party);
+
+        send_message(&mut app, leader_id, "party Hello!");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, member_id).unwrap(),
+            "[party] Ashur: Hello!"
+        );
+        assert!(get_message_content(&mut app, outsider_id).is_none());
+    }
+
+    #[test]
+    fn party_channel_without_party() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, chat);
+
+        let (_, client_id, _) = PlayerBuilder::new().build(&mut app);
+
+        send_message(&mut app, client_id, "party Hello!");
+        app.update();
+
+        assert_eq!(
+            get_message_content(&mut app, client_id).unwrap(),
+            "You're not in a party."
+        );
+    }
 }
@@ -10,7 +10,10 @@ use regex::Regex;
 use crate::{
     input::events::{Command, ParseError, ParsedCommand},
     interact::components::Interactions,
-    items::components::{Item, Surface},
+    items::{
+        components::{Item, Stack, Surface},
+        utils::quantity,
+    },
     npc::components::Npc,
     paint,
     player::{
@@ -25,7 +28,7 @@ use crate::{
     visual::{
         components::{Depiction, Sprite},
         paint::Color,
-        utils::name_list,
+        utils::{counted_name_list, name_list},
     },
     world::resources::WorldTime,
 };
@@ -55,6 +58,7 @@ pub struct ItemQuery {
     surface: Option<&'static Surface>,
     door: Option<&'static Door>,
     children: Option<&'static Children>,
+    stack: Option<&'static Stack>,
     with_item: With<Item>,
 }
 
@@ -172,13 +176,15 @@ fn look_at_item(
                         .filter_map(|child| {
                             items.get(*child).ok().filter(|item| item.depiction.visible)
                         })
-                        .map(|item| item.depiction.short_name.clone())
-                        .collect::<Vec<String>>();
+                        .map(|item| (item.depiction.short_name.clone(), quantity(item.stack)))
+                        .collect::<Vec<_>>();
+
+                    let plural = on_surface.iter().map(|(_, quantity)| quantity).sum::<u32>() > 1;
 
                     let on_surface = if on_surface.is_empty() {
                         "".to_string()
                     } else {
-                        name_list(&on_surface, None, true)
+                        counted_name_list(&on_surface, None, true)
                     };
 
                     if on_surface.is_empty() {
@@ -188,7 +194,7 @@ fn look_at_item(
                             " {} the {} {} {}.",
                             to_title_case(&surface.kind.to_string()),
                             item.depiction.short_name,
-                            if children.len() > 1 { "are" } else { "is" },
+                            if plural { "are" } else { "is" },
                             on_surface
                         )
                     }
@@ -363,14 +369,14 @@ fn get_items_line(siblings: Option<&Children>, items: &Query<ItemQuery>) -> Stri
         .flat_map(|children| children.iter())
         .filter_map(|sibling| items.get(*sibling).ok())
         .filter(|item| item.depiction.visible)
-        .map(|item| item.depiction.short_name.clone())
-        .collect::<Vec<String>>();
+        .map(|item| (item.depiction.short_name.clone(), quantity(item.stack)))
+        .collect::<Vec<_>>();
 
     if items_found.is_empty() {
         return "".into();
     }
 
-    let item_names = name_list(&items_found, Some(Color::Item), true);
+    let item_names = counted_name_list(&items_found, Some(Color::Item), true);
 
     let formatted = format!(
         "{}{}",
@@ -381,7 +387,12 @@ fn get_items_line(siblings: Option<&Children>, items: &Query<ItemQuery>) -> Stri
     format!(
         "\n\n{} {} on the ground.",
         formatted,
-        if items_found.len() == 1 {
+        if items_found
+            .iter()
+            .map(|(_, quantity)| quantity)
+            .sum::<u32>()
+            == 1
+        {
             "lies"
         } else {
             "lie"
@@ -8,7 +8,10 @@ use regex::Regex;
 
 use crate::{
     input::events::{Command, ParseError, ParsedCommand},
-    items::components::{Inventory, Item, Surface},
+    items::{
+        components::{Inventory, Item, Stack},
+        utils::{find_path, quantified, split_path, HolderQuery},
+    },
     npc::components::Npc,
     player::components::{Character, Client, Online},
     spatial::components::Tile,
@@ -48,8 +51,8 @@ pub struct ItemQuery {
     entity: Entity,
     item: &'static Item,
     depiction: &'static Depiction,
-    surface: Option<&'static Surface>,
     children: Option<&'static Children>,
+    stack: Option<&'static Stack>,
 }
 
 #[derive(WorldQuery)]
@@ -81,6 +84,7 @@ pub fn scan(
     mut outbox: EventWriter<Outbox>,
     inventories: Query<InventoryQuery>,
     items: Query<ItemQuery>,
+    holders: Query<HolderQuery>,
     npcs: Query<NpcQuery>,
     players: Query<PlayerQuery>,
     tiles: Query<TileQuery>,
@@ -99,6 +103,7 @@ pub fn scan(
                 player.children,
                 &inventories,
                 &items,
+                &holders,
                 &tiles,
             );
 
@@ -107,8 +112,9 @@ pub fn scan(
             } else {
                 entities_to_scan
                     .iter()
-                    .filter_map(|&entity| {
+                    .filter_map(|&(entity, depth)| {
                         format_entity_description(entity, &items, &npcs, &players)
+                            .map(|description| format!("{}{description}", "  ".repeat(depth)))
                     })
                     .collect()
             };
@@ -127,34 +133,69 @@ fn collect_entities_to_scan(
     children: &Children,
     inventories: &Query<InventoryQuery>,
     items: &Query<ItemQuery>,
+    holders: &Query<HolderQuery>,
     tiles: &Query<TileQuery>,
-) -> Vec<Entity> {
+) -> Vec<(Entity, usize)> {
+    let carried = children
+        .iter()
+        .find_map(|child| inventories.get(*child).ok())
+        .map(|inventory| inventory.children.iter().copied().collect::<Vec<_>>())
+        .unwrap_or_default();
+
     // Scan player's inventory
     if *in_inventory {
-        return children
-            .iter()
-            .find_map(|child| inventories.get(*child).ok())
-            .map(|inventory| inventory.children.iter().copied().collect())
-            .unwrap_or_default();
+        return with_contents(&carried, items, 0);
     }
 
-    // Scan for a specific target
+    let nearby = tiles
+        .get(tile.get())
+        .ok()
+        .map(|tile| tile.children.iter().copied().collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    // Scan for a specific target, nearby or carried
     if let Some(target_name) = target {
-        return items
-            .iter()
-            .filter(|item| item.surface.is_some())
-            .find(|item| item.depiction.matches_query(&item.entity, target_name))
-            .and_then(|item| item.children)
-            .map(|children| children.iter().copied().collect())
+        let roots = nearby.iter().chain(carried.iter()).copied();
+
+        return find_path(&split_path(target_name), roots, holders)
+            .and_then(|path| path.last().copied())
+            .and_then(|target| holders.get(target).ok())
+            .filter(|target| target.capacity().is_some())
+            .and_then(|target| target.children)
+            .map(|children| with_contents(&children.iter().copied().collect::<Vec<_>>(), items, 0))
             .unwrap_or_else(Vec::new);
     }
 
     // Scan the current tile
-    tiles
-        .get(tile.get())
-        .ok()
-        .map(|tile| tile.children.iter().copied().collect())
-        .unwrap_or_default()
+    nearby.into_iter().map(|entity| (entity, 0)).collect()
+}
+
+/// The entities along with whatever items are inside them, and how deep
+/// each is nested.
+fn with_contents(
+    entities: &[Entity],
+    items: &Query<ItemQuery>,
+    depth: usize,
+) -> Vec<(Entity, usize)> {
+    entities
+        .iter()
+        .flat_map(|&entity| {
+            let contents = items
+                .get(entity)
+                .ok()
+                .and_then(|item| item.children)
+                .map(|children| {
+                    with_contents(
+                        &children.iter().copied().collect::<Vec<_>>(),
+                        items,
+                        depth + 1,
+                    )
+                })
+                .unwrap_or_default();
+
+            std::iter::once((entity, depth)).chain(contents)
+        })
+        .collect()
 }
 
 fn format_entity_description(
@@ -167,7 +208,7 @@ fn format_entity_description(
         return Some(format!(
             "#{}: {}",
             item.entity.index(),
-            item.depiction.short_name
+            quantified(&item.depiction.short_name, item.stack)
         ));
     }
 
@@ -300,4 +341,38 @@ mod tests {
 
         assert_eq!(content, format!("#{}: rock", rock.index()));
     }
+
+    #[test]
+    fn nested_in_inventory() {
+        let mut app = AppBuilder::new().build();
+        app.add_systems(Update, scan);
+
+        let zone = ZoneBuilder::new().build(&mut app);
+        let tile = TileBuilder::new().build(&mut app, zone);
+
+        let pouch = ItemBuilder::new()
+            .short_name("pouch")
+            .is_container(2)
+            .build(&mut app);
+        let coin = ItemBuilder::new().short_name("coin").build(&mut app);
+
+        app.world.entity_mut(pouch).add_child(coin);
+
+        let (_, client_id, inventory) = PlayerBuilder::new()
+            .tile(tile)
+            .has_inventory()
+            .build(&mut app);
+
+        app.world.entity_mut(inventory.unwrap()).add_child(pouch);
+
+        send_message(&mut app, client_id, "scan inv");
+        app.update();
+
+        let content = get_message_content(&mut app, client_id).unwrap();
+
+        assert_eq!(
+            content,
+            format!("#{}: pouch\n  #{}: coin", pouch.index(), coin.index())
+        );
+    }
 }
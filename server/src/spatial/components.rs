@@ -11,6 +11,12 @@ pub struct Zone {
     pub name: String,
 }
 
+/// A marker component for zones where players can only fight each other
+/// in a duel they've both agreed to.
+#[derive(Debug, Component, Schematic, Reflect)]
+#[reflect(Schematic)]
+pub struct NoPvp;
+
 /// A tile is a single block of the world.
 /// When moving, the player becomes a child of the tile they are moving to.
 #[derive(Component, Reflect)]
@@ -4,4 +4,4 @@ pub mod components;
 pub mod events;
 pub mod plugin;
 mod systems;
-mod utils;
+pub mod utils;
@@ -21,6 +21,7 @@ impl Plugin for SpatialPlugin {
             .register_type::<DeathSpawn>()
             .register_type::<Transition>()
             .register_type::<Zone>()
+            .register_type::<NoPvp>()
             .register_type::<Door>()
             .register_type::<TileBundle>()
             .register_type::<TransitionBundle>();
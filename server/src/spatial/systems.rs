@@ -1,29 +1,63 @@
-use anyhow::Context;
 use bevy::prelude::*;
 use bevy_mod_sysfail::sysfail;
+use bevy_nest::prelude::*;
 
 use crate::{
     input::events::{Command, ParsedCommand, ProxyCommand},
+    npc::components::Npc,
     player::components::Client,
+    visual::components::Depiction,
 };
 
-use super::events::{MovementEvent, MovementEventKind};
+use super::{
+    components::{Door, Position, Tile, Zone},
+    events::{MovementEvent, MovementEventKind},
+    utils::{adjacent_tile, offset_for_direction},
+};
 
 #[sysfail(log)]
 pub fn on_movement_event_flee(
+    mut bevy: Commands,
     mut events: EventReader<MovementEvent>,
     mut proxy: EventWriter<ProxyCommand>,
+    mut outbox: EventWriter<Outbox>,
     clients: Query<&Client>,
+    npcs: Query<(&Depiction, &Parent), With<Npc>>,
+    tiles: Query<(Entity, &Position, &Parent, Option<&Children>), With<Tile>>,
+    zones: Query<&Children, With<Zone>>,
+    doors: Query<&Door>,
 ) -> Result<(), anyhow::Error> {
     for event in events.iter() {
         match &event.kind {
             MovementEventKind::Flee(direction) => {
-                let client = clients.get(event.source).context("Client not found")?;
+                if let Ok(client) = clients.get(event.source) {
+                    proxy.send(ProxyCommand(ParsedCommand {
+                        from: client.id,
+                        command: Command::Movement((direction.clone(), true)),
+                    }));
+
+                    continue;
+                }
+
+                // NPCs don't issue commands, so they're moved here directly.
+                let (depiction, tile) = npcs.get(event.source)?;
+
+                let Some(destination) = offset_for_direction(direction)
+                    .and_then(|offset| adjacent_tile(&tiles, &zones, &doors, tile.get(), offset))
+                else {
+                    continue;
+                };
+
+                if let Ok((_, _, _, Some(siblings))) = tiles.get(tile.get()) {
+                    for client in siblings.iter().filter_map(|child| clients.get(*child).ok()) {
+                        outbox.send_text(
+                            client.id,
+                            format!("The {} flees {direction}.", depiction.short_name),
+                        );
+                    }
+                }
 
-                proxy.send(ProxyCommand(ParsedCommand {
-                    from: client.id,
-                    command: Command::Movement((direction.clone(), true)),
-                }));
+                bevy.entity(event.source).set_parent(destination);
             }
         }
     }
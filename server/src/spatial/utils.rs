@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
+use super::components::{Door, Position, Tile, Zone};
+
 pub fn offset_for_direction(direction: &str) -> Option<IVec3> {
     match direction {
         "north" | "n" => Some(IVec3::new(0, -1, 0)),
@@ -15,3 +17,54 @@ pub fn offset_for_direction(direction: &str) -> Option<IVec3> {
         _ => None,
     }
 }
+
+pub fn direction_for_offset(offset: IVec3) -> Option<&'static str> {
+    DIRECTIONS
+        .iter()
+        .find(|direction| offset_for_direction(direction) == Some(offset))
+        .copied()
+}
+
+pub static DIRECTIONS: [&str; 10] = [
+    "north",
+    "northeast",
+    "east",
+    "southeast",
+    "south",
+    "southwest",
+    "west",
+    "northwest",
+    "up",
+    "down",
+];
+
+/// Finds the tile one step from `tile` in the direction of `offset`, as long
+/// as it's in the same zone and no closed door stands in the way.
+pub fn adjacent_tile(
+    tiles: &Query<(Entity, &Position, &Parent, Option<&Children>), With<Tile>>,
+    zones: &Query<&Children, With<Zone>>,
+    doors: &Query<&Door>,
+    tile: Entity,
+    offset: IVec3,
+) -> Option<Entity> {
+    let (_, position, zone, siblings) = tiles.get(tile).ok()?;
+
+    let blocked = siblings.is_some_and(|siblings| {
+        siblings
+            .iter()
+            .filter_map(|child| doors.get(*child).ok())
+            .any(|door| door.blocks == offset && !door.is_open)
+    });
+
+    if blocked {
+        return None;
+    }
+
+    zones.get(zone.get()).ok()?.iter().find_map(|child| {
+        tiles
+            .get(*child)
+            .ok()
+            .filter(|(_, other, _, _)| other.0 == position.0 + offset)
+            .map(|(entity, _, _, _)| entity)
+    })
+}
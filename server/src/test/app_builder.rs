@@ -3,9 +3,12 @@ use bevy_nest::prelude::*;
 use sqlx::PgPool;
 
 use crate::{
-    combat::{components::Distance, events::CombatEvent},
-    data::resources::{Masteries, Mastery},
-    data::resources::{Skill, Skills},
+    combat::{
+        components::Distance,
+        events::{CombatEvent, DamageEvent},
+    },
+    data::resources::{Balance, Conditions, Masteries, Mastery, SkillNode},
+    data::resources::{Skill, SkillApproach, Skills},
     db::pool::DatabasePool,
     input::{
         events::{ParsedCommand, ProxyCommand},
@@ -13,16 +16,20 @@ use crate::{
     },
     player::events::Prompt,
     visual::paint,
-    world::resources::{WorldState, WorldTime},
+    world::resources::{GameRng, WorldState, WorldTime},
 };
 
 pub struct AppBuilder {
     database: Option<PgPool>,
+    seed: u64,
 }
 
 impl AppBuilder {
     pub fn new() -> Self {
-        Self { database: None }
+        Self {
+            database: None,
+            seed: 0,
+        }
     }
 
     pub fn database(mut self, pool: &PgPool) -> Self {
@@ -30,6 +37,11 @@ impl AppBuilder {
         self
     }
 
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
     pub fn build(self) -> App {
         paint::toggle(false);
 
@@ -47,6 +59,9 @@ impl AppBuilder {
                 distance: Distance::Near,
                 dodge_difficulty: 0.0,
                 block_difficulty: 0.0,
+                approach: SkillApproach::Any,
+                reaction: None,
+                ammo: None,
                 scripts: vec![],
             },
         );
@@ -64,7 +79,11 @@ impl AppBuilder {
                 dexterity: 0,
                 intelligence: 0,
                 auto_attack: "punch".into(),
-                skills: vec!["punch".into()],
+                skills: vec![SkillNode {
+                    skill: "punch".into(),
+                    level: 0,
+                    requires: vec![],
+                }],
             },
         );
 
@@ -75,12 +94,16 @@ impl AppBuilder {
             .insert_resource(WorldTime::default())
             .insert_resource(skills)
             .insert_resource(masteries)
+            .insert_resource(Conditions::default())
+            .insert_resource(Balance::default())
+            .insert_resource(GameRng::seeded(self.seed))
             .add_event::<Inbox>()
             .add_event::<Outbox>()
             .add_event::<ParsedCommand>()
             .add_event::<ProxyCommand>()
             .add_event::<Prompt>()
             .add_event::<CombatEvent>()
+            .add_event::<DamageEvent>()
             .add_systems(First, (parse_command, handle_proxy_command));
 
         if let Some(database) = self.database {
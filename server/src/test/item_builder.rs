@@ -1,11 +1,12 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use fake::{Dummy, Fake, Faker};
 
 use crate::{
+    data::resources::Stat,
     interact::components::{Interaction, Interactions},
     items::{
         bundles::ItemBundle,
-        components::{Item, Size, Surface, SurfaceKind},
+        components::{Container, Equippable, Item, Size, Slot, Stack, Surface, SurfaceKind},
     },
     visual::components::Depiction,
 };
@@ -24,10 +25,20 @@ pub struct ItemBuilder {
     surface_kind: Option<SurfaceKind>,
     #[dummy(expr = "None")]
     surface_capacity: Option<u8>,
+    #[dummy(expr = "None")]
+    container_capacity: Option<u8>,
+    #[dummy(expr = "None")]
+    stack: Option<Stack>,
     #[dummy(expr = "Size::Small")]
     size: Size,
     #[dummy(expr = "None")]
     tile: Option<Entity>,
+    #[dummy(expr = "None")]
+    slot: Option<Slot>,
+    #[dummy(expr = "Vec::new()")]
+    modifiers: Vec<(Stat, f32)>,
+    #[dummy(expr = "HashMap::new()")]
+    resistances: HashMap<String, u32>,
 }
 
 #[allow(dead_code)]
@@ -68,6 +79,16 @@ impl ItemBuilder {
         self
     }
 
+    pub fn is_container(mut self, capacity: u8) -> Self {
+        self.container_capacity = Some(capacity);
+        self
+    }
+
+    pub fn stack(mut self, quantity: u32, max: u32) -> Self {
+        self.stack = Some(Stack { quantity, max });
+        self
+    }
+
     pub fn size(mut self, size: Size) -> Self {
         self.size = size;
         self
@@ -78,6 +99,21 @@ impl ItemBuilder {
         self
     }
 
+    pub fn equippable(mut self, slot: Slot) -> Self {
+        self.slot = Some(slot);
+        self
+    }
+
+    pub fn modifier(mut self, stat: Stat, amount: f32) -> Self {
+        self.modifiers.push((stat, amount));
+        self
+    }
+
+    pub fn resistance(mut self, resistance: &str, amount: u32) -> Self {
+        self.resistances.insert(resistance.to_string(), amount);
+        self
+    }
+
     pub fn build(self, app: &mut App) -> Entity {
         let mut entity = app.world.spawn(ItemBundle {
             item: Item { size: self.size },
@@ -105,6 +141,22 @@ impl ItemBuilder {
             });
         }
 
+        if let Some(capacity) = self.container_capacity {
+            entity.insert(Container { capacity });
+        }
+
+        if let Some(stack) = self.stack {
+            entity.insert(stack);
+        }
+
+        if let Some(slot) = self.slot {
+            entity.insert(Equippable {
+                slot,
+                modifiers: self.modifiers,
+                resistances: self.resistances,
+            });
+        }
+
         entity.id()
     }
 }
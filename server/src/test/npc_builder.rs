@@ -6,7 +6,7 @@ use crate::{
     interact::components::{Interaction, Interactions},
     npc::{
         bundles::{HostileBundle, NpcBundle},
-        components::{Friendly, Hostile, Npc},
+        components::{Aggression, Behavior, Friendly, Hostile, Npc},
     },
     visual::components::Depiction,
 };
@@ -24,6 +24,14 @@ pub struct NpcBuilder {
     #[dummy(expr = "false")]
     combat: bool,
     skills: Vec<String>,
+    #[dummy(expr = "Behavior::default()")]
+    behavior: Behavior,
+    #[dummy(expr = "None")]
+    faction: Option<String>,
+    #[dummy(expr = "None")]
+    aggression: Option<Aggression>,
+    #[dummy(expr = "None")]
+    loot: Option<String>,
 }
 
 #[allow(dead_code)]
@@ -73,6 +81,29 @@ impl NpcBuilder {
         self
     }
 
+    pub fn behavior(mut self, behavior: Behavior) -> Self {
+        self.behavior = behavior;
+        self
+    }
+
+    pub fn faction(mut self, faction: &str) -> Self {
+        self.faction = Some(faction.into());
+        self
+    }
+
+    pub fn aggression(mut self, radius: u32, grace: f32) -> Self {
+        self.aggression = Some(Aggression {
+            radius,
+            grace: Some(grace),
+        });
+        self
+    }
+
+    pub fn loot(mut self, loot: &str) -> Self {
+        self.loot = Some(loot.into());
+        self
+    }
+
     pub fn build(self, app: &mut App) -> Entity {
         let mut entity = app.world.spawn(NpcBundle {
             npc: Npc,
@@ -99,6 +130,10 @@ impl NpcBuilder {
                 hostile: Hostile {
                     auto_attack: "kick".into(),
                     skills: self.skills,
+                    behavior: self.behavior,
+                    faction: self.faction,
+                    aggression: self.aggression,
+                    loot: self.loot,
                 },
                 combat: CombatBundle::default(),
             },));
@@ -5,8 +5,11 @@ use sqlx::{types::Json, PgPool};
 
 use crate::{
     auth::components::Authenticating,
-    combat::bundles::CombatBundle,
-    items::components::Inventory,
+    combat::{
+        bundles::CombatBundle,
+        components::{CombatMeter, LearnedSkills},
+    },
+    items::components::{Equipment, Inventory},
     keycard::Keycard,
     player::{
         bundles::PlayerBundle,
@@ -36,6 +39,8 @@ pub struct PlayerBuilder {
     has_inventory: bool,
     #[dummy(expr = "None")]
     tile: Option<Entity>,
+    #[dummy(expr = "Vec::new()")]
+    skills: Vec<String>,
 }
 
 #[allow(dead_code)]
@@ -94,6 +99,11 @@ impl PlayerBuilder {
         self
     }
 
+    pub fn skills(mut self, skills: &[&str]) -> Self {
+        self.skills = skills.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
     pub async fn store(self, pool: &PgPool) -> Result<Self, sqlx::Error> {
         sqlx::query("INSERT INTO characters (id, name, password, mastery, config) VALUES ($1, $2, $3, $4, $5)")
             .bind(&self.id)
@@ -131,6 +141,9 @@ impl PlayerBuilder {
                         name: self.name,
                     },
                     combat: CombatBundle::default(),
+                    meter: CombatMeter::default(),
+                    skills: LearnedSkills(self.skills.into_iter().collect()),
+                    equipment: Equipment::default(),
                 },
             ));
         }
@@ -8,7 +8,7 @@ use fake::{
 use crate::{
     spatial::{
         bundles::TileBundle,
-        components::{LifeSpawn, Position, Tile, Zone},
+        components::{LifeSpawn, NoPvp, Position, Tile, Zone},
     },
     visual::components::Sprite,
 };
@@ -17,6 +17,8 @@ use crate::{
 pub struct ZoneBuilder {
     #[dummy(faker = "CityName()")]
     name: String,
+    #[dummy(expr = "false")]
+    no_pvp: bool,
 }
 
 impl ZoneBuilder {
@@ -29,8 +31,19 @@ impl ZoneBuilder {
         self
     }
 
+    pub fn no_pvp(mut self) -> Self {
+        self.no_pvp = true;
+        self
+    }
+
     pub fn build(self, app: &mut App) -> Entity {
-        app.world.spawn(Zone { name: self.name }).id()
+        let mut entity = app.world.spawn(Zone { name: self.name });
+
+        if self.no_pvp {
+            entity.insert(NoPvp);
+        }
+
+        entity.id()
     }
 }
 
@@ -22,6 +22,12 @@ pub fn get_message_content(app: &mut App, to: ClientId) -> Option<String> {
         })
 }
 
+/// Drops any messages still sitting in the outbox, so the next
+/// `get_message_content` only sees what was sent afterwards.
+pub fn clear_messages(app: &mut App) {
+    app.world.resource_mut::<Events<Outbox>>().clear();
+}
+
 pub fn get_command_content(app: &mut App, to: ClientId) -> Option<Vec<u8>> {
     let outbox_events = app.world.resource::<Events<Outbox>>();
     let mut outbox_reader = outbox_events.get_reader();
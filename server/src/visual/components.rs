@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 use bevy_proto::prelude::*;
+use inflector::string::pluralize::to_plural;
 
 /// What is rendered via the `map` command.
 #[derive(Component, Reflect)]
@@ -7,7 +8,7 @@ pub struct Sprite {
     pub character: String,
 }
 
-#[derive(Component, Schematic, Reflect)]
+#[derive(Component, Schematic, Reflect, Clone)]
 #[reflect(Schematic)]
 pub struct Depiction {
     pub name: String,
@@ -26,9 +27,14 @@ impl Depiction {
         {
             idx == entity.index()
         } else {
-            self.name.eq_ignore_ascii_case(query)
-                || self.short_name.eq_ignore_ascii_case(query)
-                || self.tags.contains(&query.to_lowercase())
+            let query = query.to_lowercase();
+
+            // Plurals match too, so `take 5 arrows` finds the arrows.
+            [&self.name, &self.short_name]
+                .into_iter()
+                .chain(self.tags.iter())
+                .map(|name| name.to_lowercase())
+                .any(|name| name == query || to_plural(&name) == query)
         }
     }
 }
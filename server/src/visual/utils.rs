@@ -5,12 +5,30 @@ use inflector::string::pluralize::to_plural;
 use crate::visual::paint::Color;
 
 pub fn name_list(names: &[String], color: Option<Color>, include_indefinite: bool) -> String {
-    let count_map: HashMap<String, u16> =
-        names.iter().cloned().fold(HashMap::new(), |mut map, name| {
-            *map.entry(name).or_default() += 1;
+    let counted = names
+        .iter()
+        .map(|name| (name.clone(), 1))
+        .collect::<Vec<_>>();
+
+    counted_name_list(&counted, color, include_indefinite)
+}
+
+/// Like `name_list`, for names that each stand for a quantity, such as a
+/// stack of items.
+pub fn counted_name_list(
+    names: &[(String, u32)],
+    color: Option<Color>,
+    include_indefinite: bool,
+) -> String {
+    let count_map: HashMap<String, u32> =
+        names
+            .iter()
+            .cloned()
+            .fold(HashMap::new(), |mut map, (name, quantity)| {
+                *map.entry(name).or_default() += quantity;
 
-            map
-        });
+                map
+            });
 
     let color_tag = color.map_or_else(
         || String::with_capacity(0),
@@ -22,9 +22,14 @@ impl Plugin for WorldPlugin {
             .add_systems(Last, save_world_state)
             .add_systems(
                 Update,
-                (handle_save_world_state_task, handle_load_world_state_task),
+                (
+                    handle_save_world_state_task,
+                    handle_load_world_state_task,
+                    restore_corpses,
+                    restore_dropped_items,
+                ),
             )
-            .add_systems(Startup, (load_world_state,))
+            .add_systems(Startup, (load_world_state, log_rng_seed))
             .add_systems(Update, (time, update_world_time));
 
         app.add_systems(
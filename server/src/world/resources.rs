@@ -1,8 +1,17 @@
-use std::fmt::Display;
+use std::{collections::HashMap, fmt::Display, path::Path};
 
 use bevy::prelude::*;
+use bevy_proto::prelude::*;
 use chrono::prelude::*;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    combat::components::{AppliedCondition, Conditions, Cooldowns, Modifiers, Stats},
+    data::resources::{Modifier, ModifierKind, Stat},
+    items::components::{Corpse, Item, ItemId, ItemState, PlayerCorpse, Slot},
+};
 
 #[derive(Resource)]
 pub struct SaveTimer(pub Timer);
@@ -10,13 +19,353 @@ pub struct SaveTimer(pub Timer);
 #[derive(Debug, Default, Serialize, Deserialize, Resource)]
 pub struct WorldState {
     pub characters: Vec<WorldStateCharacter>,
+    #[serde(default)]
+    pub corpses: Vec<WorldStateCorpse>,
+    #[serde(default)]
+    pub items: Vec<WorldStateDroppedItem>,
+}
+
+impl WorldState {
+    /// Puts a character's save in place of their old one. Anything they now
+    /// carry is taken out of the saved corpses and off the saved ground, so
+    /// it isn't saved twice.
+    pub fn merge_character(&mut self, character: WorldStateCharacter) {
+        let mut carried = Vec::new();
+
+        for item in character
+            .inventory
+            .iter()
+            .chain(character.equipment.iter().map(|(_, item)| item))
+        {
+            item.collect_ids(&mut carried);
+        }
+
+        self.items.retain_mut(|dropped| {
+            dropped.item.remove_contents(&carried);
+
+            !carried.contains(&dropped.item.id)
+        });
+
+        for corpse in self.corpses.iter_mut() {
+            WorldStateItem::remove_from(&mut corpse.items, &carried);
+        }
+
+        if let Some(index) = self.characters.iter().position(|c| c.id == character.id) {
+            self.characters[index] = character;
+        } else {
+            self.characters.push(character);
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorldStateCharacter {
     pub id: i64,
     pub tile: String,
-    pub inventory: Vec<String>,
+    pub inventory: Vec<WorldStateItem>,
+    #[serde(default)]
+    pub combat: Option<WorldStateCombat>,
+    #[serde(default)]
+    pub skills: Vec<String>,
+    /// Equipped items by the slot they're in.
+    #[serde(default)]
+    pub equipment: Vec<(Slot, WorldStateItem)>,
+}
+
+/// Everything needed to snapshot an item and what's inside it.
+pub type SavedItems<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Name,
+        Option<&'static ItemId>,
+        Option<&'static ItemState>,
+        Option<&'static Children>,
+    ),
+    With<Item>,
+>;
+
+/// An item instance. It's respawned from its prototype on load, then its
+/// id, persisted state and contents are laid back over the top.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "SavedItem")]
+pub struct WorldStateItem {
+    pub id: Uuid,
+    pub prototype: String,
+    /// Persisted component state, keyed by type name.
+    #[serde(default)]
+    pub state: HashMap<String, serde_json::Value>,
+    /// Items inside or on top of this one.
+    #[serde(default)]
+    pub contents: Vec<WorldStateItem>,
+}
+
+/// Saves from before item instances stored only the prototype's name, which
+/// still load as a fresh instance of it.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedItem {
+    Instance {
+        id: Uuid,
+        prototype: String,
+        #[serde(default)]
+        state: HashMap<String, serde_json::Value>,
+        #[serde(default)]
+        contents: Vec<WorldStateItem>,
+    },
+    Prototype(String),
+}
+
+impl From<SavedItem> for WorldStateItem {
+    fn from(saved: SavedItem) -> Self {
+        match saved {
+            SavedItem::Instance {
+                id,
+                prototype,
+                state,
+                contents,
+            } => Self {
+                id,
+                prototype,
+                state,
+                contents,
+            },
+            SavedItem::Prototype(name) => Self {
+                id: Uuid::new_v4(),
+                prototype: name.trim_end_matches(" (Prototype)").into(),
+                state: HashMap::new(),
+                contents: vec![],
+            },
+        }
+    }
+}
+
+impl WorldStateItem {
+    pub fn new(item: Entity, items: &SavedItems) -> Option<Self> {
+        let (name, id, state, children) = items.get(item).ok()?;
+
+        Some(Self {
+            // Items get their id the frame after they spawn, so one saved
+            // before then takes a new one now.
+            id: id.map_or_else(Uuid::new_v4, |id| id.0),
+            prototype: name.trim_end_matches(" (Prototype)").into(),
+            state: state
+                .map(|state| state.0.clone().into_iter().collect())
+                .unwrap_or_default(),
+            contents: children
+                .iter()
+                .flat_map(|children| children.iter())
+                .filter_map(|child| Self::new(*child, items))
+                .collect(),
+        })
+    }
+
+    fn collect_ids(&self, ids: &mut Vec<Uuid>) {
+        ids.push(self.id);
+
+        for item in self.contents.iter() {
+            item.collect_ids(ids);
+        }
+    }
+
+    fn remove_contents(&mut self, ids: &[Uuid]) {
+        Self::remove_from(&mut self.contents, ids);
+    }
+
+    fn remove_from(items: &mut Vec<Self>, ids: &[Uuid]) {
+        items.retain_mut(|item| {
+            item.remove_contents(ids);
+
+            !ids.contains(&item.id)
+        });
+    }
+
+    /// Spawns the item and its contents, returning the item.
+    pub fn spawn(&self, proto: &mut ProtoCommands) -> Entity {
+        let item = proto.spawn(self.prototype.as_str()).id();
+        let state = ItemState(self.state.clone().into_iter().collect());
+
+        // Queued behind the prototype so the saved state wins.
+        proto.commands().entity(item).insert(ItemId(self.id)).add(
+            move |item, world: &mut World| {
+                let registry = world.resource::<AppTypeRegistry>().clone();
+                state.restore(&mut world.entity_mut(item), &registry.read());
+            },
+        );
+
+        for content in self.contents.iter() {
+            let content = content.spawn(proto);
+            proto.commands().entity(content).set_parent(item);
+        }
+
+        item
+    }
+
+    /// The prototypes of the item and everything inside it.
+    pub fn prototypes(&self) -> Vec<&str> {
+        std::iter::once(self.prototype.as_str())
+            .chain(
+                self.contents
+                    .iter()
+                    .flat_map(|content| content.prototypes()),
+            )
+            .collect()
+    }
+}
+
+/// A snapshot of a character's combat components. Timers are stored
+/// as their remaining duration in seconds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorldStateCombat {
+    #[serde(default)]
+    pub level: u32,
+    #[serde(default)]
+    pub experience: u32,
+    pub health: u32,
+    pub vigor: u32,
+    pub conditions: HashMap<String, Option<f32>>,
+    /// Stacks per condition. Who applied them isn't kept, so every instance
+    /// of a condition comes back as one.
+    #[serde(default)]
+    pub condition_stacks: HashMap<String, u32>,
+    pub modifiers: HashMap<String, (Stat, f32)>,
+    #[serde(default)]
+    pub percent_modifiers: HashMap<String, (Stat, f32)>,
+    pub cooldowns: HashMap<String, f32>,
+}
+
+impl WorldStateCombat {
+    pub fn new(
+        stats: &Stats,
+        conditions: &Conditions,
+        modifiers: &Modifiers,
+        cooldowns: &Cooldowns,
+    ) -> Self {
+        Self {
+            level: stats.level,
+            experience: stats.status.experience,
+            health: stats.status.health,
+            vigor: stats.status.vigor,
+            conditions: conditions
+                .0
+                .iter()
+                .map(|(id, instances)| {
+                    // Keep the longest remaining, or none if any are permanent.
+                    let remaining = instances.iter().try_fold(0.0, |longest: f32, instance| {
+                        instance
+                            .duration
+                            .as_ref()
+                            .map(|timer| longest.max(timer.remaining_secs()))
+                    });
+
+                    (id.clone(), remaining)
+                })
+                .collect(),
+            condition_stacks: conditions
+                .0
+                .keys()
+                .map(|id| (id.clone(), conditions.stacks(id)))
+                .collect(),
+            modifiers: modifiers_of_kind(modifiers, ModifierKind::Flat),
+            percent_modifiers: modifiers_of_kind(modifiers, ModifierKind::Percent),
+            cooldowns: cooldowns
+                .0
+                .iter()
+                .map(|(id, (_, timer))| (id.clone(), timer.remaining_secs()))
+                .collect(),
+        }
+    }
+
+    /// Applies the snapshot to freshly built combat components. Health and vigor
+    /// are clamped to the character's maximums, counting the restored modifiers.
+    pub fn restore(
+        &self,
+        entity: Entity,
+        stats: &mut Stats,
+        conditions: &mut Conditions,
+        modifiers: &mut Modifiers,
+        cooldowns: &mut Cooldowns,
+    ) {
+        stats.level = self.level;
+        stats.status.experience = self.experience;
+        for (id, remaining) in self.conditions.iter() {
+            let mut instance = AppliedCondition::new(entity, *remaining);
+            instance.stacks = self.condition_stacks.get(id).copied().unwrap_or(1).max(1);
+
+            conditions.0.insert(id.clone(), vec![instance]);
+        }
+
+        for (id, (stat, amount)) in self.modifiers.iter() {
+            modifiers
+                .0
+                .insert(id.clone(), Modifier::flat(stat.clone(), *amount));
+        }
+
+        for (id, (stat, amount)) in self.percent_modifiers.iter() {
+            modifiers
+                .0
+                .insert(id.clone(), Modifier::percent(stat.clone(), *amount));
+        }
+
+        for (id, remaining) in self.cooldowns.iter() {
+            cooldowns.0.insert(
+                id.clone(),
+                (entity, Timer::from_seconds(*remaining, TimerMode::Once)),
+            );
+        }
+
+        stats.bonuses = modifiers.bonuses();
+        stats.status.health = self.health.clamp(1, stats.max_health());
+        stats.status.vigor = self.vigor.min(stats.max_vigor());
+    }
+}
+
+fn modifiers_of_kind(modifiers: &Modifiers, kind: ModifierKind) -> HashMap<String, (Stat, f32)> {
+    modifiers
+        .0
+        .iter()
+        .filter(|(_, modifier)| modifier.kind == kind)
+        .map(|(id, modifier)| (id.clone(), (modifier.stat.clone(), modifier.amount)))
+        .collect()
+}
+
+/// A player's corpse and what it holds. Timers are stored as their remaining
+/// duration in seconds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldStateCorpse {
+    pub owner: i64,
+    pub name: String,
+    pub tile: String,
+    pub items: Vec<WorldStateItem>,
+    pub decay: f32,
+}
+
+impl WorldStateCorpse {
+    pub fn new(
+        player_corpse: &PlayerCorpse,
+        corpse: &Corpse,
+        tile: String,
+        items: Vec<WorldStateItem>,
+    ) -> Self {
+        Self {
+            owner: player_corpse.owner,
+            name: player_corpse.name.clone(),
+            tile,
+            items,
+            decay: corpse.decay.remaining_secs(),
+        }
+    }
+}
+
+/// An item left on a tile's ground or placed on one of its surfaces.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldStateDroppedItem {
+    pub tile: String,
+    /// The name of the surface it's on, or none if it's on the ground.
+    pub surface: Option<String>,
+    pub item: WorldStateItem,
+    /// Seconds until it's cleaned up, if it ever is.
+    pub cleanup: Option<f32>,
 }
 
 #[derive(Default, Resource)]
@@ -87,3 +436,334 @@ impl WorldTime {
         self.hour > 21 || self.hour < 5
     }
 }
+
+/// Every random roll in the game goes through here, so the same seed always
+/// plays out the same way.
+#[derive(Resource)]
+pub struct GameRng {
+    pub seed: u64,
+    rng: StdRng,
+}
+
+/// What a replay file holds to reproduce a run.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+}
+
+impl GameRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Seeds from the replay file, or records a fresh seed there if there
+    /// isn't one yet so the run can be replayed later.
+    pub fn from_replay(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+
+        if path.exists() {
+            let replay = ron::from_str::<Replay>(&std::fs::read_to_string(path)?)?;
+
+            return Ok(Self::seeded(replay.seed));
+        }
+
+        let rng = Self::default();
+        std::fs::write(path, ron::to_string(&Replay { seed: rng.seed })?)?;
+
+        Ok(rng)
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::seeded(rand::random())
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combat_round_trip() {
+        let entity = Entity::from_raw(1);
+
+        let mut stats = Stats {
+            level: 3,
+            ..Default::default()
+        };
+        stats.status.experience = 55;
+        stats.status.health = 42;
+        stats.status.vigor = 7;
+
+        let mut conditions = Conditions::default();
+        let mut bleeding = AppliedCondition::new(Entity::from_raw(2), Some(10.0));
+        bleeding.stacks = 3;
+        bleeding
+            .duration
+            .as_mut()
+            .unwrap()
+            .tick(std::time::Duration::from_secs(4));
+        conditions.0.insert("bleeding".into(), vec![bleeding]);
+        conditions.0.insert(
+            "blessed".into(),
+            vec![AppliedCondition::new(Entity::from_raw(2), None)],
+        );
+
+        let mut modifiers = Modifiers::default();
+        modifiers.0.insert(
+            "blessing".into(),
+            Modifier::flat(Stat::CritStrikeChance, 0.1),
+        );
+        modifiers
+            .0
+            .insert("haste".into(), Modifier::percent(Stat::AttackSpeed, 0.2));
+
+        let mut cooldowns = Cooldowns::default();
+        cooldowns.0.insert(
+            "swift-strike".into(),
+            (entity, Timer::from_seconds(3.0, TimerMode::Once)),
+        );
+
+        let saved = WorldStateCombat::new(&stats, &conditions, &modifiers, &cooldowns);
+        let saved: WorldStateCombat =
+            serde_json::from_str(&serde_json::to_string(&saved).unwrap()).unwrap();
+
+        let mut restored_stats = Stats::default();
+        let mut restored_conditions = Conditions::default();
+        let mut restored_modifiers = Modifiers::default();
+        let mut restored_cooldowns = Cooldowns::default();
+
+        saved.restore(
+            entity,
+            &mut restored_stats,
+            &mut restored_conditions,
+            &mut restored_modifiers,
+            &mut restored_cooldowns,
+        );
+
+        assert_eq!(restored_stats.level, 3);
+        assert_eq!(restored_stats.status.experience, 55);
+        assert_eq!(restored_stats.status.health, 42);
+        assert_eq!(restored_stats.status.vigor, 7);
+
+        let bleeding = &restored_conditions.0.get("bleeding").unwrap()[0];
+        assert_eq!(bleeding.duration.as_ref().unwrap().remaining_secs(), 6.0);
+        assert_eq!(bleeding.stacks, 3);
+        assert_eq!(bleeding.source, entity);
+
+        let blessed = &restored_conditions.0.get("blessed").unwrap()[0];
+        assert!(blessed.duration.is_none());
+
+        assert_eq!(restored_modifiers.sum_stat(&Stat::CritStrikeChance), 0.1);
+        assert_eq!(
+            restored_modifiers.0.get("haste"),
+            Some(&Modifier::percent(Stat::AttackSpeed, 0.2))
+        );
+
+        let cooldown = restored_cooldowns.0.get("swift-strike").unwrap();
+        assert_eq!(cooldown.0, entity);
+        assert_eq!(cooldown.1.remaining_secs(), 3.0);
+    }
+
+    #[test]
+    fn combat_restore_clamps_vitals() {
+        let saved = WorldStateCombat {
+            health: 10_000,
+            vigor: 10_000,
+            ..Default::default()
+        };
+
+        let mut stats = Stats::default();
+
+        saved.restore(
+            Entity::from_raw(1),
+            &mut stats,
+            &mut Conditions::default(),
+            &mut Modifiers::default(),
+            &mut Cooldowns::default(),
+        );
+
+        assert_eq!(stats.status.health, stats.max_health());
+        assert_eq!(stats.status.vigor, stats.max_vigor());
+    }
+
+    #[test]
+    fn combat_restore_counts_modifiers() {
+        let base = Stats::default().max_health();
+
+        let saved = WorldStateCombat {
+            health: base + 20,
+            modifiers: HashMap::from([(Slot::Head.modifier_id(0), (Stat::Vitality, 5.0))]),
+            ..Default::default()
+        };
+
+        let mut stats = Stats::default();
+        let mut modifiers = Modifiers::default();
+
+        saved.restore(
+            Entity::from_raw(1),
+            &mut stats,
+            &mut Conditions::default(),
+            &mut modifiers,
+            &mut Cooldowns::default(),
+        );
+
+        assert!(stats.max_health() >= base + 20);
+        assert_eq!(stats.status.health, base + 20);
+    }
+
+    #[test]
+    fn deserializes_saves_without_combat() {
+        let character: WorldStateCharacter =
+            serde_json::from_str(r#"{"id": 1, "tile": "Tile", "inventory": []}"#).unwrap();
+
+        assert!(character.combat.is_none());
+    }
+
+    #[test]
+    fn merging_a_character_drops_what_they_took() {
+        let item = |id| WorldStateItem {
+            id,
+            prototype: "stick".into(),
+            state: HashMap::new(),
+            contents: vec![],
+        };
+
+        let (stick, pebble, other) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        let mut bag = item(Uuid::new_v4());
+        bag.contents = vec![item(pebble), item(other)];
+
+        let mut state = WorldState {
+            characters: vec![],
+            corpses: vec![WorldStateCorpse {
+                owner: 2,
+                name: "Bau".into(),
+                tile: "Tile".into(),
+                items: vec![item(pebble)],
+                decay: 60.0,
+            }],
+            items: vec![
+                WorldStateDroppedItem {
+                    tile: "Tile".into(),
+                    surface: None,
+                    item: item(stick),
+                    cleanup: None,
+                },
+                WorldStateDroppedItem {
+                    tile: "Tile".into(),
+                    surface: None,
+                    item: bag,
+                    cleanup: None,
+                },
+            ],
+        };
+
+        state.merge_character(WorldStateCharacter {
+            id: 1,
+            tile: "Tile".into(),
+            inventory: vec![item(stick), item(pebble)],
+            combat: None,
+            skills: vec![],
+            equipment: vec![],
+        });
+
+        assert_eq!(state.characters.len(), 1);
+        assert!(state.corpses[0].items.is_empty());
+        assert_eq!(state.items.len(), 1);
+        assert_eq!(state.items[0].item.contents.len(), 1);
+        assert_eq!(state.items[0].item.contents[0].id, other);
+    }
+
+    #[test]
+    fn loads_item_names_from_older_saves() {
+        let character: WorldStateCharacter = serde_json::from_str(
+            r#"{
+                "id": 1,
+                "tile": "Tile",
+                "inventory": ["items.matted-pelt (Prototype)"],
+                "equipment": [["Head", "items.hide-cap (Prototype)"]]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(character.inventory[0].prototype, "items.matted-pelt");
+        assert_eq!(character.equipment[0].0, Slot::Head);
+        assert_eq!(character.equipment[0].1.prototype, "items.hide-cap");
+    }
+
+    #[test]
+    fn nested_items_round_trip() {
+        let pouch = WorldStateItem {
+            id: Uuid::new_v4(),
+            prototype: "items.pouch".into(),
+            state: HashMap::from([("Charges".into(), serde_json::json!(3))]),
+            contents: vec![WorldStateItem {
+                id: Uuid::new_v4(),
+                prototype: "items.yellowed-fang".into(),
+                state: HashMap::new(),
+                contents: vec![],
+            }],
+        };
+
+        let loaded: WorldStateItem =
+            serde_json::from_str(&serde_json::to_string(&pouch).unwrap()).unwrap();
+
+        assert_eq!(loaded.id, pouch.id);
+        assert_eq!(loaded.state, pouch.state);
+        assert_eq!(loaded.contents[0].id, pouch.contents[0].id);
+        assert_eq!(
+            loaded.prototypes(),
+            vec!["items.pouch", "items.yellowed-fang"]
+        );
+    }
+
+    #[test]
+    fn deserializes_saves_without_corpses() {
+        let state: WorldState = serde_json::from_str(r#"{"characters": []}"#).unwrap();
+
+        assert!(state.corpses.is_empty());
+    }
+
+    #[test]
+    fn same_seed_same_rolls() {
+        let mut first = GameRng::seeded(7);
+        let mut second = GameRng::seeded(7);
+
+        assert_eq!(first.next_u64(), second.next_u64());
+    }
+
+    #[test]
+    fn replays_recorded_seed() {
+        let path = std::env::temp_dir().join(format!("{}.replay.ron", uuid::Uuid::new_v4()));
+
+        let recorded = GameRng::from_replay(&path).unwrap();
+        let replayed = GameRng::from_replay(&path).unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(recorded.seed, replayed.seed);
+    }
+}
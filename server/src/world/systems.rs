@@ -9,13 +9,24 @@ use futures_lite::future;
 use sqlx::{Pool, Postgres};
 
 use crate::{
+    combat::components::{Conditions, Cooldowns, LearnedSkills, Modifiers, Stats},
     db::{models::WorldSaveModel, pool::DatabasePool, utils::store_world_state},
-    items::components::{Inventory, Item},
+    items::{
+        bundles::CorpseBundle,
+        components::{Corpse, Dropped, Equipment, Inventory, PlayerCorpse, Surface},
+    },
     player::components::{Character, Online},
     spatial::components::Tile,
 };
 
-use super::resources::{SaveTimer, WorldState, WorldStateCharacter, WorldTime};
+use super::resources::{
+    GameRng, SaveTimer, SavedItems, WorldState, WorldStateCharacter, WorldStateCombat,
+    WorldStateCorpse, WorldStateDroppedItem, WorldStateItem, WorldTime,
+};
+
+pub fn log_rng_seed(rng: Res<GameRng>) {
+    info!("Seeded RNG with {}", rng.seed);
+}
 
 pub fn spawn_abyss(mut commands: ProtoCommands) {
     commands.spawn("world.abyss");
@@ -36,17 +47,44 @@ pub struct SaveWorldTask(Task<Result<WorldState, sqlx::Error>>);
 pub fn save_world_state(
     database: Res<DatabasePool>,
     inventories: Query<Option<&Children>, With<Inventory>>,
-    items: Query<&Name, With<Item>>,
+    items: SavedItems,
     mut bevy: Commands,
     mut save_timer: ResMut<SaveTimer>,
-    players: Query<(&Character, &Parent, &Children), With<Online>>,
+    players: Query<
+        (
+            &Character,
+            &Parent,
+            &Children,
+            &Stats,
+            &Conditions,
+            &Modifiers,
+            &Cooldowns,
+            (&LearnedSkills, &Equipment),
+        ),
+        With<Online>,
+    >,
+    corpses: Query<(&PlayerCorpse, &Corpse, &Parent, Option<&Children>)>,
+    pending_corpses: Query<&PendingCorpse>,
+    dropped: Query<(Entity, &Dropped, &Parent)>,
+    surfaces: Query<(&Name, &Parent), (With<Surface>, Without<Dropped>, Without<Corpse>)>,
+    pending_items: Query<&PendingDroppedItem>,
     tiles: Query<&Name, With<Tile>>,
     time: Res<Time>,
 ) -> Result<(), anyhow::Error> {
     if save_timer.0.tick(time.delta()).just_finished() {
         let mut characters: Vec<WorldStateCharacter> = Vec::new();
 
-        for (character, parent, children) in players.iter() {
+        for (
+            character,
+            parent,
+            children,
+            stats,
+            conditions,
+            modifiers,
+            cooldowns,
+            (learned, equipment),
+        ) in players.iter()
+        {
             let tile_name = tiles
                 .get(parent.get())
                 .ok()
@@ -58,25 +96,82 @@ pub fn save_world_state(
                 .find_map(|child| inventories.get(*child).ok())
                 .context("Inventory not found")?;
 
-            let items_names = inventory
+            let inventory = inventory
                 .iter()
                 .flat_map(|children| children.iter())
-                .filter_map(|child| items.get(*child).ok())
-                .map(|name| name.to_string())
+                .filter_map(|child| WorldStateItem::new(*child, &items))
                 .collect();
 
             let character = WorldStateCharacter {
                 id: character.id,
                 tile: tile_name,
-                inventory: items_names,
+                inventory,
+                combat: Some(WorldStateCombat::new(
+                    stats, conditions, modifiers, cooldowns,
+                )),
+                skills: learned.0.iter().cloned().collect(),
+                equipment: equipment
+                    .0
+                    .iter()
+                    .filter_map(|(slot, item)| {
+                        WorldStateItem::new(*item, &items).map(|item| (*slot, item))
+                    })
+                    .collect(),
             };
 
             characters.push(character);
         }
 
+        let corpses = corpses
+            .iter()
+            .filter_map(|(player_corpse, corpse, parent, children)| {
+                let tile = tiles.get(parent.get()).ok()?.to_string();
+
+                let items = children
+                    .iter()
+                    .flat_map(|children| children.iter())
+                    .filter_map(|child| WorldStateItem::new(*child, &items))
+                    .collect();
+
+                Some(WorldStateCorpse::new(player_corpse, corpse, tile, items))
+            })
+            // Corpses still waiting on their tile to spawn are kept as they were.
+            .chain(pending_corpses.iter().map(|pending| pending.0.clone()))
+            .collect();
+
+        let dropped = dropped
+            .iter()
+            .filter_map(|(entity, dropped, parent)| {
+                // Only what's on the ground or on one of the tile's own surfaces,
+                // anything else is saved with whatever it's in.
+                let (tile, surface) = if let Ok(tile) = tiles.get(parent.get()) {
+                    (tile.to_string(), None)
+                } else {
+                    let (surface, tile) = surfaces.get(parent.get()).ok()?;
+
+                    (
+                        tiles.get(tile.get()).ok()?.to_string(),
+                        Some(surface.to_string()),
+                    )
+                };
+
+                Some(WorldStateDroppedItem {
+                    tile,
+                    surface,
+                    item: WorldStateItem::new(entity, &items)?,
+                    cleanup: dropped.cleanup.as_ref().map(|timer| timer.remaining_secs()),
+                })
+            })
+            .chain(pending_items.iter().map(|pending| pending.0.clone()))
+            .collect();
+
         bevy.spawn(SaveWorldTask(spawn_save_world_state_task(
             database.0.clone(),
-            WorldState { characters },
+            WorldState {
+                characters,
+                corpses,
+                items: dropped,
+            },
         )));
     }
 
@@ -108,7 +203,11 @@ fn spawn_save_world_state_task(
             }
         }
 
-        let state = WorldState { characters };
+        let state = WorldState {
+            characters,
+            corpses: state.corpses,
+            items: state.items,
+        };
 
         store_world_state(&state, &mut transaction).await?;
 
@@ -164,6 +263,14 @@ pub fn handle_load_world_state_task(
 ) {
     for (entity, mut task) in &mut tasks {
         if let Some(Ok(state)) = future::block_on(future::poll_once(&mut task.0)) {
+            for corpse in state.corpses.iter() {
+                bevy.spawn(PendingCorpse(corpse.clone()));
+            }
+
+            for item in state.items.iter() {
+                bevy.spawn(PendingDroppedItem(item.clone()));
+            }
+
             *world_state = state;
 
             bevy.entity(entity).remove::<LoadWorldStateTask>();
@@ -171,6 +278,113 @@ pub fn handle_load_world_state_task(
     }
 }
 
+/// A saved corpse waiting for its tile and items to be ready to spawn.
+#[derive(Component)]
+pub struct PendingCorpse(pub WorldStateCorpse);
+
+pub fn restore_corpses(
+    mut bevy: Commands,
+    mut proto: ProtoCommands,
+    pending: Query<(Entity, &PendingCorpse)>,
+    prototypes: Prototypes,
+    tiles: Query<(Entity, &Name), With<Tile>>,
+) {
+    for (entity, PendingCorpse(saved)) in pending.iter() {
+        let Some((tile, _)) = tiles.iter().find(|(_, name)| {
+            name.trim_end_matches(" (Prototype)") == saved.tile.trim_end_matches(" (Prototype)")
+        }) else {
+            continue;
+        };
+
+        if !saved
+            .items
+            .iter()
+            .flat_map(|item| item.prototypes())
+            .all(|prototype| prototypes.is_ready(prototype))
+        {
+            continue;
+        }
+
+        let corpse = bevy
+            .spawn((
+                CorpseBundle::player(&saved.name, saved.owner, saved.decay),
+                PlayerCorpse {
+                    owner: saved.owner,
+                    name: saved.name.clone(),
+                },
+            ))
+            .set_parent(tile)
+            .id();
+
+        for item in saved.items.iter() {
+            let item = item.spawn(&mut proto);
+            bevy.entity(item).set_parent(corpse);
+        }
+
+        bevy.entity(entity).despawn();
+    }
+}
+
+/// A saved item waiting for its tile and prototypes to be ready to spawn.
+#[derive(Component)]
+pub struct PendingDroppedItem(pub WorldStateDroppedItem);
+
+pub fn restore_dropped_items(
+    mut bevy: Commands,
+    mut proto: ProtoCommands,
+    pending: Query<(Entity, &PendingDroppedItem)>,
+    prototypes: Prototypes,
+    tiles: Query<(Entity, &Name, Option<&Children>), With<Tile>>,
+    surfaces: Query<&Name, With<Surface>>,
+) {
+    for (entity, PendingDroppedItem(saved)) in pending.iter() {
+        let Some((tile, _, children)) = tiles.iter().find(|(_, name, _)| {
+            name.trim_end_matches(" (Prototype)") == saved.tile.trim_end_matches(" (Prototype)")
+        }) else {
+            continue;
+        };
+
+        if !saved
+            .item
+            .prototypes()
+            .iter()
+            .all(|prototype| prototypes.is_ready(*prototype))
+        {
+            continue;
+        }
+
+        // A tile's surfaces spawn with it, so one that's missing is gone from
+        // the zone and the item falls to the ground instead.
+        let parent = saved
+            .surface
+            .as_ref()
+            .and_then(|surface| {
+                children
+                    .iter()
+                    .flat_map(|children| children.iter())
+                    .find(|child| {
+                        surfaces
+                            .get(**child)
+                            .is_ok_and(|name| name.as_str() == surface)
+                    })
+                    .copied()
+            })
+            .unwrap_or(tile);
+
+        let item = saved.item.spawn(&mut proto);
+
+        bevy.entity(item)
+            .insert(Dropped {
+                cleanup: saved
+                    .cleanup
+                    .map(|seconds| Timer::from_seconds(seconds, TimerMode::Once)),
+            })
+            .set_parent(parent);
+
+        bevy.entity(entity).despawn();
+    }
+}
+
 pub fn update_world_time(mut time: ResMut<WorldTime>) {
     time.update();
 }